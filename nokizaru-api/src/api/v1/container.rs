@@ -1,11 +1,21 @@
 use std::{env, sync::Arc};
 
 use nokizaru_slack::{
-    EventService, ExecuteCommandUsecase, MessageContextService, ProcessEventUsecase,
-     SlackCommandService, slack_api::SlackApi,
+    AnswerService, ChannelAccessService, ContextAssembler, DigestService, EventService,
+    ExecuteCommandUsecase, FeedbackService,
+    InteractionService, ScheduledMessageJob, POST_MESSAGE_JOB,
+    MessageContextService, ProcessEventUsecase, ProcessInteractionUsecase,
+    SlackCommandService, SummaryService, slack_api::SlackApi, slack_toolset,
 };
 
-use nokizaru_core::AgentService;
+use nokizaru_core::{
+    llm::{token_counter_for_model, LlmStage, ModelRouter},
+    AgentService, AnswerCacheRepository, DbPool, FeedbackRepository, InteractionLogRepository, UsageLogRecorder,
+    Prefilter, UsageRepository, PromptLibrary, Redactor, ScheduledJobRepository, Scheduler,
+    Space, CHANNEL_DIGEST_JOB, PURGE_ANSWER_CACHE_JOB,
+    SpaceSettings, ToolAgent,
+};
+use uuid::Uuid;
 use serde::Deserialize;
 
 /// DIコンテナ - アプリケーション全体の依存関係を管理
@@ -14,35 +24,182 @@ pub struct AppContainer {
     // Slack Usecases
     pub process_event_usecase: Arc<ProcessEventUsecase>,
     pub execute_command_usecase: Arc<ExecuteCommandUsecase>,
+    pub process_interaction_usecase: Arc<ProcessInteractionUsecase>,
 
     // Configuration
     pub config: Arc<AppConfig>,
+    /// 登録済みスペースのID（未登録の場合は None）
+    pub space_id: Option<Uuid>,
+    pub space_settings: Arc<SpaceSettings>,
+
+    // Infrastructure
+    pub db_pool: DbPool,
+    pub usage_repository: Arc<UsageRepository>,
+    pub feedback_repository: Arc<FeedbackRepository>,
+    /// cron 式による定期実行（ループは SchedulerSettings::enabled のときだけ起動する）
+    pub scheduler: Arc<Scheduler>,
 }
 
 impl AppContainer {
-    pub fn new(config: AppConfig) -> Self {
+    pub fn new(
+        config: AppConfig,
+        db_pool: DbPool,
+        space: Option<&Space>,
+        space_settings: SpaceSettings,
+    ) -> anyhow::Result<Self> {
+        let space_id = space.map(|space| space.id);
+
         // Infrastructure層
         let slack_client = Arc::new(SlackApi::new(config.slack.bot_token.clone()));
+        // 検索・閲覧権限のチェックにはユーザートークンが必要
+        let user_token = env::var("SLACK_USER_TOKEN")
+            .map_err(|_| ConfigError::MissingEnvVar("SLACK_USER_TOKEN".to_string()))?;
+        let user_client = Arc::new(SlackApi::new(user_token));
+        let usage_repository = Arc::new(UsageRepository::new(db_pool.clone()));
+        let mut model_router = ModelRouter::from_settings(&space_settings.llm)?;
+        if space_settings.usage.enabled {
+            model_router = model_router.with_recorder(Arc::new(UsageLogRecorder::new(
+                usage_repository.clone(),
+                space_id,
+                space_settings.usage.clone(),
+            )));
+        }
+        if space_settings.redaction.enabled {
+            model_router = model_router
+                .with_redactor(Arc::new(Redactor::from_settings(&space_settings.redaction)?));
+        }
+        let model_router = Arc::new(model_router);
+        let interaction_log_repository = Arc::new(InteractionLogRepository::new(db_pool.clone()));
+        let answer_cache_repository = Arc::new(AnswerCacheRepository::new(db_pool.clone()));
+        let feedback_repository = Arc::new(FeedbackRepository::new(db_pool.clone()));
 
         // Domain Services
-        let slack_command_service = Arc::new(SlackCommandService::new());
-        let slack_context_service = Arc::new(MessageContextService::new());
-        let agent_service = Arc::new(AgentService);
-        let slack_event_service = Arc::new(EventService::new(
-            slack_context_service,
-            agent_service,
-            slack_client,
-        ));
+        let mut prompts = PromptLibrary::new().with_overrides(space_settings.prompts.clone());
+        if let Some(space) = space {
+            prompts = prompts.with_space_name(space.name.clone());
+        }
+        let agent_service =
+            Arc::new(AgentService::new(model_router.clone()).with_prompts(prompts));
+        // 回答モデルのトークナイザで見積もり、長いスレッドは要約してコンテキストに含める
+        let token_counter =
+            token_counter_for_model(model_router.model(LlmStage::Answer).unwrap_or_default());
+        let channel_access_service = Arc::new(ChannelAccessService::new(user_client.clone(), slack_client.clone()));
+        let slack_context_service = Arc::new(
+            MessageContextService::with_repository(user_client.clone())
+                .with_retrieval(space_settings.retrieval.clone())
+                .with_access(channel_access_service.clone())
+                .with_assembler(
+                    ContextAssembler::new(token_counter).with_summarizer(agent_service.clone()),
+                ),
+        );
+        let mut answer_service =
+            AnswerService::new(slack_context_service, agent_service.clone())
+                .with_settings(space_settings.answer.clone())
+                .with_verification(space_settings.verification.clone())
+                .with_language(space_settings.language.clone())
+                .with_interaction_log(interaction_log_repository.clone(), space_id)
+                .with_answer_cache(
+                    answer_cache_repository.clone(),
+                    space_id,
+                    space_settings.cache.clone(),
+                );
+        // チャンネル・スレッドの要約（履歴はボットが参加している会話から取得する）
+        let summary_service = Arc::new(
+            SummaryService::new(
+                agent_service.clone(),
+                slack_client.clone(),
+                token_counter_for_model(
+                    model_router.model(LlmStage::Summarization).unwrap_or_default(),
+                ),
+            )
+            .with_access(channel_access_service.clone())
+            .with_language(space_settings.language.clone()),
+        );
+        if space_settings.agent.enabled {
+            let tool_agent = ToolAgent::new(
+                model_router,
+                slack_toolset(user_client, channel_access_service.clone()),
+                space_settings.agent.clone(),
+            );
+            answer_service = answer_service.with_tool_agent(Arc::new(tool_agent));
+        }
+        let answer_service = Arc::new(answer_service);
+        let prefilter = Arc::new(Prefilter::new(space_settings.prefilter.clone()));
+        let scheduled_job_repository = Arc::new(ScheduledJobRepository::new(db_pool.clone()));
+        let digest_service = space_settings.digest.enabled.then(|| {
+            Arc::new(DigestService::new(
+                scheduled_job_repository.clone(),
+                summary_service.clone(),
+                slack_client.clone(),
+                space_id,
+                space_settings.digest.clone(),
+            ))
+        });
+        // 回答への 👍/👎 ボタンとリアクション（質問応答ログに紐付けて記録する）
+        let feedback_service = space_settings.feedback.enabled.then(|| {
+            Arc::new(FeedbackService::new(
+                feedback_repository.clone(),
+                interaction_log_repository,
+                slack_client.clone(),
+                space_id,
+                space_settings.feedback.clone(),
+            ))
+        });
+        let mut slack_command_service =
+            SlackCommandService::new(answer_service.clone(), slack_client.clone())
+                .with_usage(usage_repository.clone(), space_id)
+                .with_prefilter(prefilter.clone())
+                .with_summaries(summary_service.clone());
+        if let Some(digest_service) = &digest_service {
+            slack_command_service = slack_command_service.with_digests(digest_service.clone());
+        }
+        let mut interaction_service =
+            InteractionService::new(slack_client.clone()).with_summaries(summary_service);
+        let mut slack_event_service =
+            EventService::new(agent_service, answer_service, slack_client.clone())
+                .with_intent_routing(space_settings.intents.clone())
+                .with_prefilter(prefilter)
+                .with_access(channel_access_service);
+        if let Some(feedback_service) = feedback_service {
+            slack_command_service = slack_command_service.with_feedback(feedback_service.clone());
+            interaction_service = interaction_service.with_feedback(feedback_service.clone());
+            slack_event_service = slack_event_service.with_feedback(feedback_service);
+        }
+        let slack_command_service = Arc::new(slack_command_service);
+        let interaction_service = Arc::new(interaction_service);
+        let slack_event_service = Arc::new(slack_event_service);
+
+        // 定期実行ジョブ（種類ごとのハンドラー、ダイジェストの配信もここで実行する）
+        let mut scheduler = Scheduler::new(
+            scheduled_job_repository,
+            space_id,
+            space_settings.scheduler.clone(),
+        )
+        .with_handler(PURGE_ANSWER_CACHE_JOB, answer_cache_repository)
+        .with_handler(POST_MESSAGE_JOB, Arc::new(ScheduledMessageJob::new(slack_client)));
+        if let Some(digest_service) = digest_service {
+            scheduler = scheduler.with_handler(CHANNEL_DIGEST_JOB, digest_service);
+        }
+        let scheduler = Arc::new(scheduler);
 
         // Application Usecases
         let process_event_usecase = Arc::new(ProcessEventUsecase::new(slack_event_service));
         let execute_command_usecase = Arc::new(ExecuteCommandUsecase::new(slack_command_service));
+        let process_interaction_usecase =
+            Arc::new(ProcessInteractionUsecase::new(interaction_service));
 
-        Self {
+        Ok(Self {
             process_event_usecase,
             execute_command_usecase,
+            process_interaction_usecase,
             config: Arc::new(config),
-        }
+            space_id,
+            space_settings: Arc::new(space_settings),
+            db_pool,
+            usage_repository,
+            feedback_repository,
+            scheduler,
+        })
     }
 
     /// Signing Secret取得（ミドルウェア用）
@@ -56,6 +213,8 @@ pub struct AppConfig {
     pub server: ServerConfig,
     pub slack: SlackConfig,
     pub database: DatabaseConfig,
+    /// 管理用 API（/api/v1/usage など）のトークン（未設定の場合は管理用 API を無効にする）
+    pub admin_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -97,6 +256,7 @@ impl AppConfig {
                 url: env::var("DATABASE_URL")
                     .map_err(|_| ConfigError::MissingEnvVar("DATABASE_URL".to_string()))?,
             },
+            admin_token: env::var("ADMIN_API_TOKEN").ok().filter(|token| !token.is_empty()),
         })
     }
 }
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use nokizaru_core::{FeedbackBreakdown, FeedbackReasonCount};
+
+/// 回答へのフィードバックの集計条件（API DTO）
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FeedbackQueryDto {
+    /// Number of days to summarize (1-366, default 30)
+    #[param(example = 30)]
+    pub days: Option<u32>,
+}
+
+/// モデル・プロンプトのバージョンごとのフィードバック（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedbackBreakdownDto {
+    /// Model that generated the answers
+    #[schema(example = "gpt-4.1")]
+    pub model: String,
+    /// Version of the answer prompt (cited_answer or agent)
+    #[schema(example = "v2")]
+    pub prompt_version: Option<String>,
+    pub helpful: i64,
+    pub not_helpful: i64,
+}
+
+impl From<FeedbackBreakdown> for FeedbackBreakdownDto {
+    fn from(row: FeedbackBreakdown) -> Self {
+        Self {
+            model: row.model,
+            prompt_version: row.prompt_version,
+            helpful: row.helpful,
+            not_helpful: row.not_helpful,
+        }
+    }
+}
+
+/// 役に立たなかった理由ごとの件数（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedbackReasonCountDto {
+    /// Reason chosen in the feedback modal (incorrect, outdated, irrelevant, incomplete, other)
+    #[schema(example = "outdated")]
+    pub reason: String,
+    pub count: i64,
+}
+
+impl From<FeedbackReasonCount> for FeedbackReasonCountDto {
+    fn from(row: FeedbackReasonCount) -> Self {
+        Self {
+            reason: row.reason,
+            count: row.count,
+        }
+    }
+}
+
+/// 回答へのフィードバックの集計結果（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeedbackSummaryDto {
+    /// Number of days summarized
+    #[schema(example = 30)]
+    pub days: u32,
+    /// Ratings from 👍 buttons and reactions
+    pub helpful: i64,
+    /// Ratings from 👎 buttons and reactions
+    pub not_helpful: i64,
+    /// Share of helpful ratings (null when there is no feedback)
+    #[schema(example = 0.82)]
+    pub helpfulness: Option<f64>,
+    /// Ratings per model and prompt version, most rated first
+    pub breakdown: Vec<FeedbackBreakdownDto>,
+    /// Reasons given for unhelpful answers, most common first
+    pub reasons: Vec<FeedbackReasonCountDto>,
+}
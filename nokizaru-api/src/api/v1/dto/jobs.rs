@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use nokizaru_core::ScheduledJob;
+
+/// 定期実行ジョブ（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduledJobDto {
+    pub id: Uuid,
+    #[schema(example = "Purge answer cache")]
+    pub name: String,
+    /// Job kind, i.e. the handler that runs it
+    #[schema(example = "purge_answer_cache")]
+    pub kind: String,
+    /// Cron expression (minute hour day month weekday, or with a leading seconds field)
+    #[schema(example = "0 4 * * *")]
+    pub cron: String,
+    /// IANA time zone the cron expression is evaluated in
+    #[schema(example = "Asia/Tokyo")]
+    pub time_zone: String,
+    /// Parameters passed to the handler
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    /// What to do with runs missed while the server was down (run_once, skip)
+    #[schema(example = "run_once")]
+    pub missed_run_policy: String,
+    pub paused: bool,
+    pub next_run_at: chrono::DateTime<chrono::Utc>,
+    pub last_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Error of the last run (null if it succeeded)
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ScheduledJob> for ScheduledJobDto {
+    fn from(job: ScheduledJob) -> Self {
+        Self {
+            id: job.id,
+            name: job.name,
+            kind: job.kind,
+            cron: job.cron,
+            time_zone: job.time_zone,
+            payload: job.payload,
+            missed_run_policy: job.missed_run_policy,
+            paused: job.paused,
+            next_run_at: job.next_run_at,
+            last_run_at: job.last_run_at,
+            last_error: job.last_error,
+            created_at: job.created_at,
+        }
+    }
+}
+
+/// 定期実行ジョブの一覧（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduledJobListDto {
+    /// Jobs ordered by next run
+    pub jobs: Vec<ScheduledJobDto>,
+    /// Job kinds that can be registered
+    #[schema(example = json!(["post_message", "purge_answer_cache"]))]
+    pub kinds: Vec<String>,
+}
+
+/// 定期実行ジョブの登録（API DTO）
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateScheduledJobDto {
+    #[schema(example = "Weekly reminder")]
+    pub name: String,
+    #[schema(example = "post_message")]
+    pub kind: String,
+    #[schema(example = "0 9 * * Mon")]
+    pub cron: String,
+    /// IANA time zone (defaults to the space's scheduler time zone)
+    #[schema(example = "Asia/Tokyo")]
+    pub time_zone: Option<String>,
+    /// Parameters passed to the handler
+    #[schema(value_type = Object, example = json!({"channel_id": "C01234ABC56", "text": "週次の定例です"}))]
+    #[serde(default)]
+    pub payload: serde_json::Value,
+    /// run_once (default) or skip
+    #[schema(example = "run_once")]
+    pub missed_run_policy: Option<String>,
+}
@@ -1,7 +1,13 @@
 pub mod agent;
 pub mod error;
+pub mod feedback;
+pub mod jobs;
 pub mod slack;
+pub mod usage;
 
 pub use agent::*;
 pub use error::*;
+pub use feedback::*;
+pub use jobs::*;
 pub use slack::*;
+pub use usage::*;
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -72,3 +74,110 @@ impl SlackCommandResponseDto {
         }
     }
 }
+
+/// Slackインタラクションリクエスト（API DTO）
+///
+/// ボタン操作などのペイロードは `payload` フィールドにJSON文字列として送られます。
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SlackInteractionDto {
+    /// JSON-encoded interaction payload
+    #[schema(example = r#"{"type":"block_actions","user":{"id":"U01234ABC56"},"actions":[]}"#)]
+    pub payload: String,
+}
+
+/// Slackインタラクションのペイロード
+#[derive(Debug, Deserialize)]
+pub struct SlackInteractionPayloadDto {
+    #[serde(rename = "type")]
+    pub interaction_type: String,
+    pub user: SlackIdDto,
+    #[serde(default)]
+    pub channel: Option<SlackIdDto>,
+    #[serde(default)]
+    pub actions: Vec<SlackActionDto>,
+    #[serde(default)]
+    pub response_url: Option<String>,
+    /// ショートカットの callback_id（message_action のみ）
+    #[serde(default)]
+    pub callback_id: Option<String>,
+    /// ショートカットを実行したメッセージ（message_action のみ）
+    #[serde(default)]
+    pub message: Option<SlackShortcutMessageDto>,
+    /// モーダルを開くための trigger_id
+    #[serde(default)]
+    pub trigger_id: Option<String>,
+    /// 送信されたモーダル（view_submission のみ）
+    #[serde(default)]
+    pub view: Option<SlackViewDto>,
+}
+
+/// 送信されたモーダル
+#[derive(Debug, Deserialize)]
+pub struct SlackViewDto {
+    pub callback_id: String,
+    #[serde(default)]
+    pub private_metadata: String,
+    #[serde(default)]
+    pub state: SlackViewStateDto,
+}
+
+/// モーダルの入力値（block_id → action_id → 入力値）
+#[derive(Debug, Default, Deserialize)]
+pub struct SlackViewStateDto {
+    #[serde(default)]
+    pub values: BTreeMap<String, BTreeMap<String, SlackViewInputDto>>,
+}
+
+/// モーダルの入力欄の値
+#[derive(Debug, Deserialize)]
+pub struct SlackViewInputDto {
+    /// テキスト入力の値
+    #[serde(default)]
+    pub value: Option<String>,
+    /// セレクトメニューで選ばれた選択肢
+    #[serde(default)]
+    pub selected_option: Option<SlackSelectedOptionDto>,
+}
+
+/// セレクトメニューで選ばれた選択肢
+#[derive(Debug, Deserialize)]
+pub struct SlackSelectedOptionDto {
+    pub value: String,
+}
+
+impl SlackViewDto {
+    /// 入力欄の action_id → 入力値（未入力の欄は含めない）
+    pub fn input_values(self) -> BTreeMap<String, String> {
+        self.state
+            .values
+            .into_values()
+            .flatten()
+            .filter_map(|(action_id, input)| {
+                let value = input.selected_option.map(|option| option.value).or(input.value)?;
+                Some((action_id, value))
+            })
+            .collect()
+    }
+}
+
+/// ショートカットを実行したメッセージ
+#[derive(Debug, Deserialize)]
+pub struct SlackShortcutMessageDto {
+    pub ts: String,
+    #[serde(default)]
+    pub thread_ts: Option<String>,
+}
+
+/// IDのみを持つSlackオブジェクト
+#[derive(Debug, Deserialize)]
+pub struct SlackIdDto {
+    pub id: String,
+}
+
+/// ブロックアクション
+#[derive(Debug, Deserialize)]
+pub struct SlackActionDto {
+    pub action_id: String,
+    #[serde(default)]
+    pub value: Option<String>,
+}
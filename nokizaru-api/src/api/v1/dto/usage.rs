@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use nokizaru_core::{DailyUsage, StageUsage};
+
+/// LLM 利用量の集計条件（API DTO）
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UsageQueryDto {
+    /// Number of days to summarize, including today (UTC, 1-366, default 30)
+    #[param(example = 30)]
+    pub days: Option<u32>,
+
+    /// Only count calls made for this channel
+    #[param(example = "C01234ABC56")]
+    pub channel_id: Option<String>,
+
+    /// Only count calls made for this user
+    #[param(example = "U01234ABC56")]
+    pub user_id: Option<String>,
+}
+
+/// 日ごとの LLM 利用量（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailyUsageDto {
+    /// Day (UTC)
+    #[schema(value_type = String, format = Date, example = "2026-10-18")]
+    pub day: chrono::NaiveDate,
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Cost in USD computed from the price table
+    #[schema(example = 0.0123)]
+    pub cost_usd: f64,
+    /// Calls to models missing from the price table (not included in cost)
+    pub unpriced_calls: i64,
+}
+
+impl From<DailyUsage> for DailyUsageDto {
+    fn from(usage: DailyUsage) -> Self {
+        Self {
+            day: usage.day,
+            calls: usage.calls,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost_usd: usage.cost_usd,
+            unpriced_calls: usage.unpriced_calls,
+        }
+    }
+}
+
+/// ステージ・モデルごとの LLM 利用量（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StageUsageDto {
+    /// Pipeline stage (reflection, query_rewriting, answer, verification, summarization)
+    #[schema(example = "answer")]
+    pub stage: String,
+    #[schema(example = "gpt-4.1")]
+    pub model: String,
+    pub calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+    pub avg_latency_ms: f64,
+    pub failed_calls: i64,
+}
+
+impl From<StageUsage> for StageUsageDto {
+    fn from(usage: StageUsage) -> Self {
+        Self {
+            stage: usage.stage,
+            model: usage.model,
+            calls: usage.calls,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost_usd: usage.cost_usd,
+            avg_latency_ms: usage.avg_latency_ms,
+            failed_calls: usage.failed_calls,
+        }
+    }
+}
+
+/// LLM 利用量の集計結果（API DTO）
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageSummaryDto {
+    /// Number of days summarized
+    #[schema(example = 30)]
+    pub days: u32,
+    pub total_calls: i64,
+    pub total_cost_usd: f64,
+    /// Usage per day, oldest first
+    pub daily: Vec<DailyUsageDto>,
+    /// Usage per stage and model, most expensive first
+    pub stages: Vec<StageUsageDto>,
+}
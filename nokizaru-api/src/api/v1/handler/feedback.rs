@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use super::usage::reject_non_admin;
+use crate::api::v1::container::AppContainer;
+use crate::api::v1::dto::{ErrorResponse, FeedbackQueryDto, FeedbackSummaryDto};
+
+const DEFAULT_FEEDBACK_DAYS: u32 = 30;
+const MAX_FEEDBACK_DAYS: u32 = 366;
+
+/// Summarize answer feedback
+///
+/// Returns how helpful the bot's answers were rated, per model and prompt version,
+/// and the reasons given for unhelpful answers.
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/feedback",
+    params(FeedbackQueryDto),
+    responses(
+        (status = 200, description = "Feedback summary", body = FeedbackSummaryDto),
+        (status = 400, description = "Invalid query", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 500, description = "Failed to summarize feedback", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_feedback(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+    Query(query): Query<FeedbackQueryDto>,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    let days = query.days.unwrap_or(DEFAULT_FEEDBACK_DAYS);
+    if !(1..=MAX_FEEDBACK_DAYS).contains(&days) {
+        let error_response = ErrorResponse::with_details(
+            "Invalid query",
+            format!("days must be between 1 and {}", MAX_FEEDBACK_DAYS),
+        );
+        return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
+    }
+
+    let since = Utc::now() - Duration::days(days as i64);
+    match container
+        .feedback_repository
+        .summary(container.space_id, since)
+        .await
+    {
+        Ok(summary) => Json(FeedbackSummaryDto {
+            days,
+            helpful: summary.helpful,
+            not_helpful: summary.not_helpful,
+            helpfulness: summary.helpfulness(),
+            breakdown: summary.breakdown.into_iter().map(Into::into).collect(),
+            reasons: summary.reasons.into_iter().map(Into::into).collect(),
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to summarize feedback: {}", e);
+            let error_response = ErrorResponse::new("Failed to summarize feedback");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response()
+        }
+    }
+}
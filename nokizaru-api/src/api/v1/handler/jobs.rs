@@ -0,0 +1,221 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::usage::reject_non_admin;
+use crate::api::v1::container::AppContainer;
+use crate::api::v1::dto::{
+    CreateScheduledJobDto, ErrorResponse, ScheduledJobDto, ScheduledJobListDto,
+};
+use nokizaru_core::{JobSpec, MissedRunPolicy, ScheduledJob, SchedulerError};
+
+/// List scheduled jobs
+///
+/// Returns the space's scheduled jobs and the job kinds that can be registered.
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/jobs",
+    responses(
+        (status = 200, description = "Scheduled jobs", body = ScheduledJobListDto),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 500, description = "Failed to list jobs", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_list_jobs(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    match container.scheduler.list().await {
+        Ok(jobs) => Json(ScheduledJobListDto {
+            jobs: jobs.into_iter().map(Into::into).collect(),
+            kinds: container.scheduler.kinds(),
+        })
+        .into_response(),
+        Err(e) => scheduler_error(SchedulerError::Database(e)),
+    }
+}
+
+/// Create a scheduled job
+///
+/// Registers a job that runs on a cron schedule.
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/jobs",
+    request_body = CreateScheduledJobDto,
+    responses(
+        (status = 201, description = "Job created", body = ScheduledJobDto),
+        (status = 400, description = "Invalid job", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 500, description = "Failed to create job", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_create_job(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+    Json(request): Json<CreateScheduledJobDto>,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    let missed_run_policy = match request.missed_run_policy.as_deref() {
+        None => MissedRunPolicy::default(),
+        Some(policy) => match MissedRunPolicy::parse(policy) {
+            Ok(policy) => policy,
+            Err(e) => return scheduler_error(e),
+        },
+    };
+    let payload = match request.payload {
+        serde_json::Value::Null => serde_json::json!({}),
+        payload => payload,
+    };
+    let spec = JobSpec {
+        name: request.name,
+        kind: request.kind,
+        cron: request.cron,
+        time_zone: request.time_zone,
+        payload,
+        missed_run_policy,
+    };
+
+    match container.scheduler.create(spec).await {
+        Ok(job) => (StatusCode::CREATED, Json(ScheduledJobDto::from(job))).into_response(),
+        Err(e) => scheduler_error(e),
+    }
+}
+
+/// Pause a scheduled job
+///
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/jobs/{id}/pause",
+    params(("id" = Uuid, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job paused", body = ScheduledJobDto),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 500, description = "Failed to pause job", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_pause_job(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    job_response(container.scheduler.pause(id).await)
+}
+
+/// Resume a paused job
+///
+/// Runs missed while paused are skipped; the job resumes from its next scheduled time.
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/jobs/{id}/resume",
+    params(("id" = Uuid, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job resumed", body = ScheduledJobDto),
+        (status = 400, description = "The job has no upcoming run", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 500, description = "Failed to resume job", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_resume_job(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    job_response(container.scheduler.resume(id).await)
+}
+
+/// Delete a scheduled job
+///
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/jobs/{id}",
+    params(("id" = Uuid, Path, description = "Job ID")),
+    responses(
+        (status = 204, description = "Job deleted"),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 500, description = "Failed to delete job", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_delete_job(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    match container.scheduler.delete(id).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => job_not_found(),
+        Err(e) => scheduler_error(SchedulerError::Database(e)),
+    }
+}
+
+fn job_response(result: Result<Option<ScheduledJob>, SchedulerError>) -> Response {
+    match result {
+        Ok(Some(job)) => Json(ScheduledJobDto::from(job)).into_response(),
+        Ok(None) => job_not_found(),
+        Err(e) => scheduler_error(e),
+    }
+}
+
+fn job_not_found() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new("Job not found")),
+    )
+        .into_response()
+}
+
+/// 入力の誤りは 400、それ以外は 500 にする
+fn scheduler_error(error: SchedulerError) -> Response {
+    match error {
+        SchedulerError::Database(e) => {
+            tracing::error!("Scheduled job operation failed: {}", e);
+            let error_response = ErrorResponse::new("Scheduled job operation failed");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response()
+        }
+        e => {
+            let error_response = ErrorResponse::with_details("Invalid job", e.to_string());
+            (StatusCode::BAD_REQUEST, Json(error_response)).into_response()
+        }
+    }
+}
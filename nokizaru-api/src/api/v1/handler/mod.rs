@@ -1,5 +1,11 @@
 pub mod docs;
+pub mod feedback;
+pub mod jobs;
 pub mod slack;
+pub mod usage;
 
 pub use docs::*;
+pub use feedback::*;
+pub use jobs::*;
 pub use slack::*;
+pub use usage::*;
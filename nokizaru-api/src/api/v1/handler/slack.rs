@@ -7,9 +7,14 @@ use axum::{
 use std::sync::Arc;
 use utoipa;
 use crate::api::v1::container::AppContainer;
-use nokizaru_slack::{SlackCommand, SlackEvent};
+use nokizaru_slack::{
+    SlackCommand, SlackEvent, SlackInteraction, SlackResponseType, SlackViewSubmission,
+};
 
-use crate::api::v1::dto::{ErrorResponse, SlackCommandDto, SlackCommandResponseDto, SlackEventPayloadDto};
+use crate::api::v1::dto::{
+    ErrorResponse, SlackCommandDto, SlackCommandResponseDto, SlackEventPayloadDto,
+    SlackInteractionDto, SlackInteractionPayloadDto,
+};
 
 const SLACK_TAG: &str = "Slack";
 
@@ -100,8 +105,12 @@ pub async fn handle_slack_commands(
     };
 
     match container.execute_command_usecase.execute(command).await {
-        Ok(response_text) => {
-            Json(SlackCommandResponseDto::in_channel(response_text)).into_response()
+        Ok(response) => {
+            let dto = match response.response_type {
+                SlackResponseType::InChannel => SlackCommandResponseDto::in_channel(response.text),
+                SlackResponseType::Ephemeral => SlackCommandResponseDto::ephemeral(response.text),
+            };
+            Json(dto).into_response()
         }
         Err(e) => {
             tracing::error!("Command execution failed: {}", e);
@@ -111,6 +120,95 @@ pub async fn handle_slack_commands(
     }
 }
 
+/// Handle Slack interactions
+///
+/// Processes interactive component payloads (e.g. button clicks, message shortcuts and modal submissions) sent from Slack.
+#[utoipa::path(
+    post,
+    path = "/api/v1/slack/interactions",
+    request_body(content = SlackInteractionDto, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Interaction accepted", body = String),
+        (status = 400, description = "Invalid interaction payload", body = ErrorResponse),
+    ),
+    tag = SLACK_TAG,
+)]
+pub async fn handle_slack_interactions(
+    State(container): State<Arc<AppContainer>>,
+    Form(dto): Form<SlackInteractionDto>,
+) -> Response {
+    let payload = match serde_json::from_str::<SlackInteractionPayloadDto>(&dto.payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::error!("Failed to parse interaction payload: {}", e);
+            let error_response = ErrorResponse::new("Invalid interaction payload");
+            return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
+        }
+    };
+
+    // DTOをドメインモデルに変換（アクションごとに1インタラクション）
+    let channel_id = payload.channel.map(|c| c.id).unwrap_or_default();
+    let mut interactions: Vec<SlackInteraction> = payload
+        .actions
+        .into_iter()
+        .map(|action| SlackInteraction {
+            interaction_type: payload.interaction_type.clone(),
+            user_id: payload.user.id.clone(),
+            channel_id: channel_id.clone(),
+            action_id: action.action_id,
+            value: action.value,
+            response_url: payload.response_url.clone(),
+            trigger_id: payload.trigger_id.clone(),
+            view: None,
+        })
+        .collect();
+    // メッセージショートカットは callback_id をアクション、スレッドの ts を値として扱う
+    if let (Some(callback_id), Some(message)) = (payload.callback_id, payload.message) {
+        interactions.push(SlackInteraction {
+            interaction_type: payload.interaction_type.clone(),
+            user_id: payload.user.id.clone(),
+            channel_id: channel_id.clone(),
+            action_id: callback_id,
+            value: Some(message.thread_ts.unwrap_or(message.ts)),
+            response_url: payload.response_url.clone(),
+            trigger_id: payload.trigger_id.clone(),
+            view: None,
+        });
+    }
+    // モーダルの送信は callback_id をアクションとして扱う
+    if let Some(view) = payload.view.filter(|_| payload.interaction_type == "view_submission") {
+        interactions.push(SlackInteraction {
+            interaction_type: payload.interaction_type.clone(),
+            user_id: payload.user.id.clone(),
+            channel_id: channel_id.clone(),
+            action_id: view.callback_id.clone(),
+            value: None,
+            response_url: None,
+            trigger_id: None,
+            view: Some(SlackViewSubmission {
+                private_metadata: view.private_metadata.clone(),
+                values: view.input_values(),
+            }),
+        });
+    }
+
+    // バックグラウンドで処理を実行（Slackに即座にレスポンスを返すため）
+    for interaction in interactions {
+        let container_clone = Arc::clone(&container);
+        tokio::spawn(async move {
+            if let Err(e) = container_clone
+                .process_interaction_usecase
+                .execute(interaction)
+                .await
+            {
+                tracing::error!("❌ Background interaction processing failed: {}", e);
+            }
+        });
+    }
+
+    (StatusCode::OK, "").into_response()
+}
+
 /// Health check endpoint
 ///
 /// Returns server health status.
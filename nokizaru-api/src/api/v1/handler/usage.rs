@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+
+use crate::api::v1::container::AppContainer;
+use crate::api::v1::dto::{ErrorResponse, UsageQueryDto, UsageSummaryDto};
+use nokizaru_core::UsageFilter;
+
+const DEFAULT_USAGE_DAYS: u32 = 30;
+const MAX_USAGE_DAYS: u32 = 366;
+
+/// Summarize LLM usage
+///
+/// Returns token usage and cost of LLM calls per day and per stage.
+/// Requires `Authorization: Bearer <ADMIN_API_TOKEN>`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/usage",
+    params(UsageQueryDto),
+    responses(
+        (status = 200, description = "Usage summary", body = UsageSummaryDto),
+        (status = 400, description = "Invalid query", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+        (status = 403, description = "Admin API is disabled", body = ErrorResponse),
+        (status = 500, description = "Failed to summarize usage", body = ErrorResponse),
+    ),
+    tag = "Admin",
+)]
+pub async fn handle_usage(
+    State(container): State<Arc<AppContainer>>,
+    headers: HeaderMap,
+    Query(query): Query<UsageQueryDto>,
+) -> Response {
+    if let Some(rejection) = reject_non_admin(&container, &headers) {
+        return rejection;
+    }
+
+    let days = query.days.unwrap_or(DEFAULT_USAGE_DAYS);
+    if !(1..=MAX_USAGE_DAYS).contains(&days) {
+        let error_response = ErrorResponse::with_details(
+            "Invalid query",
+            format!("days must be between 1 and {}", MAX_USAGE_DAYS),
+        );
+        return (StatusCode::BAD_REQUEST, Json(error_response)).into_response();
+    }
+
+    let filter = UsageFilter {
+        channel_id: query.channel_id,
+        user_id: query.user_id,
+        ..UsageFilter::last_days(container.space_id, days)
+    };
+    let repository = &container.usage_repository;
+    match tokio::try_join!(repository.daily(&filter), repository.by_stage(&filter)) {
+        Ok((daily, stages)) => Json(UsageSummaryDto {
+            days,
+            total_calls: daily.iter().map(|day| day.calls).sum(),
+            total_cost_usd: daily.iter().map(|day| day.cost_usd).sum(),
+            daily: daily.into_iter().map(Into::into).collect(),
+            stages: stages.into_iter().map(Into::into).collect(),
+        })
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to summarize usage: {}", e);
+            let error_response = ErrorResponse::new("Failed to summarize usage");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response)).into_response()
+        }
+    }
+}
+
+/// 管理用 API のトークンを検証する（拒否する場合はそのレスポンスを返す）
+pub(crate) fn reject_non_admin(container: &AppContainer, headers: &HeaderMap) -> Option<Response> {
+    let Some(expected) = container.config.admin_token.as_deref() else {
+        let error_response =
+            ErrorResponse::new("Admin API is disabled (ADMIN_API_TOKEN is not set)");
+        return Some((StatusCode::FORBIDDEN, Json(error_response)).into_response());
+    };
+
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => None,
+        _ => {
+            let error_response = ErrorResponse::new("Invalid admin token");
+            Some((StatusCode::UNAUTHORIZED, Json(error_response)).into_response())
+        }
+    }
+}
+
+/// タイミング攻撃を避けるための比較
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -2,8 +2,10 @@ use axum::{response::IntoResponse, Json};
 use utoipa::OpenApi;
 
 use super::dto::{
-    ErrorResponse, SlackCommandDto, SlackCommandResponseDto,
-    SlackEventPayloadDto,
+    CreateScheduledJobDto, DailyUsageDto, ErrorResponse, FeedbackBreakdownDto,
+    FeedbackReasonCountDto, FeedbackSummaryDto, ScheduledJobDto, ScheduledJobListDto,
+    SlackCommandDto, SlackCommandResponseDto,
+    SlackEventPayloadDto, SlackInteractionDto, StageUsageDto, UsageSummaryDto,
 };
 
 /// API Documentation structure
@@ -25,12 +27,30 @@ use super::dto::{
         crate::api::v1::handler::slack::handle_health_check,
         crate::api::v1::handler::slack::handle_slack_events,
         crate::api::v1::handler::slack::handle_slack_commands,
+        crate::api::v1::handler::slack::handle_slack_interactions,
+        crate::api::v1::handler::usage::handle_usage,
+        crate::api::v1::handler::feedback::handle_feedback,
+        crate::api::v1::handler::jobs::handle_list_jobs,
+        crate::api::v1::handler::jobs::handle_create_job,
+        crate::api::v1::handler::jobs::handle_pause_job,
+        crate::api::v1::handler::jobs::handle_resume_job,
+        crate::api::v1::handler::jobs::handle_delete_job,
     ),
     components(
         schemas(
             SlackEventPayloadDto,
             SlackCommandDto,
             SlackCommandResponseDto,
+            SlackInteractionDto,
+            UsageSummaryDto,
+            DailyUsageDto,
+            StageUsageDto,
+            FeedbackSummaryDto,
+            FeedbackBreakdownDto,
+            FeedbackReasonCountDto,
+            ScheduledJobListDto,
+            ScheduledJobDto,
+            CreateScheduledJobDto,
             ErrorResponse,
         )
     ),
@@ -38,6 +58,7 @@ use super::dto::{
         (name = "Health", description = "Health check endpoints"),
         (name = "Agent", description = "AI Agent processing endpoints"),
         (name = "Slack", description = "Slack integration endpoints"),
+        (name = "Admin", description = "Administration endpoints (require ADMIN_API_TOKEN)"),
     ),
     servers(
         (url = "http://localhost:3000", description = "Local development server"),
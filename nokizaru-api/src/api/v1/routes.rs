@@ -1,13 +1,18 @@
 use crate::api::v1::container::AppContainer;
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 
 use super::{
-    handler::{docs_html, handle_health_check, handle_slack_commands, handle_slack_events},
+    handler::{
+        docs_html, handle_create_job, handle_delete_job, handle_feedback, handle_health_check,
+        handle_list_jobs,
+        handle_pause_job, handle_resume_job, handle_slack_commands, handle_slack_events,
+        handle_slack_interactions, handle_usage,
+    },
     openapi::openapi_json,
 };
 
@@ -17,6 +22,13 @@ pub fn create_router(container: Arc<AppContainer>) -> Router {
         .route("/health", get(handle_health_check))
         .route("/slack/events", post(handle_slack_events))
         .route("/slack/commands", post(handle_slack_commands))
+        .route("/slack/interactions", post(handle_slack_interactions))
+        .route("/usage", get(handle_usage))
+        .route("/feedback", get(handle_feedback))
+        .route("/jobs", get(handle_list_jobs).post(handle_create_job))
+        .route("/jobs/:id", delete(handle_delete_job))
+        .route("/jobs/:id/pause", post(handle_pause_job))
+        .route("/jobs/:id/resume", post(handle_resume_job))
         .layer(TraceLayer::new_for_http())
         .with_state(container);
 
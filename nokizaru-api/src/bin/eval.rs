@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use nokizaru_core::{
+    eval::{answer_similarity, load_dataset, CaseResult, EvalCase, EvalReport},
+    llm::{token_counter_for_model, LlmProviderKind, LlmStage, ModelRouter},
+    AgentService, MessageIntent, PromptLibrary, Redactor, SpaceSettings,
+};
+use nokizaru_slack::{
+    slack_api::{MessageContext, SlackApi},
+    ContextAssembler, MessageContextService, WorkspaceFixture,
+};
+use std::{env, sync::Arc};
+
+/// コマンドライン引数（`--name value` 形式）
+struct Args {
+    values: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut values = Vec::new();
+        let mut flags = Vec::new();
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let name = arg
+                .strip_prefix("--")
+                .with_context(|| format!("Unexpected argument: {}", arg))?;
+            match iter.peek() {
+                Some(value) if !value.starts_with("--") => {
+                    values.push((name.to_string(), value.to_string()));
+                    iter.next();
+                }
+                _ => flags.push(name.to_string()),
+            }
+        }
+        Ok(Self { values, flags })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn require(&self, name: &str) -> Result<&str> {
+        self.get(name)
+            .with_context(|| format!("--{} is required", name))
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|f| f == name)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // 環境変数の読み込み
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
+        )
+        .init();
+
+    let args: Vec<String> = env::args().collect();
+    let Some(command) = args.get(1) else {
+        print_usage(&args[0]);
+        std::process::exit(1);
+    };
+    let options = Args::parse(&args[2..])?;
+
+    match command.as_str() {
+        "run" => run(&options).await,
+        "record" => record(&options).await,
+        _ => {
+            print_usage(&args[0]);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Slack からチャンネルを記録してワークスペースのフィクスチャを作る
+async fn record(options: &Args) -> Result<()> {
+    let channels: Vec<String> = options
+        .require("channels")?
+        .split(',')
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .collect();
+    let limit: u32 = options
+        .get("limit")
+        .map(str::parse)
+        .transpose()
+        .context("--limit must be a number")?
+        .unwrap_or(200);
+    let out = options.require("out")?;
+
+    let bot_token =
+        env::var("SLACK_BOT_TOKEN").context("SLACK_BOT_TOKEN environment variable not set")?;
+    let api = SlackApi::new(bot_token);
+
+    println!("📥 Recording {} channel(s)...", channels.len());
+    let fixture = WorkspaceFixture::record(&api, &channels, limit).await?;
+    for channel in &fixture.channels {
+        println!("   #{}: {} messages", channel.name, channel.messages.len());
+    }
+
+    std::fs::write(out, serde_json::to_string_pretty(&fixture)?)
+        .with_context(|| format!("Failed to write {}", out))?;
+    println!("✅ Saved to {}", out);
+
+    Ok(())
+}
+
+/// データセットを記録したワークスペースに対して実行し、レポートを出力する
+async fn run(options: &Args) -> Result<()> {
+    let dataset_path = options.require("dataset")?;
+    let dataset = load_dataset(
+        &std::fs::read_to_string(dataset_path)
+            .with_context(|| format!("Failed to read {}", dataset_path))?,
+    )?;
+    let fixture = WorkspaceFixture::load(options.require("fixture")?)?;
+    let k: usize = options
+        .get("k")
+        .map(str::parse)
+        .transpose()
+        .context("--k must be a number")?
+        .unwrap_or(5);
+    let skip_answer = options.flag("skip-answer");
+
+    // スペース設定（省略時は既定値）。プロバイダ・モデルは引数で上書きできる
+    let mut settings: SpaceSettings = match options.get("settings") {
+        Some(path) => serde_json::from_str(
+            &std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?,
+        )
+        .with_context(|| format!("Failed to parse {}", path))?,
+        None => SpaceSettings::default(),
+    };
+    if let Some(provider) = options.get("provider") {
+        settings.llm.default.provider = serde_json::from_value::<LlmProviderKind>(
+            serde_json::Value::String(provider.to_string()),
+        )
+        .with_context(|| format!("Unknown provider: {}", provider))?;
+    }
+    if let Some(model) = options.get("model") {
+        settings.llm.default.model = model.to_string();
+    }
+
+    let mut router = ModelRouter::from_settings(&settings.llm)?;
+    if settings.redaction.enabled {
+        router = router.with_redactor(Arc::new(Redactor::from_settings(&settings.redaction)?));
+    }
+    let router = Arc::new(router);
+    let agent_service = Arc::new(
+        AgentService::new(router.clone())
+            .with_prompts(PromptLibrary::new().with_overrides(settings.prompts.clone())),
+    );
+    let token_counter = token_counter_for_model(router.model(LlmStage::Answer).unwrap_or_default());
+    let context_service = MessageContextService::with_repository(Arc::new(fixture))
+        .with_retrieval(settings.retrieval.clone())
+        .with_assembler(
+            ContextAssembler::new(token_counter).with_summarizer(agent_service.clone()),
+        );
+
+    println!(
+        "🧪 Evaluating {} case(s) with {} (k = {})",
+        dataset.len(),
+        router.model(LlmStage::Answer).unwrap_or_default(),
+        k
+    );
+
+    let mut results = Vec::new();
+    for case in &dataset {
+        println!("   {}: {}", case.id, case.question);
+        let mut result = CaseResult::new(case);
+        if let Err(e) = evaluate(
+            case,
+            &settings,
+            &agent_service,
+            &context_service,
+            skip_answer,
+            &mut result,
+        )
+        .await
+        {
+            result.error = Some(e.to_string());
+        }
+        results.push(result);
+    }
+
+    let report = EvalReport::new(k, results);
+    let markdown = report.to_markdown();
+    match options.get("out") {
+        Some(path) => {
+            std::fs::write(path, &markdown).with_context(|| format!("Failed to write {}", path))?;
+            println!("✅ Report saved to {}", path);
+        }
+        None => println!("\n{}", markdown),
+    }
+    if let Some(path) = options.get("json") {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("Failed to write {}", path))?;
+    }
+
+    Ok(())
+}
+
+/// 1件を評価する（途中で失敗してもそれまでの結果は `result` に残る）
+async fn evaluate(
+    case: &EvalCase,
+    settings: &SpaceSettings,
+    agent_service: &AgentService,
+    context_service: &MessageContextService,
+    skip_answer: bool,
+    result: &mut CaseResult,
+) -> Result<()> {
+    let language = case
+        .language
+        .unwrap_or_else(|| settings.language.resolve("", "", &case.question));
+
+    if case.is_question.is_some() {
+        let classification = agent_service.classify_intent(&case.question).await?;
+        result.predicted_is_question = Some(classification.intent == MessageIntent::Question);
+    }
+
+    let queries = agent_service
+        .query_rewriting_with_history(&case.question, &[], language)
+        .await?
+        .queries;
+    result.queries = queries.clone();
+
+    let contexts = context_service.search_with_full_context(&queries).await?;
+    result.source_ranks = case
+        .expected_sources
+        .iter()
+        .map(|expected| {
+            contexts
+                .iter()
+                .position(|context| contains(context, &expected.channel_id, &expected.ts))
+                .map(|i| i + 1)
+        })
+        .collect();
+
+    let Some(reference) = case.reference_answer.as_deref().filter(|_| !skip_answer) else {
+        return Ok(());
+    };
+    let retrieved = context_service.assemble(contexts).await;
+    let answer = agent_service
+        .answer_with_citations(&case.question, &retrieved.text, &[], language)
+        .await?;
+    result.similarity = Some(answer_similarity(&answer.answer, reference));
+    result.answer = Some(answer.answer);
+
+    Ok(())
+}
+
+/// 検索結果（前後のメッセージ・スレッドを含む）に指定したメッセージが含まれるか
+fn contains(context: &MessageContext, channel_id: &str, ts: &str) -> bool {
+    let target = &context.target_message;
+    let same_channel = target
+        .channel
+        .as_ref()
+        .and_then(|channel| channel.id.as_deref())
+        == Some(channel_id);
+    if !same_channel {
+        return false;
+    }
+
+    target.ts == ts
+        || context
+            .before_messages
+            .iter()
+            .chain(&context.after_messages)
+            .any(|msg| msg.ts == ts)
+        || context
+            .threads
+            .iter()
+            .flat_map(|thread| &thread.replies)
+            .any(|msg| msg.ts == ts)
+}
+
+fn print_usage(program: &str) {
+    println!("🧪 Nokizaru Offline Evaluation");
+    println!();
+    println!("Usage:");
+    println!(
+        "  {} run --dataset <cases.jsonl> --fixture <workspace.json> [options]",
+        program
+    );
+    println!(
+        "  {} record --channels <C1,C2> --out <workspace.json> [--limit N]",
+        program
+    );
+    println!();
+    println!("run options:");
+    println!("  --settings <file>   Space settings JSON (same format as spaces.settings)");
+    println!("  --provider <name>   Override the default provider (openai, anthropic, ...)");
+    println!("  --model <name>      Override the default model");
+    println!("  --k <n>             Cut-off for recall@k (default: 5)");
+    println!("  --out <file>        Write the markdown report to a file (default: stdout)");
+    println!("  --json <file>       Also write the report as JSON");
+    println!("  --skip-answer       Evaluate retrieval only");
+    println!();
+    println!("Dataset (one JSON object per line):");
+    println!(
+        r#"  {{"id":"deploy-owner","question":"デプロイの担当は？","is_question":true,"expected_sources":[{{"channel_id":"C01234ABC56","ts":"1700000000.000100"}}],"reference_answer":"山田さんです"}}"#
+    );
+    println!();
+    println!("Environment:");
+    println!("  SLACK_BOT_TOKEN  Required for record");
+    println!("  OPENAI_API_KEY   Or the key for the selected provider (run)");
+    println!();
+    println!("Examples:");
+    println!("  # Record two channels (200 messages each)");
+    println!(
+        "  {} record --channels C01234ABC56,C0FEDCBA987 --out workspace.json",
+        program
+    );
+    println!();
+    println!("  # Compare two models");
+    println!("  {} run --dataset cases.jsonl --fixture workspace.json --model gpt-4.1-mini --out before.md", program);
+    println!(
+        "  {} run --dataset cases.jsonl --fixture workspace.json --model gpt-4.1 --out after.md",
+        program
+    );
+    println!("  diff before.md after.md");
+}
@@ -119,7 +119,7 @@ fn print_message(index: usize, msg: &Message) {
         if let Ok(secs) = ts.parse::<i64>() {
             use chrono::{DateTime, Utc};
             let dt = DateTime::<Utc>::from_timestamp(secs, 0)
-                .unwrap_or_else(|| Utc::now());
+                .unwrap_or_else(Utc::now);
             dt.format("%Y-%m-%d %H:%M:%S UTC").to_string()
         } else {
             msg.ts.clone()
@@ -1,5 +1,9 @@
 use nokizaru_api::api::v1::{create_router, AppConfig, AppContainer};
-use nokizaru_core::{create_pool, run_migrations};
+use nokizaru_core::{
+    create_pool, run_migrations, AnswerCacheRepository, DbPool, Space, SpaceRepository,
+    SpaceSettings,
+};
+use nokizaru_slack::slack_api::SlackApi;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[tokio::main]
@@ -20,10 +24,34 @@ async fn main() -> anyhow::Result<()> {
     run_migrations(&db_pool).await?;
     tracing::info!("✅ Database migrations completed");
 
+    // 期限切れの回答キャッシュを削除（失敗しても起動は続ける）
+    match AnswerCacheRepository::new(db_pool.clone()).purge_expired().await {
+        Ok(n) => tracing::info!("✅ Purged {} expired cached answers", n),
+        Err(e) => tracing::warn!("Failed to purge answer cache: {}", e),
+    }
+
+    // スペース設定の読み込み
+    let (space, space_settings) = load_space(&config, &db_pool).await?;
+    tracing::info!(
+        "✅ Space settings loaded (LLM: {:?} / {})",
+        space_settings.llm.default.provider,
+        space_settings.llm.default.model
+    );
+
     // DIコンテナ構築
-    let container = std::sync::Arc::new(AppContainer::new(config.clone()));
+    let container = std::sync::Arc::new(AppContainer::new(
+        config.clone(),
+        db_pool,
+        space.as_ref(),
+        space_settings,
+    )?);
     tracing::info!("✅ DI container initialized");
 
+    // cron 式の定期実行ジョブ（管理用 API の /api/v1/jobs や /nokizaru digest で登録する）
+    if container.scheduler.settings().enabled {
+        tokio::spawn(container.scheduler.clone().run());
+    }
+
     // ルーター構築
     let app = create_router(container);
     tracing::info!("✅ Router configured");
@@ -36,12 +64,45 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("📝 Health check: http://{}/health", addr);
     tracing::info!("📨 Slack events: http://{}/slack/events", addr);
     tracing::info!("⚡ Slack commands: http://{}/slack/commands", addr);
+    tracing::info!("🖱️  Slack interactions: http://{}/slack/interactions", addr);
 
     axum::serve(listener, app).await?;
 
     Ok(())
 }
 
+/// ボットトークンのワークスペースに対応するスペースとその設定を読み込む
+///
+/// スペースが登録されていない場合は None を返し、既定の設定を使用します。
+/// スペースの設定が壊れている場合は、既定の設定で動かさずに起動を中止します。
+async fn load_space(
+    config: &AppConfig,
+    db_pool: &DbPool,
+) -> anyhow::Result<(Option<Space>, SpaceSettings)> {
+    let team_id = match SlackApi::new(config.slack.bot_token.clone()).auth_test().await {
+        Ok(auth) => auth.team_id,
+        Err(e) => {
+            tracing::warn!("⚠️  auth.test failed, using default space settings: {}", e);
+            return Ok((None, SpaceSettings::default()));
+        }
+    };
+
+    match SpaceRepository::new(db_pool.clone())
+        .find_by_slack_team_id(&team_id)
+        .await?
+    {
+        Some(space) => {
+            tracing::info!("Using space '{}' for team {}", space.name, team_id);
+            let settings = space.settings()?;
+            Ok((Some(space), settings))
+        }
+        None => {
+            tracing::info!("No space registered for team {}, using defaults", team_id);
+            Ok((None, SpaceSettings::default()))
+        }
+    }
+}
+
 fn init_logging() {
     tracing_subscriber::registry()
         .with(
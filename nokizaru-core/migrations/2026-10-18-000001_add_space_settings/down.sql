@@ -0,0 +1,3 @@
+ALTER TABLE spaces
+  DROP COLUMN settings,
+  DROP COLUMN slack_team_id;
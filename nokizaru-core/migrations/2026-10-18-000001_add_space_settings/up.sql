@@ -0,0 +1,6 @@
+ALTER TABLE spaces
+  ADD COLUMN slack_team_id VARCHAR(32) UNIQUE,
+  ADD COLUMN settings JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMENT ON COLUMN spaces.slack_team_id IS 'SlackワークスペースID（auth.test の team_id）';
+COMMENT ON COLUMN spaces.settings IS 'スペースごとの設定（LLMプロバイダなど）';
@@ -0,0 +1 @@
+DROP TABLE interaction_logs;
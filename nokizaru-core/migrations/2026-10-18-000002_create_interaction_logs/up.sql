@@ -0,0 +1,23 @@
+CREATE TABLE interaction_logs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+  space_id UUID REFERENCES spaces(id) ON DELETE CASCADE,
+  source VARCHAR(20) NOT NULL,
+  channel_id VARCHAR(32) NOT NULL,
+  user_id VARCHAR(32) NOT NULL,
+  question TEXT NOT NULL,
+  answer TEXT NOT NULL,
+  decision VARCHAR(30) NOT NULL,
+  confidence DOUBLE PRECISION,
+  faithfulness DOUBLE PRECISION,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX interaction_logs_space_id_created_at_idx ON interaction_logs (space_id, created_at);
+
+COMMENT ON TABLE interaction_logs IS 'ボットの質問応答ログ';
+COMMENT ON COLUMN interaction_logs.source IS '質問の経路（ask / mention / message）';
+COMMENT ON COLUMN interaction_logs.decision IS '回答の扱い（post / post_with_disclaimer / abstain）';
+COMMENT ON COLUMN interaction_logs.confidence IS 'LLMが自己評価した確信度（0.0〜1.0）';
+COMMENT ON COLUMN interaction_logs.faithfulness IS '検証で出典に裏付けられた主張の割合（0.0〜1.0）';
+
+alter table interaction_logs enable row level security;
@@ -0,0 +1,4 @@
+ALTER TABLE interaction_logs
+  ADD COLUMN tool_trace JSONB;
+
+COMMENT ON COLUMN interaction_logs.tool_trace IS 'エージェントモードでのツール呼び出しの記録';
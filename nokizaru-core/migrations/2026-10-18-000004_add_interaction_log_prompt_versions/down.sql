@@ -0,0 +1,2 @@
+ALTER TABLE interaction_logs
+  DROP COLUMN prompt_versions;
@@ -0,0 +1,4 @@
+ALTER TABLE interaction_logs
+  ADD COLUMN prompt_versions JSONB;
+
+COMMENT ON COLUMN interaction_logs.prompt_versions IS '回答に使ったプロンプトテンプレートのバージョン（種類 → バージョン）';
@@ -0,0 +1,29 @@
+CREATE TABLE answer_cache (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+  space_id UUID REFERENCES spaces(id) ON DELETE CASCADE,
+  question_key TEXT NOT NULL,
+  context_hash VARCHAR(64) NOT NULL,
+  language VARCHAR(8) NOT NULL,
+  queries JSONB NOT NULL DEFAULT '[]',
+  answer JSONB NOT NULL,
+  cited_messages JSONB NOT NULL DEFAULT '[]',
+  hit_count INTEGER NOT NULL DEFAULT 0,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE UNIQUE INDEX answer_cache_key_idx ON answer_cache (space_id, question_key, context_hash, language) NULLS NOT DISTINCT;
+CREATE INDEX answer_cache_question_key_idx ON answer_cache (space_id, question_key, language, created_at);
+CREATE INDEX answer_cache_cited_messages_idx ON answer_cache USING GIN (cited_messages jsonb_path_ops);
+CREATE INDEX answer_cache_expires_at_idx ON answer_cache (expires_at);
+
+COMMENT ON TABLE answer_cache IS '回答のキャッシュ';
+COMMENT ON COLUMN answer_cache.question_key IS '正規化した質問文（検索範囲の指定を含む）';
+COMMENT ON COLUMN answer_cache.context_hash IS '検索結果のコンテキストとプロンプトのバージョンの SHA-256';
+COMMENT ON COLUMN answer_cache.language IS '回答言語（ja / en）';
+COMMENT ON COLUMN answer_cache.queries IS '生成した検索クエリ（同じ質問ではクエリ生成を省略する）';
+COMMENT ON COLUMN answer_cache.answer IS 'キャッシュした回答';
+COMMENT ON COLUMN answer_cache.cited_messages IS '出典のメッセージ（{"channel_id", "ts"} の配列、編集・削除時に無効化する）';
+COMMENT ON COLUMN answer_cache.hit_count IS 'キャッシュから回答した回数';
+
+alter table answer_cache enable row level security;
@@ -0,0 +1,28 @@
+CREATE TABLE llm_usage_logs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+  space_id UUID REFERENCES spaces(id) ON DELETE CASCADE,
+  channel_id VARCHAR(32),
+  user_id VARCHAR(32),
+  stage VARCHAR(30) NOT NULL,
+  provider VARCHAR(30) NOT NULL,
+  model VARCHAR(100) NOT NULL,
+  input_tokens BIGINT NOT NULL,
+  output_tokens BIGINT NOT NULL,
+  latency_ms INTEGER NOT NULL,
+  cost_usd DOUBLE PRECISION,
+  succeeded BOOLEAN NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX llm_usage_logs_space_id_created_at_idx ON llm_usage_logs (space_id, created_at);
+CREATE INDEX llm_usage_logs_user_id_created_at_idx ON llm_usage_logs (user_id, created_at);
+
+COMMENT ON TABLE llm_usage_logs IS 'LLM 呼び出しごとのトークン数とコスト';
+COMMENT ON COLUMN llm_usage_logs.channel_id IS '質問されたチャンネル（不明な場合は NULL）';
+COMMENT ON COLUMN llm_usage_logs.user_id IS '質問したユーザー（不明な場合は NULL）';
+COMMENT ON COLUMN llm_usage_logs.stage IS 'パイプラインのステージ（reflection / query_rewriting / answer / verification / summarization）';
+COMMENT ON COLUMN llm_usage_logs.latency_ms IS '呼び出しの所要時間（ミリ秒）';
+COMMENT ON COLUMN llm_usage_logs.cost_usd IS '料金表から計算したコスト（米ドル、料金表にないモデルは NULL）';
+COMMENT ON COLUMN llm_usage_logs.succeeded IS '呼び出しが成功したか（エラー・タイムアウトは false）';
+
+alter table llm_usage_logs enable row level security;
@@ -0,0 +1,30 @@
+CREATE TABLE scheduled_jobs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+  space_id UUID REFERENCES spaces(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  kind VARCHAR(64) NOT NULL,
+  cron VARCHAR(128) NOT NULL,
+  time_zone VARCHAR(64) NOT NULL,
+  payload JSONB NOT NULL DEFAULT '{}',
+  missed_run_policy VARCHAR(16) NOT NULL DEFAULT 'run_once',
+  paused BOOLEAN NOT NULL DEFAULT FALSE,
+  next_run_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  last_run_at TIMESTAMP WITH TIME ZONE,
+  last_error TEXT,
+  claimed_until TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX scheduled_jobs_space_id_next_run_at_idx ON scheduled_jobs (space_id, next_run_at) WHERE NOT paused;
+
+COMMENT ON TABLE scheduled_jobs IS 'cron 式で定期実行するジョブ';
+COMMENT ON COLUMN scheduled_jobs.kind IS 'ジョブの種類（実行するハンドラー、例: purge_answer_cache, channel_digest）';
+COMMENT ON COLUMN scheduled_jobs.cron IS 'cron 式（分 時 日 月 曜日、秒から始まる6項目も可）';
+COMMENT ON COLUMN scheduled_jobs.time_zone IS 'cron 式を解釈するタイムゾーン（IANA 名、例: Asia/Tokyo）';
+COMMENT ON COLUMN scheduled_jobs.payload IS 'ハンドラーに渡すパラメータ';
+COMMENT ON COLUMN scheduled_jobs.missed_run_policy IS '停止中に実行時刻を過ぎた場合の扱い（run_once = 1回だけ実行 / skip = 次の時刻まで待つ）';
+COMMENT ON COLUMN scheduled_jobs.next_run_at IS '次に実行する時刻';
+COMMENT ON COLUMN scheduled_jobs.last_error IS '最後の実行のエラー（成功した場合は NULL）';
+COMMENT ON COLUMN scheduled_jobs.claimed_until IS '実行中のレプリカが処理を確保している期限（期限を過ぎると他のレプリカが再試行する）';
+
+alter table scheduled_jobs enable row level security;
@@ -0,0 +1,8 @@
+DROP TABLE answer_feedback;
+
+DROP INDEX interaction_logs_channel_id_message_ts_idx;
+
+ALTER TABLE interaction_logs
+  DROP COLUMN message_ts,
+  DROP COLUMN model,
+  DROP COLUMN sources;
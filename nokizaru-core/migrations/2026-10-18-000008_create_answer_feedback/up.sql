@@ -0,0 +1,34 @@
+ALTER TABLE interaction_logs
+  ADD COLUMN sources JSONB,
+  ADD COLUMN model VARCHAR(100),
+  ADD COLUMN message_ts VARCHAR(32);
+
+CREATE INDEX interaction_logs_channel_id_message_ts_idx ON interaction_logs (channel_id, message_ts) WHERE message_ts IS NOT NULL;
+
+COMMENT ON COLUMN interaction_logs.sources IS '回答のコンテキストに含めたメッセージ（出典の候補）';
+COMMENT ON COLUMN interaction_logs.model IS '回答を生成したモデル';
+COMMENT ON COLUMN interaction_logs.message_ts IS '回答を投稿したメッセージの ts（リアクションとの対応付けに使う、エフェメラルな回答では NULL）';
+
+CREATE TABLE answer_feedback (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+  space_id UUID REFERENCES spaces(id) ON DELETE CASCADE,
+  interaction_log_id UUID NOT NULL REFERENCES interaction_logs(id) ON DELETE CASCADE,
+  user_id VARCHAR(32) NOT NULL,
+  source VARCHAR(20) NOT NULL,
+  helpful BOOLEAN NOT NULL,
+  reason VARCHAR(30),
+  comment TEXT,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  UNIQUE (interaction_log_id, user_id, source)
+);
+
+CREATE INDEX answer_feedback_space_id_created_at_idx ON answer_feedback (space_id, created_at);
+
+COMMENT ON TABLE answer_feedback IS 'ボットの回答へのフィードバック（質問・出典・プロンプト・モデルは interaction_logs を参照）';
+COMMENT ON COLUMN answer_feedback.source IS 'フィードバックの経路（button / reaction）';
+COMMENT ON COLUMN answer_feedback.helpful IS '役に立ったかどうか';
+COMMENT ON COLUMN answer_feedback.reason IS '役に立たなかった理由（incorrect / outdated / irrelevant / incomplete / other）';
+COMMENT ON COLUMN answer_feedback.comment IS 'モーダルで入力された自由記述';
+
+alter table answer_feedback enable row level security;
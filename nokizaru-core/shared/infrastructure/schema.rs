@@ -1,5 +1,115 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    answer_feedback (id) {
+        id -> Uuid,
+        space_id -> Nullable<Uuid>,
+        interaction_log_id -> Uuid,
+        #[max_length = 32]
+        user_id -> Varchar,
+        #[max_length = 20]
+        source -> Varchar,
+        helpful -> Bool,
+        #[max_length = 30]
+        reason -> Nullable<Varchar>,
+        comment -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    answer_cache (id) {
+        id -> Uuid,
+        space_id -> Nullable<Uuid>,
+        question_key -> Text,
+        #[max_length = 64]
+        context_hash -> Varchar,
+        #[max_length = 8]
+        language -> Varchar,
+        queries -> Jsonb,
+        answer -> Jsonb,
+        cited_messages -> Jsonb,
+        hit_count -> Int4,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    interaction_logs (id) {
+        id -> Uuid,
+        space_id -> Nullable<Uuid>,
+        #[max_length = 20]
+        source -> Varchar,
+        #[max_length = 32]
+        channel_id -> Varchar,
+        #[max_length = 32]
+        user_id -> Varchar,
+        question -> Text,
+        answer -> Text,
+        #[max_length = 30]
+        decision -> Varchar,
+        confidence -> Nullable<Float8>,
+        faithfulness -> Nullable<Float8>,
+        created_at -> Timestamptz,
+        tool_trace -> Nullable<Jsonb>,
+        prompt_versions -> Nullable<Jsonb>,
+        sources -> Nullable<Jsonb>,
+        #[max_length = 100]
+        model -> Nullable<Varchar>,
+        #[max_length = 32]
+        message_ts -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    llm_usage_logs (id) {
+        id -> Uuid,
+        space_id -> Nullable<Uuid>,
+        #[max_length = 32]
+        channel_id -> Nullable<Varchar>,
+        #[max_length = 32]
+        user_id -> Nullable<Varchar>,
+        #[max_length = 30]
+        stage -> Varchar,
+        #[max_length = 30]
+        provider -> Varchar,
+        #[max_length = 100]
+        model -> Varchar,
+        input_tokens -> Int8,
+        output_tokens -> Int8,
+        latency_ms -> Int4,
+        cost_usd -> Nullable<Float8>,
+        succeeded -> Bool,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    scheduled_jobs (id) {
+        id -> Uuid,
+        space_id -> Nullable<Uuid>,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 64]
+        kind -> Varchar,
+        #[max_length = 128]
+        cron -> Varchar,
+        #[max_length = 64]
+        time_zone -> Varchar,
+        payload -> Jsonb,
+        #[max_length = 16]
+        missed_run_policy -> Varchar,
+        paused -> Bool,
+        next_run_at -> Timestamptz,
+        last_run_at -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        claimed_until -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     spaces (id) {
         id -> Uuid,
@@ -7,5 +117,24 @@ diesel::table! {
         name -> Varchar,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        #[max_length = 32]
+        slack_team_id -> Nullable<Varchar>,
+        settings -> Jsonb,
     }
 }
+
+diesel::joinable!(answer_cache -> spaces (space_id));
+diesel::joinable!(answer_feedback -> interaction_logs (interaction_log_id));
+diesel::joinable!(answer_feedback -> spaces (space_id));
+diesel::joinable!(interaction_logs -> spaces (space_id));
+diesel::joinable!(llm_usage_logs -> spaces (space_id));
+diesel::joinable!(scheduled_jobs -> spaces (space_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    answer_cache,
+    answer_feedback,
+    interaction_logs,
+    llm_usage_logs,
+    scheduled_jobs,
+    spaces,
+);
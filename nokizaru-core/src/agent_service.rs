@@ -1,36 +1,199 @@
+use std::sync::Arc;
+
 use anyhow::Result;
-use rig::{client::CompletionClient, completion::Prompt, providers::openai};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    extract::{ExtractError, Extractor},
+    llm::{LlmRequest, LlmStage, LlmToolCall, ModelRouter},
+    prompt::{PromptKind, PromptLibrary},
+    ConversationSummary, IntentClassification, Language,
+};
+
+/// 会話を要約する際、まとまりごとの要約の最大トークン数
+const CHUNK_SUMMARY_TOKENS: u64 = 800;
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct TestResponse {
-    #[schemars(description = "Input message category. only 'Question' or 'NonQuestion'")]
-    pub category: String,
+    #[schemars(description = "Input message category.")]
+    pub category: MessageCategory,
     #[schemars(description = "Is the input message a question?")]
     pub is_question: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct SearchQuery {
-    #[schemars(description = "List of search queries. maximum 3 queries. minimum 1 query.")]
+    #[schemars(
+        description = "List of search queries. maximum 3 queries. minimum 1 query.",
+        length(min = 1, max = 3)
+    )]
     pub queries: Vec<String>,
 }
 
-#[derive(Debug)]
+/// 出典付きの回答
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CitedAnswer {
+    #[schemars(description = "Answer to the question, written for a Slack message.")]
+    pub answer: String,
+    #[schemars(
+        description = "Numbers of the context items (the n in '[n]') that the answer is based on. Empty if none were used."
+    )]
+    #[serde(default)]
+    pub citations: Vec<usize>,
+    #[schemars(
+        description = "How confident you are that the answer is correct and supported by the context, from 0.0 to 1.0.",
+        range(min = 0.0, max = 1.0)
+    )]
+    #[serde(default)]
+    pub confidence: Option<f64>,
+    #[schemars(
+        description = "True if the context does not contain enough information to answer the question."
+    )]
+    #[serde(default)]
+    pub insufficient_context: bool,
+}
+
+/// 回答中の1つの主張と、それが出典に裏付けられているか
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ClaimCheck {
+    #[schemars(description = "A single factual claim made in the answer.")]
+    pub claim: String,
+    #[schemars(description = "Whether the claim is directly supported by the context.")]
+    pub supported: bool,
+}
+
+/// 回答の検証結果
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GroundingCheck {
+    #[schemars(description = "Every factual claim in the answer, each checked against the context.")]
+    pub claims: Vec<ClaimCheck>,
+    #[schemars(
+        description = "The answer rewritten without the unsupported claims, keeping the original language and tone."
+    )]
+    pub revised_answer: String,
+}
+
+impl GroundingCheck {
+    /// 出典に裏付けられた主張の割合（主張がない場合は 1.0）
+    pub fn faithfulness(&self) -> f64 {
+        if self.claims.is_empty() {
+            return 1.0;
+        }
+        let supported = self.claims.iter().filter(|c| c.supported).count();
+        supported as f64 / self.claims.len() as f64
+    }
+
+    /// 裏付けのない主張
+    pub fn unsupported_claims(&self) -> Vec<String> {
+        self.claims
+            .iter()
+            .filter(|c| !c.supported)
+            .map(|c| c.claim.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub enum MessageCategory {
     Question,
     NonQuestion,
-    Unknown,
 }
 
 pub struct ReflectionResult {
     pub category: MessageCategory,
 }
 
-pub struct AgentService;
+/// 会話の発言者
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ChatRole {
+    User,
+    Assistant,
+    /// ツールの実行結果
+    Tool,
+}
+
+/// 会話履歴の1ターン
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChatTurn {
+    pub role: ChatRole,
+    pub content: String,
+    /// 関連するツール呼び出し（Assistant: 要求した呼び出し / Tool: 結果を返す呼び出し）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<LlmToolCall>,
+}
+
+impl ChatTurn {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::User,
+            content: content.into(),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    /// ツール呼び出しを要求したアシスタントのターン
+    pub fn assistant_tool_calls(content: impl Into<String>, tool_calls: Vec<LlmToolCall>) -> Self {
+        Self {
+            role: ChatRole::Assistant,
+            content: content.into(),
+            tool_calls,
+        }
+    }
+
+    /// ツールの実行結果のターン
+    pub fn tool_result(call: LlmToolCall, content: impl Into<String>) -> Self {
+        Self {
+            role: ChatRole::Tool,
+            content: content.into(),
+            tool_calls: vec![call],
+        }
+    }
+}
+
+/// エージェントのドメインサービス
+///
+/// LLM の呼び出しは [`ModelRouter`] 経由で行い、ステージごとに設定されたモデルを使います。
+/// システムプロンプトは [`PromptLibrary`] のテンプレートから組み立てます。
+pub struct AgentService {
+    router: Arc<ModelRouter>,
+    extractor: Extractor,
+    prompts: PromptLibrary,
+}
 
 impl AgentService {
+    pub fn new(router: Arc<ModelRouter>) -> Self {
+        Self {
+            extractor: Extractor::new(router.clone()),
+            router,
+            prompts: PromptLibrary::default(),
+        }
+    }
+
+    /// スペースのプロンプトを指定する
+    pub fn with_prompts(mut self, prompts: PromptLibrary) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    /// 使用しているプロンプト
+    pub fn prompts(&self) -> &PromptLibrary {
+        &self.prompts
+    }
+
+    /// ステージで使うモデル
+    pub fn model(&self, stage: LlmStage) -> Option<&str> {
+        self.router.model(stage)
+    }
+
     pub async fn test(&self, input: &str) -> Result<String> {
         println!("Input: {}", input);
 
@@ -65,68 +228,353 @@ impl AgentService {
         Ok(answer)
     }
 
-    pub async fn reflection(&self, input: &str) -> Result<ReflectionResult> {
-        let openai = openai::Client::from_env();
-
-        let extractor = openai
-            .extractor::<TestResponse>(openai::GPT_4_1_MINI)
-            .preamble("You are judge message is question or not.")
-            .build();
-
-        let response = extractor.extract(input).await;
+    /// 質問かどうかを判定する
+    ///
+    /// 判定できなかった場合は [`ExtractError`] を返します。
+    pub async fn reflection(&self, input: &str) -> Result<ReflectionResult, ExtractError> {
+        let response = self
+            .extractor
+            .extract::<TestResponse>(
+                LlmStage::Reflection,
+                &self.prompts.render(PromptKind::Reflection),
+                input,
+                &[],
+            )
+            .await?;
         println!("Agent response: {:?}", response);
 
-        let category = match response {
-            Ok(res) if res.is_question => Some(MessageCategory::Question),
-            Ok(_) => Some(MessageCategory::NonQuestion),
-            Err(err) => {
-                println!("Failed to extract structured response: {}", err);
-                Some(MessageCategory::Unknown)
-            }
-        };
-
         Ok(ReflectionResult {
-            category: category.unwrap_or(MessageCategory::Unknown),
+            category: response.category,
         })
     }
 
-    pub async fn query_rewriting(&self, input: &str) -> Result<SearchQuery> {
-        let openai = openai::Client::from_env();
-
-        let rewriter = openai
-            .extractor::<SearchQuery>(openai::GPT_4_1_MINI)
-            .preamble(
-                "Extract only search-effective keywords from the user's message. \
-                Remove question words (who, what, when, where, why, how, です, ですか), \
-                particles (は, が, を, に, で, から, まで, etc.), \
-                and sentence-ending expressions. \
-                Output only nouns, names, and core search terms. \
-                Maximum 3 keywords, minimum 1 keyword.\n\n\
-                Examples:\n\
-                Input: '課長はだれですか？' → Output: ['課長']\n\
-                Input: '明日の会議の場所はどこですか？' → Output: ['明日', '会議', '場所']\n\
-                Input: 'プロジェクトの進捗状況を教えて' → Output: ['プロジェクト', '進捗状況']",
+    /// メッセージの意図を分類する
+    ///
+    /// 判定できなかった場合は [`ExtractError`] を返します。
+    pub async fn classify_intent(
+        &self,
+        input: &str,
+    ) -> Result<IntentClassification, ExtractError> {
+        let classification = self
+            .extractor
+            .extract::<IntentClassification>(
+                LlmStage::Reflection,
+                &self.prompts.render(PromptKind::IntentClassification),
+                input,
+                &[],
             )
-            .build();
+            .await?;
+        tracing::debug!("Intent: {:?}", classification);
+        Ok(classification)
+    }
 
-        let rewritten_query = rewriter.extract(input).await?;
+    pub async fn query_rewriting(&self, input: &str) -> Result<SearchQuery, ExtractError> {
+        let language = Language::detect(input).unwrap_or_default();
+        self.query_rewriting_with_history(input, &[], language).await
+    }
+
+    /// 会話履歴を考慮して検索クエリを生成する
+    ///
+    /// 「去年は？」のような続きの質問でも、履歴から話題を補って検索できるようにします。
+    /// プロンプトの例文は質問の言語 `language` に合わせます。
+    pub async fn query_rewriting_with_history(
+        &self,
+        input: &str,
+        history: &[ChatTurn],
+        language: Language,
+    ) -> Result<SearchQuery, ExtractError> {
+        let rewritten_query = self
+            .extractor
+            .extract::<SearchQuery>(
+                LlmStage::QueryRewriting,
+                &self.prompts.render_in(PromptKind::QueryRewriting, language),
+                input,
+                history,
+            )
+            .await?;
         println!("Rewritten query: {:?}", rewritten_query);
         Ok(rewritten_query)
     }
 
     pub async fn answer(&self, input: &str, context: &str) -> Result<String> {
-        println!("Answering with context length: {}", context.len());
-        let openai = openai::Client::from_env();
-        let agent = openai
-            .agent(openai::GPT_4_1_MINI)
-            .preamble(
-                "You are a helpful assistant that answers questions based on provided context.",
-            )
-            .build();
+        self.answer_with_history(input, context, &[]).await
+    }
 
+    /// 会話履歴を考慮して回答する
+    pub async fn answer_with_history(
+        &self,
+        input: &str,
+        context: &str,
+        history: &[ChatTurn],
+    ) -> Result<String> {
+        println!("Answering with context length: {}", context.len());
         let prompt = format!("Context:\n{}\n\nQuestion:\n{}", context, input);
-        let response = agent.prompt(&prompt).await?;
+        let response = self
+            .router
+            .complete(
+                LlmStage::Answer,
+                LlmRequest {
+                    preamble: self.prompts.render(PromptKind::Answer),
+                    prompt,
+                    history: history.to_vec(),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .text;
         println!("Agent response: {}", response);
         Ok(response)
     }
+
+    /// 長いスレッドをコンテキスト用に要約する
+    ///
+    /// 要約の長さは `max_tokens` で制限します。
+    pub async fn summarize_thread(&self, thread: &str, max_tokens: u64) -> Result<String> {
+        let summary = self
+            .router
+            .complete(
+                LlmStage::Summarization,
+                LlmRequest {
+                    preamble: self.prompts.render(PromptKind::ThreadSummary),
+                    prompt: thread.to_string(),
+                    max_tokens: Some(max_tokens),
+                    ..Default::default()
+                },
+            )
+            .await?
+            .text;
+        Ok(summary)
+    }
+
+    /// 会話（チャンネルの履歴・スレッド）を決定事項・未解決の質問・対応事項に要約する
+    ///
+    /// `chunks` は [`crate::chunk_lines`] で分けたメッセージです。複数ある場合は、
+    /// それぞれを [`Self::summarize_thread`] で要約してから全体の要約をまとめます。
+    pub async fn summarize_conversation(
+        &self,
+        chunks: &[String],
+        language: Language,
+    ) -> Result<ConversationSummary> {
+        let input = match chunks {
+            [] => return Ok(ConversationSummary::default()),
+            [chunk] => chunk.clone(),
+            _ => {
+                let notes = futures::future::try_join_all(
+                    chunks
+                        .iter()
+                        .map(|chunk| self.summarize_thread(chunk, CHUNK_SUMMARY_TOKENS)),
+                )
+                .await?;
+                tracing::debug!("Summarized {} chunks", notes.len());
+                notes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, note)| format!("Part {}:\n{}", i + 1, note.trim()))
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            }
+        };
+
+        let summary = self
+            .extractor
+            .extract::<ConversationSummary>(
+                LlmStage::Summarization,
+                &self
+                    .prompts
+                    .render_in(PromptKind::ConversationSummary, language),
+                &input,
+                &[],
+            )
+            .await?;
+        Ok(summary)
+    }
+
+    /// 回答の各主張がコンテキストに裏付けられているか検証する
+    pub async fn verify_grounding(
+        &self,
+        answer: &str,
+        context: &str,
+    ) -> Result<GroundingCheck, ExtractError> {
+        let check = self
+            .extractor
+            .extract::<GroundingCheck>(
+                LlmStage::Verification,
+                &self.prompts.render(PromptKind::Verification),
+                &format!("Context:\n{}\n\nAnswer:\n{}", context, answer),
+                &[],
+            )
+            .await?;
+        tracing::info!(
+            "Grounding check: {:.2} ({} claims)",
+            check.faithfulness(),
+            check.claims.len()
+        );
+        Ok(check)
+    }
+
+    /// 出典付きで回答する
+    ///
+    /// コンテキストの各項目には `[n]` の番号が振られている前提で、
+    /// 回答の根拠にした番号を `citations` として返させます。
+    /// 応答がスキーマに沿わない場合は検証エラーを伝えて再試行し、それでも直らない場合は
+    /// 出典のない回答に置き換えず [`ExtractError`] を返します。
+    /// 回答はコンテキストの言語に関わらず `language` で書かせます。
+    pub async fn answer_with_citations(
+        &self,
+        input: &str,
+        context: &str,
+        history: &[ChatTurn],
+        language: Language,
+    ) -> Result<CitedAnswer, ExtractError> {
+        tracing::debug!("Answering with context length: {}", context.len());
+        let cited: CitedAnswer = self
+            .extractor
+            .generate(
+                LlmStage::Answer,
+                &self.prompts.render_in(PromptKind::CitedAnswer, language),
+                &format!("Context:\n{}\n\nQuestion:\n{}", context, input),
+                history,
+            )
+            .await?;
+        tracing::debug!(
+            "Cited answer: {} chars, {} citations",
+            cited.answer.chars().count(),
+            cited.citations.len()
+        );
+        Ok(cited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+
+    #[tokio::test]
+    async fn test_pipeline_with_mock_provider() {
+        let provider = Arc::new(MockProvider::new([
+            r#"{"category":"Question","is_question":true}"#,
+            r#"{"queries":["課長"]}"#,
+            "課長は山田さんです。",
+        ]));
+        let agent = AgentService::new(Arc::new(ModelRouter::single(provider.clone(), "mock-model")));
+
+        let reflection = agent.reflection("課長はだれですか？").await.unwrap();
+        assert!(matches!(reflection.category, MessageCategory::Question));
+
+        let query = agent.query_rewriting("課長はだれですか？").await.unwrap();
+        assert_eq!(query.queries, vec!["課長"]);
+
+        let answer = agent
+            .answer("課長はだれですか？", "[1] U1: 課長は山田さんです")
+            .await
+            .unwrap();
+        assert_eq!(answer, "課長は山田さんです。");
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].output_schema.is_some());
+        assert!(requests[2].output_schema.is_none());
+        assert!(requests[2].prompt.contains("課長は山田さんです"));
+    }
+
+    #[tokio::test]
+    async fn test_answer_with_citations() {
+        let provider = Arc::new(MockProvider::new([
+            r#"{"answer":"課長は山田さんです。","citations":[2]}"#,
+            "課長は山田さんです。",
+            r#"{"answer":"課長は山田さんです。","citations":[2]}"#,
+            "課長は山田さんです。",
+            "課長は山田さんです。",
+            "課長は山田さんです。",
+        ]));
+        let agent = AgentService::new(Arc::new(ModelRouter::single(provider, "mock-model")));
+        let context = "[1] U1: おはよう\n[2] U2: 課長は山田さんです";
+
+        let cited = agent
+            .answer_with_citations("課長はだれですか？", context, &[], Language::Japanese)
+            .await
+            .unwrap();
+        assert_eq!(cited.citations, vec![2]);
+
+        // スキーマに沿わない応答は検証エラーを伝えて再試行する
+        let repaired = agent
+            .answer_with_citations("課長はだれですか？", context, &[], Language::Japanese)
+            .await
+            .unwrap();
+        assert_eq!(repaired.citations, vec![2]);
+
+        // 直らない場合は出典なしの回答に置き換えずにエラーを返す
+        let error = agent
+            .answer_with_citations("課長はだれですか？", context, &[], Language::Japanese)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            ExtractError::InvalidOutput { attempts: 3, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_answer_confidence_out_of_range() {
+        let provider = Arc::new(MockProvider::new([
+            r#"{"answer":"課長は山田さんです。","citations":[2],"confidence":1.5}"#,
+            r#"{"answer":"課長は山田さんです。","citations":[2],"confidence":0.9}"#,
+        ]));
+        let agent = AgentService::new(Arc::new(ModelRouter::single(
+            provider.clone(),
+            "mock-model",
+        )));
+
+        // 0.0〜1.0 の範囲外の確信度は検証エラーとして再試行する
+        let cited = agent
+            .answer_with_citations(
+                "課長はだれですか？",
+                "[1] U1: おはよう\n[2] U2: 課長は山田さんです",
+                &[],
+                Language::Japanese,
+            )
+            .await
+            .unwrap();
+        assert_eq!(cited.confidence, Some(0.9));
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].prompt.contains("greater than the maximum"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_grounding() {
+        let provider = Arc::new(MockProvider::new([r#"{
+            "claims": [
+                {"claim": "課長は山田さん", "supported": true},
+                {"claim": "山田さんは10年目", "supported": false}
+            ],
+            "revised_answer": "課長は山田さんです。"
+        }"#]));
+        let agent = AgentService::new(Arc::new(ModelRouter::single(provider, "mock-model")));
+
+        let check = agent
+            .verify_grounding("課長は山田さんで、10年目です。", "[1] U1: 課長は山田さんです")
+            .await
+            .unwrap();
+        assert_eq!(check.faithfulness(), 0.5);
+        assert_eq!(check.unsupported_claims(), vec!["山田さんは10年目"]);
+    }
+
+    #[tokio::test]
+    async fn test_reflection_returns_error_on_invalid_output() {
+        let provider = Arc::new(MockProvider::new([
+            "not json",
+            r#"{"category":"Maybe","is_question":true}"#,
+            r#"{"category":"Question"}"#,
+        ]));
+        let agent = AgentService::new(Arc::new(ModelRouter::single(provider, "mock-model")));
+
+        let result = agent.reflection("hello").await;
+        assert!(matches!(
+            result,
+            Err(ExtractError::InvalidOutput { attempts: 3, .. })
+        ));
+    }
 }
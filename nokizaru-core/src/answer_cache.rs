@@ -0,0 +1,327 @@
+//! 回答のキャッシュ
+//!
+//! 正規化した質問文と、検索で得たコンテキストのハッシュをキーに回答を保存します。
+//! コンテキストが変わればキーも変わるため、元のメッセージが更新された場合は自然に再生成されます。
+//! 出典のメッセージが編集・削除されたときは [`AnswerCacheRepository::invalidate_message`] で破棄します。
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{pg::upsert::excluded, prelude::*, PgJsonbExpressionMethods};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared_infrastructure::{schema::answer_cache, DbPool};
+use unicode_normalization::UnicodeNormalization;
+use uuid::Uuid;
+
+use crate::{JobHandler, ScheduledJob};
+
+/// 期限切れのキャッシュを削除する定期実行ジョブの種類
+pub const PURGE_ANSWER_CACHE_JOB: &str = "purge_answer_cache";
+
+/// キャッシュの有効期間の上限（秒、30日）
+const MAX_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// 回答キャッシュの設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnswerCacheSettings {
+    pub enabled: bool,
+    /// キャッシュの有効期間（秒、30日を超える場合は30日）
+    pub ttl_secs: u64,
+}
+
+impl Default for AnswerCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// キャッシュのキーに使う質問文の正規化
+///
+/// 全角・半角の違い（NFKC）、大文字・小文字、連続する空白、末尾の「？」「。」などを無視します。
+pub fn normalize_question(question: &str) -> String {
+    let normalized: String = question.nfkc().collect::<String>().to_lowercase();
+    normalized
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(['?', '!', '.', '。', '、', ' '])
+        .to_string()
+}
+
+/// コンテキストの指紋（SHA-256 の16進表記）
+///
+/// 要素の区切りが変わっても同じ値にならないよう、各要素は長さ付きでハッシュします。
+pub fn context_fingerprint<'a>(parts: impl IntoIterator<Item = &'a str>) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// 回答の出典になったメッセージ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CitedMessage {
+    pub channel_id: String,
+    pub ts: String,
+}
+
+/// キャッシュのキー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnswerCacheKey {
+    pub space_id: Option<Uuid>,
+    /// [`normalize_question`] で正規化した質問文
+    pub question_key: String,
+    /// [`context_fingerprint`] で求めたコンテキストの指紋
+    pub context_hash: String,
+    /// 回答言語（[`Language::as_str`](crate::Language::as_str)）
+    pub language: String,
+}
+
+/// キャッシュした回答
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = answer_cache)]
+pub struct AnswerCacheEntry {
+    pub id: Uuid,
+    pub space_id: Option<Uuid>,
+    pub question_key: String,
+    pub context_hash: String,
+    pub language: String,
+    /// 生成した検索クエリ（文字列の配列）
+    pub queries: serde_json::Value,
+    pub answer: serde_json::Value,
+    /// 出典のメッセージ（[`CitedMessage`] の配列）
+    pub cited_messages: serde_json::Value,
+    pub hit_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// キャッシュの登録内容
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = answer_cache)]
+pub struct NewAnswerCacheEntry {
+    pub space_id: Option<Uuid>,
+    pub question_key: String,
+    pub context_hash: String,
+    pub language: String,
+    pub queries: serde_json::Value,
+    pub answer: serde_json::Value,
+    pub cited_messages: serde_json::Value,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl NewAnswerCacheEntry {
+    pub fn new(
+        key: AnswerCacheKey,
+        queries: &[String],
+        answer: serde_json::Value,
+        cited: &[CitedMessage],
+        ttl_secs: u64,
+    ) -> Self {
+        let ttl = chrono::Duration::try_seconds(ttl_secs.min(MAX_TTL_SECS) as i64)
+            .unwrap_or_default();
+        Self {
+            space_id: key.space_id,
+            question_key: key.question_key,
+            context_hash: key.context_hash,
+            language: key.language,
+            queries: serde_json::json!(queries),
+            answer,
+            cited_messages: serde_json::to_value(cited).unwrap_or_default(),
+            expires_at: Utc::now() + ttl,
+        }
+    }
+}
+
+/// 回答キャッシュのリポジトリ
+pub struct AnswerCacheRepository {
+    pool: DbPool,
+}
+
+impl AnswerCacheRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 有効期限内のキャッシュを探す（見つかった場合はヒット数を加算する）
+    pub async fn find(&self, key: &AnswerCacheKey) -> Result<Option<AnswerCacheEntry>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let entry = diesel::update(
+            answer_cache::table
+                .filter(answer_cache::space_id.is_not_distinct_from(key.space_id))
+                .filter(answer_cache::question_key.eq(&key.question_key))
+                .filter(answer_cache::context_hash.eq(&key.context_hash))
+                .filter(answer_cache::language.eq(&key.language))
+                .filter(answer_cache::expires_at.gt(Utc::now())),
+        )
+        .set(answer_cache::hit_count.eq(answer_cache::hit_count + 1))
+        .returning(AnswerCacheEntry::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+
+        Ok(entry)
+    }
+
+    /// 同じ質問に対して直近に生成した検索クエリ
+    ///
+    /// コンテキストが変わっていても検索クエリは再利用できるため、クエリ生成を省略するために使います。
+    pub async fn find_queries(
+        &self,
+        space_id: Option<Uuid>,
+        question_key: &str,
+        language: &str,
+    ) -> Result<Option<Vec<String>>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let queries: Option<serde_json::Value> = answer_cache::table
+            .filter(answer_cache::space_id.is_not_distinct_from(space_id))
+            .filter(answer_cache::question_key.eq(question_key))
+            .filter(answer_cache::language.eq(language))
+            .filter(answer_cache::expires_at.gt(Utc::now()))
+            .order(answer_cache::created_at.desc())
+            .select(answer_cache::queries)
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(queries
+            .and_then(|queries| serde_json::from_value::<Vec<String>>(queries).ok())
+            .filter(|queries| !queries.is_empty()))
+    }
+
+    /// キャッシュを登録（同じキーがあれば置き換える）
+    pub async fn store(&self, entry: &NewAnswerCacheEntry) -> Result<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        diesel::insert_into(answer_cache::table)
+            .values(entry)
+            .on_conflict((
+                answer_cache::space_id,
+                answer_cache::question_key,
+                answer_cache::context_hash,
+                answer_cache::language,
+            ))
+            .do_update()
+            .set((
+                answer_cache::queries.eq(excluded(answer_cache::queries)),
+                answer_cache::answer.eq(excluded(answer_cache::answer)),
+                answer_cache::cited_messages.eq(excluded(answer_cache::cited_messages)),
+                answer_cache::hit_count.eq(0),
+                answer_cache::created_at.eq(Utc::now()),
+                answer_cache::expires_at.eq(excluded(answer_cache::expires_at)),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 指定したメッセージを出典とするキャッシュを破棄する（破棄した件数を返す）
+    pub async fn invalidate_message(&self, channel_id: &str, ts: &str) -> Result<usize> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let cited = serde_json::json!([CitedMessage {
+            channel_id: channel_id.to_string(),
+            ts: ts.to_string(),
+        }]);
+        let deleted = diesel::delete(
+            answer_cache::table.filter(answer_cache::cited_messages.contains(cited)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted)
+    }
+
+    /// 有効期限切れのキャッシュを削除する（削除した件数を返す）
+    pub async fn purge_expired(&self) -> Result<usize> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let deleted =
+            diesel::delete(answer_cache::table.filter(answer_cache::expires_at.le(Utc::now())))
+                .execute(&mut conn)
+                .await?;
+
+        Ok(deleted)
+    }
+}
+
+/// 定期実行ジョブ `purge_answer_cache`
+#[async_trait]
+impl JobHandler for AnswerCacheRepository {
+    async fn run(&self, _job: &ScheduledJob) -> Result<()> {
+        let deleted = self.purge_expired().await?;
+        tracing::info!("🧹 Purged {} expired cached answers", deleted);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_question_and_fingerprint() {
+        assert_eq!(
+            normalize_question("  ＡＰＩ　トークンは どこ？ "),
+            normalize_question("api トークンは  どこ")
+        );
+        assert_eq!(
+            normalize_question("Who owns the Deploy pipeline?"),
+            "who owns the deploy pipeline"
+        );
+        assert_ne!(
+            normalize_question("課長は？"),
+            normalize_question("部長は？")
+        );
+
+        let fingerprint = context_fingerprint(["a", "bc"]);
+        assert_eq!(fingerprint.len(), 64);
+        assert_eq!(fingerprint, context_fingerprint(["a", "bc"]));
+        assert_ne!(fingerprint, context_fingerprint(["ab", "c"]));
+    }
+
+    #[test]
+    fn test_ttl_is_capped() {
+        let key = AnswerCacheKey {
+            space_id: None,
+            question_key: "q".to_string(),
+            context_hash: "h".to_string(),
+            language: "ja".to_string(),
+        };
+        let entry = NewAnswerCacheEntry::new(key, &[], serde_json::json!({}), &[], u64::MAX);
+        let max = Utc::now() + chrono::Duration::days(30);
+        assert!(entry.expires_at <= max);
+        assert!(entry.expires_at > max - chrono::Duration::minutes(1));
+    }
+}
@@ -0,0 +1,338 @@
+//! チャンネルのダイジェストの定期配信
+//!
+//! チャンネルごとに毎日・毎週の配信を登録し、期間中の要約・盛り上がったスレッド・未回答の質問を
+//! 指定したチャンネルや DM に投稿します。配信時刻は登録ごとのタイムゾーンで解釈します。
+//!
+//! 登録は種類 [`CHANNEL_DIGEST_JOB`] の定期実行ジョブとして保存し、[`crate::Scheduler`] が
+//! 他のジョブと同じく確保してから実行するため、複数のレプリカで動かしても二重に配信しません。
+
+use std::fmt;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{CronSchedule, MissedRunPolicy, NewScheduledJob, ScheduledJob, SchedulerError};
+
+/// ダイジェストの配信ジョブの種類
+pub const CHANNEL_DIGEST_JOB: &str = "channel_digest";
+
+/// ダイジェストの設定
+///
+/// 配信は定期実行ジョブとして動くため、[`crate::SchedulerSettings::enabled`] も有効にしてください。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DigestSettings {
+    pub enabled: bool,
+    /// 登録時にタイムゾーンを省略した場合のタイムゾーン（IANA 名）
+    pub time_zone: String,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            time_zone: "Asia/Tokyo".to_string(),
+        }
+    }
+}
+
+/// 配信の頻度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "daily" => Some(DigestFrequency::Daily),
+            "weekly" => Some(DigestFrequency::Weekly),
+            _ => None,
+        }
+    }
+
+    /// 1回の配信で要約する期間
+    pub fn period(&self) -> Duration {
+        match self {
+            DigestFrequency::Daily => Duration::days(1),
+            DigestFrequency::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// 配信のスケジュール
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestSchedule {
+    pub frequency: DigestFrequency,
+    /// 配信する曜日（毎週の場合のみ）
+    pub weekday: Option<Weekday>,
+    pub time: NaiveTime,
+    pub time_zone: Tz,
+}
+
+impl DigestSchedule {
+    /// 配信時刻の cron 式（5項目、曜日は名前で指定する）
+    pub fn cron(&self) -> String {
+        let weekday = match (self.frequency, self.weekday) {
+            (DigestFrequency::Weekly, Some(weekday)) => weekday.to_string(),
+            _ => "*".to_string(),
+        };
+        format!(
+            "{} {} * * {}",
+            self.time.format("%-M"),
+            self.time.format("%-H"),
+            weekday
+        )
+    }
+
+    /// `now` より後の次の配信時刻
+    pub fn next_after(&self, now: DateTime<Utc>) -> Result<DateTime<Utc>, SchedulerError> {
+        CronSchedule::parse(&self.cron(), self.time_zone.name())?
+            .next_after(now)
+            .ok_or_else(|| SchedulerError::InvalidCron {
+                expression: self.cron(),
+                reason: "no upcoming run".to_string(),
+            })
+    }
+}
+
+impl fmt::Display for DigestSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const WEEKDAYS: [&str; 7] = ["月", "火", "水", "木", "金", "土", "日"];
+        match (self.frequency, self.weekday) {
+            (DigestFrequency::Weekly, Some(weekday)) => write!(
+                f,
+                "毎週{}曜",
+                WEEKDAYS[weekday.num_days_from_monday() as usize]
+            )?,
+            _ => write!(f, "毎日")?,
+        }
+        write!(f, " {}（{}）", self.time.format("%H:%M"), self.time_zone)
+    }
+}
+
+/// 配信ジョブのペイロード
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestPayload {
+    /// 要約するチャンネル
+    pub source_channel_id: String,
+    /// 投稿先のチャンネル（DM の場合は D から始まる会話ID）
+    pub target_channel_id: String,
+    /// 登録したユーザー（閲覧権限の確認に使う）
+    pub created_by: String,
+    pub frequency: DigestFrequency,
+    /// 配信する曜日（毎週の場合のみ）
+    #[serde(default)]
+    pub weekday: Option<Weekday>,
+    pub time: NaiveTime,
+}
+
+impl DigestPayload {
+    pub fn from_value(payload: &serde_json::Value) -> Result<Self> {
+        let payload: Self =
+            serde_json::from_value(payload.clone()).context("Invalid digest payload")?;
+        anyhow::ensure!(
+            !payload.source_channel_id.is_empty(),
+            "source_channel_id is empty"
+        );
+        anyhow::ensure!(
+            !payload.target_channel_id.is_empty(),
+            "target_channel_id is empty"
+        );
+        anyhow::ensure!(
+            payload.frequency == DigestFrequency::Daily || payload.weekday.is_some(),
+            "weekday is required for weekly digests"
+        );
+        Ok(payload)
+    }
+}
+
+/// 登録済みの配信
+#[derive(Debug, Clone)]
+pub struct DigestSubscription {
+    pub id: Uuid,
+    pub source_channel_id: String,
+    pub target_channel_id: String,
+    pub created_by: String,
+    pub schedule: DigestSchedule,
+    /// 次に配信する時刻（実行中は予定していた時刻）
+    pub next_run_at: DateTime<Utc>,
+}
+
+impl DigestSubscription {
+    /// 配信ジョブから登録内容を取り出す
+    pub fn from_job(job: &ScheduledJob) -> Result<Self> {
+        anyhow::ensure!(
+            job.kind == CHANNEL_DIGEST_JOB,
+            "Not a digest job: {}",
+            job.kind
+        );
+        let payload = DigestPayload::from_value(&job.payload)?;
+        let time_zone: Tz = job
+            .time_zone
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Unknown time zone: {}", job.time_zone))?;
+        Ok(Self {
+            id: job.id,
+            source_channel_id: payload.source_channel_id,
+            target_channel_id: payload.target_channel_id,
+            created_by: payload.created_by,
+            schedule: DigestSchedule {
+                frequency: payload.frequency,
+                weekday: payload.weekday,
+                time: payload.time,
+                time_zone,
+            },
+            next_run_at: job.next_run_at,
+        })
+    }
+
+    /// 一覧などに表示する短いID
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+}
+
+/// 配信の登録内容
+#[derive(Debug, Clone)]
+pub struct NewDigestSubscription {
+    pub space_id: Option<Uuid>,
+    pub source_channel_id: String,
+    pub target_channel_id: String,
+    pub schedule: DigestSchedule,
+    pub created_by: String,
+}
+
+impl NewDigestSubscription {
+    pub fn new(
+        space_id: Option<Uuid>,
+        source_channel_id: impl Into<String>,
+        target_channel_id: impl Into<String>,
+        schedule: &DigestSchedule,
+        created_by: impl Into<String>,
+    ) -> Self {
+        Self {
+            space_id,
+            source_channel_id: source_channel_id.into(),
+            target_channel_id: target_channel_id.into(),
+            schedule: *schedule,
+            created_by: created_by.into(),
+        }
+    }
+
+    /// 保存する配信ジョブ
+    ///
+    /// 停止中に過ぎた配信は、遅れても1回だけ配信します。
+    pub fn job(&self) -> Result<NewScheduledJob, SchedulerError> {
+        let payload = DigestPayload {
+            source_channel_id: self.source_channel_id.clone(),
+            target_channel_id: self.target_channel_id.clone(),
+            created_by: self.created_by.clone(),
+            frequency: self.schedule.frequency,
+            weekday: self.schedule.weekday,
+            time: self.schedule.time,
+        };
+        Ok(NewScheduledJob {
+            space_id: self.space_id,
+            name: format!("digest #{}", self.source_channel_id),
+            kind: CHANNEL_DIGEST_JOB.to_string(),
+            cron: self.schedule.cron(),
+            time_zone: self.schedule.time_zone.name().to_string(),
+            payload: serde_json::to_value(&payload).map_err(anyhow::Error::from)?,
+            missed_run_policy: MissedRunPolicy::RunOnce.as_str().to_string(),
+            next_run_at: self.schedule.next_after(Utc::now())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_after() {
+        let tokyo = DigestSchedule {
+            frequency: DigestFrequency::Daily,
+            weekday: None,
+            time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            time_zone: chrono_tz::Asia::Tokyo,
+        };
+        assert_eq!(tokyo.cron(), "0 9 * * *");
+        // 2026-10-18 08:00 JST → 当日 09:00 JST、09:00 ちょうどなら翌日
+        let now = Utc.with_ymd_and_hms(2026, 10, 17, 23, 0, 0).unwrap();
+        assert_eq!(
+            tokyo.next_after(now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            tokyo
+                .next_after(Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap())
+                .unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap()
+        );
+
+        // 2026-10-18 は日曜。毎週月曜 09:00 JST
+        let weekly = DigestSchedule {
+            frequency: DigestFrequency::Weekly,
+            weekday: Some(Weekday::Mon),
+            ..tokyo
+        };
+        assert_eq!(weekly.cron(), "0 9 * * Mon");
+        assert_eq!(
+            weekly.next_after(now).unwrap(),
+            Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap()
+        );
+        assert_eq!(weekly.to_string(), "毎週月曜 09:00（Asia/Tokyo）");
+
+        // 配信ジョブのペイロードから同じスケジュールを取り出せる
+        let job = NewDigestSubscription::new(None, "C1", "C2", &weekly, "U1")
+            .job()
+            .unwrap();
+        let subscription = DigestSubscription::from_job(&ScheduledJob {
+            id: Uuid::nil(),
+            space_id: job.space_id,
+            name: job.name,
+            kind: job.kind,
+            cron: job.cron,
+            time_zone: job.time_zone,
+            payload: job.payload,
+            missed_run_policy: job.missed_run_policy,
+            paused: false,
+            next_run_at: job.next_run_at,
+            last_run_at: None,
+            last_error: None,
+            claimed_until: None,
+            created_at: Utc::now(),
+        })
+        .unwrap();
+        assert_eq!(subscription.schedule, weekly);
+        assert_eq!(subscription.source_channel_id, "C1");
+        assert_eq!(subscription.target_channel_id, "C2");
+
+        // 夏時間の開始日（2026-03-08）に存在しない 02:30 は 03:30 に配信する
+        let new_york = DigestSchedule {
+            time: NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+            time_zone: chrono_tz::America::New_York,
+            ..tokyo
+        };
+        assert_eq!(
+            new_york
+                .next_after(Utc.with_ymd_and_hms(2026, 3, 8, 5, 0, 0).unwrap())
+                .unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 8, 7, 30, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! オフライン評価
+//!
+//! 質問・期待する出典・参考回答のデータセットに対してパイプラインを実行した結果から、
+//! 検索の recall@k / MRR、質問判定の正解率、回答の類似度を求めます。
+//! レポートは実行日時などを含めず、同じ結果からは同じ内容になるため、
+//! プロンプトや設定を変えた前後のレポートを diff で比較できます。
+
+use std::fmt::Write as _;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{CitedMessage, Language};
+
+/// 評価データの1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub id: String,
+    pub question: String,
+    /// 質問として判定されるべきか（省略時は判定を評価しない）
+    #[serde(default)]
+    pub is_question: Option<bool>,
+    /// 検索で見つかるべきメッセージ
+    #[serde(default)]
+    pub expected_sources: Vec<CitedMessage>,
+    /// 参考回答（省略時は回答を評価しない）
+    #[serde(default)]
+    pub reference_answer: Option<String>,
+    /// 回答言語（省略時は質問から推定する）
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+/// データセットを読み込む
+///
+/// 1行1件の JSONL（空行と `#` で始まる行は無視）か、JSON の配列を受け付けます。
+pub fn load_dataset(text: &str) -> Result<Vec<EvalCase>> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text).context("Failed to parse dataset as JSON array");
+    }
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse dataset line {}", i + 1))
+        })
+        .collect()
+}
+
+/// 上位 k 件に含まれた期待する出典の割合（期待する出典がない場合は None）
+///
+/// `ranks` は期待する出典ごとの順位（1始まり、見つからない場合は None）です。
+pub fn recall_at_k(ranks: &[Option<usize>], k: usize) -> Option<f64> {
+    if ranks.is_empty() {
+        return None;
+    }
+    let found = ranks
+        .iter()
+        .filter(|rank| rank.is_some_and(|rank| rank <= k))
+        .count();
+    Some(found as f64 / ranks.len() as f64)
+}
+
+/// 最も上位の期待する出典の順位の逆数（見つからない場合は 0.0）
+pub fn reciprocal_rank(ranks: &[Option<usize>]) -> Option<f64> {
+    if ranks.is_empty() {
+        return None;
+    }
+    Some(
+        ranks
+            .iter()
+            .flatten()
+            .min()
+            .map_or(0.0, |rank| 1.0 / *rank as f64),
+    )
+}
+
+/// 回答と参考回答の類似度（文字 bigram の F1、0.0〜1.0）
+///
+/// 日本語は単語で区切れないため、NFKC・小文字化して空白を除いた文字の bigram で比較します。
+pub fn answer_similarity(answer: &str, reference: &str) -> f64 {
+    let answer = bigrams(answer);
+    let reference = bigrams(reference);
+    if answer.is_empty() && reference.is_empty() {
+        return 1.0;
+    }
+    if answer.is_empty() || reference.is_empty() {
+        return 0.0;
+    }
+
+    let mut remaining = reference.clone();
+    let mut overlap = 0;
+    for bigram in &answer {
+        if let Some(pos) = remaining.iter().position(|r| r == bigram) {
+            remaining.swap_remove(pos);
+            overlap += 1;
+        }
+    }
+    let precision = overlap as f64 / answer.len() as f64;
+    let recall = overlap as f64 / reference.len() as f64;
+    if overlap == 0 {
+        0.0
+    } else {
+        2.0 * precision * recall / (precision + recall)
+    }
+}
+
+fn bigrams(text: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = text
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if chars.len() == 1 {
+        return vec![(chars[0], ' ')];
+    }
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// 1件の評価結果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub id: String,
+    pub question: String,
+    pub expected_is_question: Option<bool>,
+    pub predicted_is_question: Option<bool>,
+    /// 生成した検索クエリ
+    pub queries: Vec<String>,
+    /// 期待する出典ごとの検索結果での順位（1始まり、見つからない場合は None）
+    pub source_ranks: Vec<Option<usize>>,
+    pub answer: Option<String>,
+    /// 参考回答との類似度
+    pub similarity: Option<f64>,
+    /// 途中で失敗した場合のエラー
+    pub error: Option<String>,
+}
+
+impl CaseResult {
+    pub fn new(case: &EvalCase) -> Self {
+        Self {
+            id: case.id.clone(),
+            question: case.question.clone(),
+            expected_is_question: case.is_question,
+            ..Default::default()
+        }
+    }
+
+    /// 質問判定が正しかったか（評価対象外の場合は None）
+    pub fn reflection_correct(&self) -> Option<bool> {
+        Some(self.expected_is_question? == self.predicted_is_question?)
+    }
+}
+
+/// 評価結果の集計
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalSummary {
+    pub cases: usize,
+    pub errors: usize,
+    /// recall@k の平均（出典を評価したケースのみ）
+    pub recall_at_k: Option<f64>,
+    /// MRR（出典を評価したケースのみ）
+    pub mrr: Option<f64>,
+    /// 質問判定の正解率
+    pub reflection_accuracy: Option<f64>,
+    /// 回答の類似度の平均
+    pub answer_similarity: Option<f64>,
+}
+
+/// 評価レポート
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    /// recall@k の k
+    pub k: usize,
+    pub summary: EvalSummary,
+    /// データセットの順の結果
+    pub results: Vec<CaseResult>,
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+impl EvalReport {
+    pub fn new(k: usize, results: Vec<CaseResult>) -> Self {
+        let summary = EvalSummary {
+            cases: results.len(),
+            errors: results.iter().filter(|r| r.error.is_some()).count(),
+            recall_at_k: mean(
+                results
+                    .iter()
+                    .filter_map(|r| recall_at_k(&r.source_ranks, k)),
+            ),
+            mrr: mean(
+                results
+                    .iter()
+                    .filter_map(|r| reciprocal_rank(&r.source_ranks)),
+            ),
+            reflection_accuracy: mean(
+                results
+                    .iter()
+                    .filter_map(CaseResult::reflection_correct)
+                    .map(|correct| if correct { 1.0 } else { 0.0 }),
+            ),
+            answer_similarity: mean(results.iter().filter_map(|r| r.similarity)),
+        };
+        Self {
+            k,
+            summary,
+            results,
+        }
+    }
+
+    /// Markdown のレポート（実行ごとの差分を diff で比較できるよう、値は小数点以下3桁に揃える）
+    pub fn to_markdown(&self) -> String {
+        let metric = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.3}", v));
+        let summary = &self.summary;
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# Evaluation report\n");
+        let _ = writeln!(out, "| metric | value |");
+        let _ = writeln!(out, "|---|---|");
+        let _ = writeln!(out, "| cases | {} |", summary.cases);
+        let _ = writeln!(out, "| errors | {} |", summary.errors);
+        let _ = writeln!(
+            out,
+            "| recall@{} | {} |",
+            self.k,
+            metric(summary.recall_at_k)
+        );
+        let _ = writeln!(out, "| MRR | {} |", metric(summary.mrr));
+        let _ = writeln!(
+            out,
+            "| reflection accuracy | {} |",
+            metric(summary.reflection_accuracy)
+        );
+        let _ = writeln!(
+            out,
+            "| answer similarity | {} |",
+            metric(summary.answer_similarity)
+        );
+
+        let _ = writeln!(out, "\n## Cases\n");
+        let _ = writeln!(
+            out,
+            "| id | reflection | recall@{} | RR | similarity | error |",
+            self.k
+        );
+        let _ = writeln!(out, "|---|---|---|---|---|---|");
+        for result in &self.results {
+            let reflection = match result.reflection_correct() {
+                Some(true) => "ok",
+                Some(false) => "NG",
+                None => "-",
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                result.id,
+                reflection,
+                metric(recall_at_k(&result.source_ranks, self.k)),
+                metric(reciprocal_rank(&result.source_ranks)),
+                metric(result.similarity),
+                result
+                    .error
+                    .as_deref()
+                    .map(|e| e.replace(['|', '\n'], " "))
+                    .unwrap_or_default(),
+            );
+        }
+
+        for result in &self.results {
+            let _ = writeln!(out, "\n### {}\n", result.id);
+            let _ = writeln!(out, "- question: {}", result.question);
+            let _ = writeln!(out, "- queries: {}", result.queries.join(" / "));
+            let ranks: Vec<String> = result
+                .source_ranks
+                .iter()
+                .map(|rank| rank.map_or("-".to_string(), |rank| rank.to_string()))
+                .collect();
+            let _ = writeln!(out, "- source ranks: {}", ranks.join(", "));
+            if let Some(answer) = &result.answer {
+                let _ = writeln!(out, "\n```\n{}\n```", answer.trim_end());
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_and_report() {
+        let dataset = load_dataset(
+            r#"
+# コメント行
+{"id":"q1","question":"デプロイ担当は？","is_question":true,"expected_sources":[{"channel_id":"C1","ts":"1.0"},{"channel_id":"C1","ts":"2.0"}],"reference_answer":"山田さんです"}
+{"id":"q2","question":"おはようございます","is_question":false}
+"#,
+        )
+        .unwrap();
+        assert_eq!(dataset.len(), 2);
+        assert_eq!(dataset[0].expected_sources.len(), 2);
+        assert_eq!(dataset[1].reference_answer, None);
+
+        let ranks = [Some(3), None];
+        assert_eq!(recall_at_k(&ranks, 5), Some(0.5));
+        assert_eq!(recall_at_k(&ranks, 2), Some(0.0));
+        assert_eq!(reciprocal_rank(&ranks), Some(1.0 / 3.0));
+        assert_eq!(reciprocal_rank(&[None]), Some(0.0));
+        assert_eq!(recall_at_k(&[], 5), None);
+
+        assert!((answer_similarity("山田さんです", "山田 さんです。") - 10.0 / 11.0).abs() < 1e-9);
+        assert_eq!(answer_similarity("ＡＢ", "ab"), 1.0);
+        assert_eq!(answer_similarity("abc", "xyz"), 0.0);
+
+        let mut first = CaseResult::new(&dataset[0]);
+        first.predicted_is_question = Some(true);
+        first.source_ranks = vec![Some(1), None];
+        first.similarity = Some(0.5);
+        let mut second = CaseResult::new(&dataset[1]);
+        second.predicted_is_question = Some(true);
+
+        let report = EvalReport::new(5, vec![first, second]);
+        assert_eq!(report.summary.recall_at_k, Some(0.5));
+        assert_eq!(report.summary.mrr, Some(1.0));
+        assert_eq!(report.summary.reflection_accuracy, Some(0.5));
+        assert_eq!(report.summary.answer_similarity, Some(0.5));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| recall@5 | 0.500 |"));
+        assert!(markdown.contains("| q2 | NG | - | - | - |  |"));
+        assert_eq!(markdown, report.to_markdown());
+    }
+}
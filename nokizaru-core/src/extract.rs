@@ -0,0 +1,457 @@
+//! 構造化出力の抽出
+//!
+//! LLM に JSON スキーマに沿った出力をさせ、スキーマで検証してから型に変換します。
+//! 検証に失敗した場合はエラー内容をモデルに伝えて再試行し、
+//! それでも直らない場合は既定値に置き換えず [`ExtractError`] を返します。
+
+use std::sync::Arc;
+
+use schemars::{schema_for, JsonSchema};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    llm::{LlmError, LlmRequest, LlmStage, ModelRouter},
+    ChatTurn,
+};
+
+/// 構造化出力の抽出エラー
+#[derive(Error, Debug)]
+pub enum ExtractError {
+    #[error(transparent)]
+    Llm(#[from] LlmError),
+
+    #[error("Failed to build JSON schema: {0}")]
+    Schema(#[from] serde_json::Error),
+
+    #[error("Invalid structured output after {attempts} attempts: {}", errors.join("; "))]
+    InvalidOutput {
+        attempts: usize,
+        /// 最後の出力の検証エラー
+        errors: Vec<String>,
+        /// 最後の出力
+        output: String,
+    },
+}
+
+/// 構造化出力を抽出するヘルパー
+///
+/// ```rust,ignore
+/// let extractor = Extractor::new(router).with_max_attempts(2);
+/// let query: SearchQuery = extractor
+///     .extract(LlmStage::QueryRewriting, "Extract search keywords.", input, &[])
+///     .await?;
+/// ```
+#[derive(Clone)]
+pub struct Extractor {
+    router: Arc<ModelRouter>,
+    max_attempts: usize,
+}
+
+impl Extractor {
+    /// 既定の最大試行回数（初回 + 再試行）
+    const DEFAULT_MAX_ATTEMPTS: usize = 3;
+
+    pub fn new(router: Arc<ModelRouter>) -> Self {
+        Self {
+            router,
+            max_attempts: Self::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+
+    /// 最大試行回数（初回を含む）を設定する
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// 構造化出力（JSON）で応答させ、スキーマで検証して型に変換する
+    pub async fn extract<T>(
+        &self,
+        stage: LlmStage,
+        preamble: &str,
+        input: &str,
+        history: &[ChatTurn],
+    ) -> Result<T, ExtractError>
+    where
+        T: JsonSchema + DeserializeOwned,
+    {
+        let preamble = format!(
+            "You are an AI assistant whose purpose is to extract structured data \
+            from the provided text. Respond with JSON matching the given schema.\n\n{}",
+            preamble
+        );
+        self.generate(stage, &preamble, input, history).await
+    }
+
+    /// 構造化出力（JSON）で応答させ、スキーマで検証して型に変換する
+    ///
+    /// [`Extractor::extract`] と異なり、`preamble` に抽出用の指示を加えずにそのまま使います。
+    /// 回答の生成など、抽出以外の用途で構造化出力を使う場合に使います。
+    pub async fn generate<T>(
+        &self,
+        stage: LlmStage,
+        preamble: &str,
+        input: &str,
+        history: &[ChatTurn],
+    ) -> Result<T, ExtractError>
+    where
+        T: JsonSchema + DeserializeOwned,
+    {
+        let schema = serde_json::to_value(schema_for!(T))?;
+
+        let mut history = history.to_vec();
+        let mut prompt = input.to_string();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let response = self
+                .router
+                .complete(
+                    stage,
+                    LlmRequest {
+                        preamble: preamble.to_string(),
+                        prompt: prompt.clone(),
+                        history: history.clone(),
+                        output_schema: Some(schema.clone()),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+
+            let errors = match parse_and_validate::<T>(&response.text, &schema) {
+                Ok(value) => return Ok(value),
+                Err(errors) => errors,
+            };
+
+            if attempt >= self.max_attempts {
+                return Err(ExtractError::InvalidOutput {
+                    attempts: attempt,
+                    errors,
+                    output: response.text,
+                });
+            }
+
+            tracing::warn!(
+                "[{}] Invalid structured output (attempt {}/{}): {}",
+                stage,
+                attempt,
+                self.max_attempts,
+                errors.join("; ")
+            );
+
+            // 誤った出力と検証エラーを会話に積んで、修正させる
+            history.push(ChatTurn::user(prompt));
+            history.push(ChatTurn::assistant(response.text));
+            prompt = format!(
+                "Your previous output was invalid:\n- {}\n\n\
+                Submit it again, fixing these errors and following the schema exactly.",
+                errors.join("\n- ")
+            );
+        }
+    }
+}
+
+/// 出力を JSON として読み、スキーマで検証してから型に変換する
+fn parse_and_validate<T: DeserializeOwned>(text: &str, schema: &Value) -> Result<T, Vec<String>> {
+    let value: Value = serde_json::from_str(strip_code_fence(text))
+        .map_err(|e| vec![format!("output is not valid JSON: {}", e)])?;
+
+    let errors = validate(&value, schema);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    serde_json::from_value(value).map_err(|e| vec![e.to_string()])
+}
+
+/// ```json ... ``` で囲まれた出力から中身を取り出す
+fn strip_code_fence(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.trim_start_matches("json").trim())
+        .unwrap_or(text)
+}
+
+/// JSON スキーマで値を検証し、違反の一覧を返す（違反がなければ空）
+///
+/// schemars が生成するスキーマで使われるキーワード
+/// （type / enum / const / properties / required / items / 長さ・範囲の制約 / $ref / anyOf / oneOf / allOf）
+/// に対応しています。
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(value, schema, schema, "$", &mut errors);
+    errors
+}
+
+fn validate_at(value: &Value, schema: &Value, root: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // true / false スキーマ
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{}: no value is allowed here", path));
+        }
+        return;
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Some(target) => validate_at(value, target, root, path, errors),
+            None => errors.push(format!(
+                "{}: unresolvable schema reference {}",
+                path, reference
+            )),
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| is_type(value, t)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of {}",
+                path,
+                value,
+                Value::Array(options.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}, got {}", path, expected, value));
+        }
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(variants) = schema.get(keyword).and_then(Value::as_array) {
+            let matches = variants
+                .iter()
+                .filter(|variant| {
+                    let mut variant_errors = Vec::new();
+                    validate_at(value, variant, root, path, &mut variant_errors);
+                    variant_errors.is_empty()
+                })
+                .count();
+            let valid = if keyword == "oneOf" {
+                matches == 1
+            } else {
+                matches > 0
+            };
+            if !valid {
+                errors.push(format!(
+                    "{}: {} does not match the allowed variants",
+                    path, value
+                ));
+            }
+        }
+    }
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            validate_at(value, sub, root, path, errors);
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        errors.push(format!("{}: missing required property '{}'", path, key));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, item) in object {
+                let item_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(item_schema) => validate_at(item, item_schema, root, &item_path, errors),
+                    None => {
+                        if let Some(additional) = schema.get("additionalProperties") {
+                            validate_at(item, additional, root, &item_path, errors);
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (items.len() as u64) < min {
+                    errors.push(format!(
+                        "{}: expected at least {} items, got {}",
+                        path,
+                        min,
+                        items.len()
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if items.len() as u64 > max {
+                    errors.push(format!(
+                        "{}: expected at most {} items, got {}",
+                        path,
+                        max,
+                        items.len()
+                    ));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item, item_schema, root, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let len = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    errors.push(format!("{}: expected at least {} characters", path, min));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    errors.push(format!("{}: expected at most {} characters", path, max));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let n = number.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if n < min {
+                    errors.push(format!("{}: {} is less than the minimum {}", path, n, min));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if n > max {
+                    errors.push(format!(
+                        "{}: {} is greater than the maximum {}",
+                        path, n, max
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `#/$defs/Name` 形式の参照を解決する
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    root.pointer(pointer)
+}
+
+fn is_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    enum Color {
+        Red,
+        Blue,
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    struct Item {
+        color: Color,
+        #[schemars(length(min = 1, max = 2))]
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_validate_reports_schema_violations() {
+        let schema = serde_json::to_value(schema_for!(Item)).unwrap();
+
+        let valid = serde_json::json!({"color": "Red", "tags": ["a"]});
+        assert!(validate(&valid, &schema).is_empty());
+
+        let invalid = serde_json::json!({"color": "Green", "tags": []});
+        let errors = validate(&invalid, &schema);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].contains("$.color"));
+        assert!(errors[1].contains("at least 1 items"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_with_validation_errors() {
+        let provider = Arc::new(MockProvider::new([
+            r#"{"color":"Green","tags":["a"]}"#,
+            "```json\n{\"color\":\"Blue\",\"tags\":[\"a\"]}\n```",
+        ]));
+        let extractor = Extractor::new(Arc::new(ModelRouter::single(
+            provider.clone(),
+            "mock-model",
+        )));
+
+        let item: Item = extractor
+            .extract(LlmStage::Reflection, "", "青いもの", &[])
+            .await
+            .unwrap();
+        assert!(matches!(item.color, Color::Blue));
+        assert_eq!(item.tags, vec!["a"]);
+
+        // 再試行では誤った出力と検証エラーを渡す
+        let retry = &provider.requests()[1];
+        assert_eq!(retry.history.len(), 2);
+        assert!(retry.prompt.contains("$.color"));
+    }
+
+    #[tokio::test]
+    async fn test_returns_error_after_max_attempts() {
+        let provider = Arc::new(MockProvider::new(["not json", "still not json"]));
+        let extractor = Extractor::new(Arc::new(ModelRouter::single(
+            provider.clone(),
+            "mock-model",
+        )))
+        .with_max_attempts(2);
+
+        let result = extractor
+            .extract::<Item>(LlmStage::Reflection, "", "青いもの", &[])
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExtractError::InvalidOutput { attempts: 2, .. })
+        ));
+        assert_eq!(provider.requests().len(), 2);
+    }
+}
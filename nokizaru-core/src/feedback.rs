@@ -0,0 +1,376 @@
+//! 回答へのフィードバック
+//!
+//! ボットの回答に付けた 👍/👎 ボタンと、回答へのリアクションを `answer_feedback` に記録します。
+//! 質問・出典・プロンプトのバージョン・モデルは質問応答ログ（`interaction_logs`）を参照し、
+//! スペースごとの「役に立った」割合を [`FeedbackRepository::summary`] で集計します。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::{
+    prelude::*,
+    sql_types::{BigInt, Nullable, Text, Timestamptz, Uuid as SqlUuid},
+};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use shared_infrastructure::{schema::answer_feedback, DbPool};
+use uuid::Uuid;
+
+/// フィードバックの設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedbackSettings {
+    pub enabled: bool,
+    /// 「役に立った」とみなすリアクション（肌の色の指定は無視する）
+    pub helpful_reactions: Vec<String>,
+    /// 「役に立たなかった」とみなすリアクション
+    pub not_helpful_reactions: Vec<String>,
+}
+
+impl Default for FeedbackSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            helpful_reactions: ["+1", "thumbsup", "white_check_mark", "heavy_check_mark"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            not_helpful_reactions: ["-1", "thumbsdown", "x"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+impl FeedbackSettings {
+    /// リアクションの評価（役に立った = true、対象外のリアクションは None）
+    pub fn rating_for_reaction(&self, reaction: &str) -> Option<bool> {
+        let reaction = reaction.split("::").next().unwrap_or(reaction);
+        if self.helpful_reactions.iter().any(|r| r == reaction) {
+            Some(true)
+        } else if self.not_helpful_reactions.iter().any(|r| r == reaction) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// フィードバックの経路
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackSource {
+    /// 回答に付けた 👍/👎 ボタン
+    Button,
+    /// 回答へのリアクション
+    Reaction,
+}
+
+impl FeedbackSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackSource::Button => "button",
+            FeedbackSource::Reaction => "reaction",
+        }
+    }
+}
+
+/// 役に立たなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackReason {
+    Incorrect,
+    Outdated,
+    Irrelevant,
+    Incomplete,
+    Other,
+}
+
+impl FeedbackReason {
+    pub const ALL: [FeedbackReason; 5] = [
+        FeedbackReason::Incorrect,
+        FeedbackReason::Outdated,
+        FeedbackReason::Irrelevant,
+        FeedbackReason::Incomplete,
+        FeedbackReason::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackReason::Incorrect => "incorrect",
+            FeedbackReason::Outdated => "outdated",
+            FeedbackReason::Irrelevant => "irrelevant",
+            FeedbackReason::Incomplete => "incomplete",
+            FeedbackReason::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|reason| reason.as_str() == value)
+    }
+
+    /// モーダルの選択肢に表示する文言
+    pub fn label(&self) -> &'static str {
+        match self {
+            FeedbackReason::Incorrect => "内容が間違っている",
+            FeedbackReason::Outdated => "情報が古い",
+            FeedbackReason::Irrelevant => "質問とずれている",
+            FeedbackReason::Incomplete => "情報が足りない",
+            FeedbackReason::Other => "その他",
+        }
+    }
+}
+
+/// フィードバックの登録内容
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = answer_feedback)]
+pub struct NewAnswerFeedback {
+    pub space_id: Option<Uuid>,
+    pub interaction_log_id: Uuid,
+    pub user_id: String,
+    pub source: String,
+    pub helpful: bool,
+    pub reason: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl NewAnswerFeedback {
+    pub fn new(
+        space_id: Option<Uuid>,
+        interaction_log_id: Uuid,
+        user_id: impl Into<String>,
+        source: FeedbackSource,
+        helpful: bool,
+    ) -> Self {
+        Self {
+            space_id,
+            interaction_log_id,
+            user_id: user_id.into(),
+            source: source.as_str().to_string(),
+            helpful,
+            reason: None,
+            comment: None,
+        }
+    }
+}
+
+/// モデル・プロンプトのバージョンごとのフィードバック
+#[derive(Debug, Clone, PartialEq, QueryableByName, Serialize)]
+pub struct FeedbackBreakdown {
+    #[diesel(sql_type = Text)]
+    pub model: String,
+    /// 回答のプロンプト（cited_answer / agent / answer）のバージョン
+    #[diesel(sql_type = Nullable<Text>)]
+    pub prompt_version: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    pub helpful: i64,
+    #[diesel(sql_type = BigInt)]
+    pub not_helpful: i64,
+}
+
+/// 役に立たなかった理由ごとの件数
+#[derive(Debug, Clone, PartialEq, QueryableByName, Serialize)]
+pub struct FeedbackReasonCount {
+    #[diesel(sql_type = Text)]
+    pub reason: String,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+/// スペースのフィードバックの集計
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FeedbackSummary {
+    pub helpful: i64,
+    pub not_helpful: i64,
+    /// モデル・プロンプトのバージョンごと（件数の多い順）
+    pub breakdown: Vec<FeedbackBreakdown>,
+    /// 役に立たなかった理由ごと（件数の多い順）
+    pub reasons: Vec<FeedbackReasonCount>,
+}
+
+impl FeedbackSummary {
+    pub fn total(&self) -> i64 {
+        self.helpful + self.not_helpful
+    }
+
+    /// 役に立った割合（フィードバックがない場合は None）
+    pub fn helpfulness(&self) -> Option<f64> {
+        (self.total() > 0).then(|| self.helpful as f64 / self.total() as f64)
+    }
+}
+
+/// 集計の共通の条件（$1: space_id, $2: since）
+const FEEDBACK_CONDITIONS: &str = "WHERE f.space_id IS NOT DISTINCT FROM $1 AND f.created_at >= $2";
+
+/// 回答へのフィードバックのリポジトリ
+pub struct FeedbackRepository {
+    pool: DbPool,
+}
+
+impl FeedbackRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// フィードバックを記録する（同じユーザー・経路の評価は上書きする）
+    pub async fn record(&self, feedback: &NewAnswerFeedback) -> Result<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        diesel::insert_into(answer_feedback::table)
+            .values(feedback)
+            .on_conflict((
+                answer_feedback::interaction_log_id,
+                answer_feedback::user_id,
+                answer_feedback::source,
+            ))
+            .do_update()
+            .set((
+                answer_feedback::helpful.eq(feedback.helpful),
+                answer_feedback::reason.eq(&feedback.reason),
+                answer_feedback::comment.eq(&feedback.comment),
+                answer_feedback::updated_at.eq(Utc::now()),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// ボタンで「役に立たなかった」とした評価に理由を記録する
+    pub async fn set_reason(
+        &self,
+        interaction_log_id: Uuid,
+        user_id: &str,
+        reason: FeedbackReason,
+        comment: Option<&str>,
+    ) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let updated = diesel::update(
+            answer_feedback::table
+                .filter(answer_feedback::interaction_log_id.eq(interaction_log_id))
+                .filter(answer_feedback::user_id.eq(user_id))
+                .filter(answer_feedback::source.eq(FeedbackSource::Button.as_str())),
+        )
+        .set((
+            answer_feedback::reason.eq(reason.as_str()),
+            answer_feedback::comment.eq(comment),
+            answer_feedback::updated_at.eq(Utc::now()),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+        Ok(updated > 0)
+    }
+
+    /// 評価を取り消す（リアクションを外した場合）
+    pub async fn remove(
+        &self,
+        interaction_log_id: Uuid,
+        user_id: &str,
+        source: FeedbackSource,
+        helpful: bool,
+    ) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let deleted = diesel::delete(
+            answer_feedback::table
+                .filter(answer_feedback::interaction_log_id.eq(interaction_log_id))
+                .filter(answer_feedback::user_id.eq(user_id))
+                .filter(answer_feedback::source.eq(source.as_str()))
+                .filter(answer_feedback::helpful.eq(helpful)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// `since` 以降のフィードバックを集計する
+    pub async fn summary(
+        &self,
+        space_id: Option<Uuid>,
+        since: DateTime<Utc>,
+    ) -> Result<FeedbackSummary> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let query = format!(
+            "SELECT COALESCE(l.model, 'unknown')::TEXT AS model, \
+               COALESCE(l.prompt_versions->>'cited_answer', l.prompt_versions->>'agent', \
+                 l.prompt_versions->>'answer') AS prompt_version, \
+               COUNT(*) FILTER (WHERE f.helpful) AS helpful, \
+               COUNT(*) FILTER (WHERE NOT f.helpful) AS not_helpful \
+             FROM answer_feedback f JOIN interaction_logs l ON l.id = f.interaction_log_id {} \
+             GROUP BY 1, 2 ORDER BY COUNT(*) DESC",
+            FEEDBACK_CONDITIONS
+        );
+        let breakdown: Vec<FeedbackBreakdown> = diesel::sql_query(query)
+            .bind::<Nullable<SqlUuid>, _>(space_id)
+            .bind::<Timestamptz, _>(since)
+            .load(&mut conn)
+            .await?;
+
+        let query = format!(
+            "SELECT f.reason::TEXT AS reason, COUNT(*) AS count \
+             FROM answer_feedback f {} AND f.reason IS NOT NULL \
+             GROUP BY 1 ORDER BY count DESC",
+            FEEDBACK_CONDITIONS
+        );
+        let reasons = diesel::sql_query(query)
+            .bind::<Nullable<SqlUuid>, _>(space_id)
+            .bind::<Timestamptz, _>(since)
+            .load(&mut conn)
+            .await?;
+
+        Ok(FeedbackSummary {
+            helpful: breakdown.iter().map(|row| row.helpful).sum(),
+            not_helpful: breakdown.iter().map(|row| row.not_helpful).sum(),
+            breakdown,
+            reasons,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reactions_and_summary() {
+        let settings = FeedbackSettings::default();
+        assert_eq!(settings.rating_for_reaction("+1"), Some(true));
+        assert_eq!(settings.rating_for_reaction("+1::skin-tone-3"), Some(true));
+        assert_eq!(settings.rating_for_reaction("thumbsdown"), Some(false));
+        assert_eq!(settings.rating_for_reaction("eyes"), None);
+
+        assert_eq!(
+            FeedbackReason::parse("outdated"),
+            Some(FeedbackReason::Outdated)
+        );
+        assert_eq!(FeedbackReason::parse("rude"), None);
+
+        assert_eq!(FeedbackSummary::default().helpfulness(), None);
+        let summary = FeedbackSummary {
+            helpful: 3,
+            not_helpful: 1,
+            ..Default::default()
+        };
+        assert_eq!(summary.helpfulness(), Some(0.75));
+    }
+}
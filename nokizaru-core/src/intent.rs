@@ -0,0 +1,201 @@
+//! メッセージの意図の分類と振り分け
+//!
+//! チャンネルに投稿されたメッセージを [`MessageIntent`] に分類し、
+//! スペースごとの振り分け表（[`IntentRoutingSettings`]）で対応を決めます。
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// メッセージの意図
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageIntent {
+    /// 過去のやり取りから答えられる質問
+    Question,
+    /// 作業の依頼
+    ActionRequest,
+    /// 不具合の報告
+    BugReport,
+    /// お知らせ
+    Announcement,
+    /// 挨拶・雑談
+    ChitChat,
+    /// 意見・要望
+    Feedback,
+    /// 緊急の障害
+    Incident,
+}
+
+impl MessageIntent {
+    pub const ALL: [MessageIntent; 7] = [
+        MessageIntent::Question,
+        MessageIntent::ActionRequest,
+        MessageIntent::BugReport,
+        MessageIntent::Announcement,
+        MessageIntent::ChitChat,
+        MessageIntent::Feedback,
+        MessageIntent::Incident,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageIntent::Question => "question",
+            MessageIntent::ActionRequest => "action_request",
+            MessageIntent::BugReport => "bug_report",
+            MessageIntent::Announcement => "announcement",
+            MessageIntent::ChitChat => "chit_chat",
+            MessageIntent::Feedback => "feedback",
+            MessageIntent::Incident => "incident",
+        }
+    }
+
+    /// チケットなどに表示する名前
+    pub fn label(&self) -> &'static str {
+        match self {
+            MessageIntent::Question => "質問",
+            MessageIntent::ActionRequest => "作業依頼",
+            MessageIntent::BugReport => "不具合報告",
+            MessageIntent::Announcement => "お知らせ",
+            MessageIntent::ChitChat => "雑談",
+            MessageIntent::Feedback => "フィードバック",
+            MessageIntent::Incident => "障害",
+        }
+    }
+}
+
+/// 意図の分類結果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct IntentClassification {
+    #[schemars(description = "The single intent that best describes the message.")]
+    pub intent: MessageIntent,
+    #[schemars(description = "How confident you are in the intent, from 0.0 to 1.0.")]
+    pub confidence: f64,
+}
+
+/// 意図ごとの対応
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentAction {
+    /// 過去のやり取りを検索して回答する
+    Answer,
+    /// チケット用のチャンネルに転記する
+    CreateTicket,
+    /// スレッドで当番をメンションする
+    NotifyOnCall,
+    /// 何もしない
+    Ignore,
+}
+
+/// 意図の振り分けの設定
+///
+/// 既定では質問にだけ回答し、それ以外は何もしません（分類を導入する前と同じ動作）。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntentRoutingSettings {
+    /// 意図ごとの対応（指定のない意図は何もしない）
+    pub routes: BTreeMap<MessageIntent, IntentAction>,
+    /// これ未満の確信度の分類では何もしない
+    pub min_confidence: f64,
+    /// チケットを転記するチャンネルのID
+    pub ticket_channel: Option<String>,
+    /// 当番のユーザーID（U...）またはユーザーグループID（S...）
+    pub on_call: Option<String>,
+}
+
+impl Default for IntentRoutingSettings {
+    fn default() -> Self {
+        Self {
+            routes: BTreeMap::from([(MessageIntent::Question, IntentAction::Answer)]),
+            min_confidence: 0.5,
+            ticket_channel: None,
+            on_call: None,
+        }
+    }
+}
+
+impl IntentRoutingSettings {
+    /// 分類結果に対する対応
+    ///
+    /// 確信度が低い場合や、転記先・当番が設定されていない場合は何もしません。
+    pub fn action_for(&self, classification: &IntentClassification) -> IntentAction {
+        if classification.confidence < self.min_confidence {
+            return IntentAction::Ignore;
+        }
+        match self.routes.get(&classification.intent) {
+            Some(IntentAction::CreateTicket) if self.ticket_channel.is_none() => {
+                tracing::warn!(
+                    "ticket_channel is not set, ignoring {}",
+                    classification.intent.as_str()
+                );
+                IntentAction::Ignore
+            }
+            Some(IntentAction::NotifyOnCall) if self.on_call.is_none() => {
+                tracing::warn!(
+                    "on_call is not set, ignoring {}",
+                    classification.intent.as_str()
+                );
+                IntentAction::Ignore
+            }
+            Some(action) => *action,
+            None => IntentAction::Ignore,
+        }
+    }
+
+    /// 当番へのメンション（ユーザーグループは `<!subteam^ID>`）
+    pub fn on_call_mention(&self) -> Option<String> {
+        self.on_call.as_deref().map(|id| {
+            if id.starts_with('S') {
+                format!("<!subteam^{}>", id)
+            } else {
+                format!("<@{}>", id)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_for() {
+        let settings: IntentRoutingSettings = serde_json::from_value(serde_json::json!({
+            "routes": {"question": "answer", "incident": "notify_on_call", "bug_report": "create_ticket"},
+            "on_call": "S0123456789"
+        }))
+        .unwrap();
+        let classify =
+            |intent, confidence| settings.action_for(&IntentClassification { intent, confidence });
+
+        assert_eq!(classify(MessageIntent::Question, 0.9), IntentAction::Answer);
+        assert_eq!(classify(MessageIntent::Question, 0.3), IntentAction::Ignore);
+        assert_eq!(
+            classify(MessageIntent::Incident, 0.8),
+            IntentAction::NotifyOnCall
+        );
+        // 転記先がなければ何もしない
+        assert_eq!(
+            classify(MessageIntent::BugReport, 0.8),
+            IntentAction::Ignore
+        );
+        assert_eq!(classify(MessageIntent::ChitChat, 1.0), IntentAction::Ignore);
+        assert_eq!(
+            settings.on_call_mention().as_deref(),
+            Some("<!subteam^S0123456789>")
+        );
+
+        // 既定では質問にだけ回答する
+        let default = IntentRoutingSettings::default();
+        assert_eq!(
+            default.action_for(&IntentClassification {
+                intent: MessageIntent::Question,
+                confidence: 0.6
+            }),
+            IntentAction::Answer
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use shared_infrastructure::{schema::interaction_logs, DbPool};
+use uuid::Uuid;
+
+/// 質問応答ログ
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = interaction_logs)]
+pub struct InteractionLog {
+    pub id: Uuid,
+    pub space_id: Option<Uuid>,
+    /// 質問の経路（ask / mention / message）
+    pub source: String,
+    pub channel_id: String,
+    pub user_id: String,
+    pub question: String,
+    pub answer: String,
+    /// 回答の扱い（[`AnswerDecision`](crate::AnswerDecision) の文字列表現）
+    pub decision: String,
+    pub confidence: Option<f64>,
+    /// 出典に裏付けられた主張の割合（検証を行った場合のみ）
+    pub faithfulness: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    /// ツール呼び出しの記録（エージェントモードのみ、[`ToolCallRecord`](crate::ToolCallRecord) の配列）
+    pub tool_trace: Option<serde_json::Value>,
+    /// 回答に使ったプロンプトのバージョン（[`PromptKind`](crate::PromptKind) → バージョン）
+    pub prompt_versions: Option<serde_json::Value>,
+    /// 回答の出典として示したメッセージ
+    pub sources: Option<serde_json::Value>,
+    /// 回答を生成したモデル
+    pub model: Option<String>,
+    /// 回答を投稿したメッセージの ts（エフェメラルな回答では None）
+    pub message_ts: Option<String>,
+}
+
+/// 質問応答ログの登録内容
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = interaction_logs)]
+pub struct NewInteractionLog {
+    pub space_id: Option<Uuid>,
+    pub source: String,
+    pub channel_id: String,
+    pub user_id: String,
+    pub question: String,
+    pub answer: String,
+    pub decision: String,
+    pub confidence: Option<f64>,
+    pub faithfulness: Option<f64>,
+    pub tool_trace: Option<serde_json::Value>,
+    /// 回答に使ったプロンプトのバージョン（[`PromptKind`](crate::PromptKind) → バージョン）
+    pub prompt_versions: Option<serde_json::Value>,
+    /// 回答の出典として示したメッセージ
+    pub sources: Option<serde_json::Value>,
+    /// 回答を生成したモデル
+    pub model: Option<String>,
+}
+
+/// 質問応答ログのリポジトリ
+pub struct InteractionLogRepository {
+    pool: DbPool,
+}
+
+impl InteractionLogRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// ログを登録
+    pub async fn create(&self, log: &NewInteractionLog) -> Result<InteractionLog> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let log = diesel::insert_into(interaction_logs::table)
+            .values(log)
+            .returning(InteractionLog::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(log)
+    }
+
+    /// 回答を投稿したメッセージの ts を記録する
+    pub async fn attach_message(&self, id: Uuid, message_ts: &str) -> Result<()> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        diesel::update(interaction_logs::table.find(id))
+            .set(interaction_logs::message_ts.eq(message_ts))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// 投稿したメッセージからログを探す
+    pub async fn find_by_message(
+        &self,
+        space_id: Option<Uuid>,
+        channel_id: &str,
+        message_ts: &str,
+    ) -> Result<Option<InteractionLog>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let log = interaction_logs::table
+            .filter(interaction_logs::space_id.is_not_distinct_from(space_id))
+            .filter(interaction_logs::channel_id.eq(channel_id))
+            .filter(interaction_logs::message_ts.eq(message_ts))
+            .select(InteractionLog::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(log)
+    }
+}
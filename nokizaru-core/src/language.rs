@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// 回答に使う言語
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "ja")]
+    Japanese,
+    #[serde(rename = "en")]
+    English,
+}
+
+impl Language {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Japanese => "ja",
+            Language::English => "en",
+        }
+    }
+
+    /// プロンプトに埋め込む言語名
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Japanese => "Japanese",
+            Language::English => "English",
+        }
+    }
+
+    /// メッセージの言語を推定する（判定できない場合は None）
+    ///
+    /// かな・漢字を含めば日本語、英字のみであれば英語とみなします。
+    /// メンションやURL、絵文字コードは判定から除きます。
+    pub fn detect(text: &str) -> Option<Language> {
+        let mut japanese = 0;
+        let mut latin = 0;
+        for word in text.split_whitespace() {
+            if word.starts_with('<') || word.starts_with(':') || word.contains("://") {
+                continue;
+            }
+            for c in word.chars() {
+                match c {
+                    '\u{3040}'..='\u{30ff}' | '\u{4e00}'..='\u{9fff}' | '\u{ff66}'..='\u{ff9f}' => {
+                        japanese += 1
+                    }
+                    c if c.is_ascii_alphabetic() => latin += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        // 日本語の文にも英単語は混ざるため、かな・漢字が少しでもあれば日本語とする
+        if japanese > 0 {
+            Some(Language::Japanese)
+        } else if latin > 0 {
+            Some(Language::English)
+        } else {
+            None
+        }
+    }
+}
+
+/// 回答言語の設定
+///
+/// ユーザー → チャンネルの指定を優先し、どちらもなければ質問の言語で回答します。
+/// 質問の言語を判定できない場合は `default` を使います。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LanguageSettings {
+    pub default: Language,
+    /// ユーザーIDごとの指定
+    pub users: HashMap<String, Language>,
+    /// チャンネルIDごとの指定
+    pub channels: HashMap<String, Language>,
+}
+
+impl LanguageSettings {
+    /// 回答に使う言語を決める
+    pub fn resolve(&self, user_id: &str, channel_id: &str, text: &str) -> Language {
+        self.users
+            .get(user_id)
+            .or_else(|| self.channels.get(channel_id))
+            .copied()
+            .or_else(|| Language::detect(text))
+            .unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_resolve() {
+        assert_eq!(
+            Language::detect("課長はだれですか？"),
+            Some(Language::Japanese)
+        );
+        assert_eq!(
+            Language::detect("Slack の API token はどこ？"),
+            Some(Language::Japanese)
+        );
+        assert_eq!(
+            Language::detect("<@U123> who owns the deploy pipeline? https://例.jp"),
+            Some(Language::English)
+        );
+        assert_eq!(Language::detect(":+1: 123"), None);
+
+        let settings = LanguageSettings {
+            users: HashMap::from([("U1".to_string(), Language::English)]),
+            ..Default::default()
+        };
+        assert_eq!(settings.resolve("U1", "C1", "課長は？"), Language::English);
+        assert_eq!(
+            settings.resolve("U2", "C1", "Who is the manager?"),
+            Language::English
+        );
+        assert_eq!(settings.resolve("U2", "C1", "?"), Language::Japanese);
+    }
+}
@@ -4,7 +4,39 @@ pub mod shared {
 }
 
 pub mod agent_service;
+pub mod answer_cache;
+pub mod digest;
+pub mod eval;
+pub mod extract;
+pub mod feedback;
+pub mod intent;
+pub mod interaction_log;
+pub mod language;
+pub mod llm;
+pub mod prefilter;
+pub mod prompt;
+pub mod redaction;
+pub mod scheduler;
+pub mod space;
+pub mod summary;
+pub mod tool_agent;
+pub mod usage;
 
 // 外部クレート再エクスポート（テスト・統合用）
-pub use shared_infrastructure::{create_pool, run_migrations};
+pub use shared_infrastructure::{create_pool, run_migrations, DbPool};
 pub use agent_service::*;
+pub use answer_cache::*;
+pub use digest::*;
+pub use extract::{ExtractError, Extractor};
+pub use feedback::*;
+pub use intent::*;
+pub use interaction_log::*;
+pub use language::*;
+pub use prefilter::*;
+pub use prompt::*;
+pub use redaction::*;
+pub use scheduler::*;
+pub use space::*;
+pub use summary::*;
+pub use tool_agent::*;
+pub use usage::*;
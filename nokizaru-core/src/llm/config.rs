@@ -0,0 +1,209 @@
+use std::{env, sync::Arc};
+
+use rig::{
+    client::CompletionClient,
+    providers::{anthropic, azure, openai},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{LlmError, LlmProvider, LlmStage, RigProvider};
+
+/// Anthropic は max_tokens が必須のため、未指定時はこの値を使う
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u64 = 4096;
+const AZURE_DEFAULT_API_VERSION: &str = "2024-10-21";
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// LLM プロバイダの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmProviderKind {
+    #[default]
+    OpenAi,
+    Anthropic,
+    AzureOpenAi,
+    /// Ollama / llama.cpp など OpenAI 互換の Chat Completions API を持つサーバー
+    OpenAiCompatible,
+}
+
+/// 使用するモデルとその接続先
+///
+/// APIキーそのものは保存せず、読み込む環境変数名だけを持ちます。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelTarget {
+    pub provider: LlmProviderKind,
+    /// モデル名（Azure の場合はデプロイメント名）
+    pub model: String,
+    /// APIキーを読み込む環境変数名（未指定時はプロバイダごとの既定値）
+    pub api_key_env: Option<String>,
+    /// エンドポイント（Azure / OpenAI互換サーバーでは必須）
+    pub base_url: Option<String>,
+    /// Azure OpenAI の API バージョン
+    pub api_version: Option<String>,
+}
+
+impl Default for ModelTarget {
+    fn default() -> Self {
+        Self {
+            provider: LlmProviderKind::OpenAi,
+            model: openai::GPT_4_1_MINI.to_string(),
+            api_key_env: None,
+            base_url: None,
+            api_version: None,
+        }
+    }
+}
+
+impl ModelTarget {
+    /// 同じクライアントを共有できるかの判定に使うキー（モデル名は含まない）
+    pub(crate) fn connection_key(&self) -> String {
+        format!(
+            "{:?}|{}|{}|{}",
+            self.provider,
+            self.api_key_env.as_deref().unwrap_or_default(),
+            self.base_url.as_deref().unwrap_or_default(),
+            self.api_version.as_deref().unwrap_or_default()
+        )
+    }
+
+    fn api_key(&self) -> Result<String, LlmError> {
+        let var = match (&self.api_key_env, self.provider) {
+            (Some(var), _) => var.as_str(),
+            (None, LlmProviderKind::OpenAi) => "OPENAI_API_KEY",
+            (None, LlmProviderKind::Anthropic) => "ANTHROPIC_API_KEY",
+            (None, LlmProviderKind::AzureOpenAi) => "AZURE_API_KEY",
+            // ローカルサーバーはキー不要なことが多い
+            (None, LlmProviderKind::OpenAiCompatible) => return Ok("local".to_string()),
+        };
+
+        env::var(var).map_err(|_| LlmError::Configuration(format!("{} must be set", var)))
+    }
+
+    fn base_url(&self) -> Result<&str, LlmError> {
+        self.base_url.as_deref().ok_or_else(|| {
+            LlmError::Configuration(format!("base_url is required for {:?}", self.provider))
+        })
+    }
+
+    /// 設定からプロバイダ（クライアント）を生成する
+    pub fn build_provider(&self) -> Result<Arc<dyn LlmProvider>, LlmError> {
+        let api_key = self.api_key()?;
+
+        let provider: Arc<dyn LlmProvider> = match self.provider {
+            LlmProviderKind::OpenAi => {
+                let client = match self.base_url.as_deref() {
+                    Some(url) => openai::Client::builder(&api_key).base_url(url).build(),
+                    None => openai::Client::new(&api_key),
+                };
+                Arc::new(RigProvider::new("openai", move |model| {
+                    client.completion_model(model)
+                }))
+            }
+            LlmProviderKind::Anthropic => {
+                let client = match self.base_url.as_deref() {
+                    Some(url) => anthropic::Client::builder(&api_key)
+                        .base_url(url)
+                        .build()
+                        .map_err(|e| LlmError::Configuration(e.to_string()))?,
+                    None => anthropic::Client::new(&api_key),
+                };
+                Arc::new(
+                    RigProvider::new("anthropic", move |model| client.completion_model(model))
+                        .with_default_max_tokens(ANTHROPIC_DEFAULT_MAX_TOKENS),
+                )
+            }
+            LlmProviderKind::AzureOpenAi => {
+                let client = azure::Client::builder(api_key, self.base_url()?)
+                    .api_version(
+                        self.api_version
+                            .as_deref()
+                            .unwrap_or(AZURE_DEFAULT_API_VERSION),
+                    )
+                    .build();
+                Arc::new(RigProvider::new("azure_openai", move |model| {
+                    client.completion_model(model)
+                }))
+            }
+            LlmProviderKind::OpenAiCompatible => {
+                let client = openai::Client::builder(&api_key)
+                    .base_url(self.base_url()?)
+                    .build();
+                // ローカルサーバーは Responses API 非対応のため Chat Completions API を使う
+                Arc::new(RigProvider::new("openai_compatible", move |model| {
+                    client.completion_model(model).completions_api()
+                }))
+            }
+        };
+
+        Ok(provider)
+    }
+}
+
+/// スペースごとの LLM 設定
+///
+/// 既定のモデル（`provider` / `model` など）はトップレベルに記述し、
+/// ステージごとにモデル・生成パラメータ・フォールバックを上書きできます。
+///
+/// ```json
+/// {
+///   "provider": "open_ai", "model": "gpt-4.1",
+///   "fallback": {"provider": "anthropic", "model": "claude-3-5-haiku-latest"},
+///   "reflection": {"primary": {"model": "gpt-4.1-nano"}, "temperature": 0.0, "max_tokens": 200},
+///   "answer": {"temperature": 0.3, "timeout_secs": 90}
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmSettings {
+    /// 既定のモデル
+    #[serde(flatten)]
+    pub default: ModelTarget,
+    /// 既定のフォールバック先（エラー・タイムアウト時）
+    pub fallback: Option<ModelTarget>,
+    /// 1回の呼び出しのタイムアウト（秒）
+    pub timeout_secs: u64,
+    pub reflection: StageSettings,
+    pub query_rewriting: StageSettings,
+    pub answer: StageSettings,
+    pub verification: StageSettings,
+    pub summarization: StageSettings,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            default: ModelTarget::default(),
+            fallback: None,
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            reflection: StageSettings::default(),
+            query_rewriting: StageSettings::default(),
+            answer: StageSettings::default(),
+            verification: StageSettings::default(),
+            summarization: StageSettings::default(),
+        }
+    }
+}
+
+impl LlmSettings {
+    /// ステージの設定
+    pub fn stage(&self, stage: LlmStage) -> &StageSettings {
+        match stage {
+            LlmStage::Reflection => &self.reflection,
+            LlmStage::QueryRewriting => &self.query_rewriting,
+            LlmStage::Answer => &self.answer,
+            LlmStage::Verification => &self.verification,
+            LlmStage::Summarization => &self.summarization,
+        }
+    }
+}
+
+/// ステージごとの上書き設定（未指定の項目はスペースの既定値を使う）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StageSettings {
+    pub primary: Option<ModelTarget>,
+    pub fallback: Option<ModelTarget>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u64>,
+    pub timeout_secs: Option<u64>,
+}
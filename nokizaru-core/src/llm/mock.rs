@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use super::{LlmError, LlmProvider, LlmRequest, LlmResponse, LlmToolCall, LlmUsage};
+
+/// 登録した応答（テキストとツール呼び出し、またはエラーメッセージ）
+type ScriptedResponse = Result<(String, Vec<LlmToolCall>), String>;
+
+/// 台本どおりに応答するモックプロバイダ
+///
+/// 登録した応答を先頭から順に返し、受け取ったリクエストを記録します。
+/// LLM を呼ばずにパイプライン全体をテストするために使います。
+///
+/// ```rust,ignore
+/// let provider = MockProvider::new([
+///     r#"{"category":"Question","is_question":true}"#, // reflection
+///     r#"{"queries":["課長"]}"#,                          // query rewriting
+///     "課長は山田さんです。",                             // answer
+/// ]);
+/// ```
+#[derive(Default)]
+pub struct MockProvider {
+    responses: Mutex<VecDeque<ScriptedResponse>>,
+    requests: Mutex<Vec<LlmRequest>>,
+}
+
+impl MockProvider {
+    pub fn new<I, S>(responses: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            responses: Mutex::new(
+                responses
+                    .into_iter()
+                    .map(|r| Ok((r.into(), Vec::new())))
+                    .collect(),
+            ),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 応答を追加する
+    pub fn push_response(&self, response: impl Into<String>) {
+        self.responses
+            .lock()
+            .unwrap()
+            .push_back(Ok((response.into(), Vec::new())));
+    }
+
+    /// ツール呼び出しの応答を追加する
+    pub fn push_tool_calls(&self, tool_calls: Vec<LlmToolCall>) {
+        self.responses
+            .lock()
+            .unwrap()
+            .push_back(Ok((String::new(), tool_calls)));
+    }
+
+    /// エラー応答を追加する
+    pub fn push_error(&self, message: impl Into<String>) {
+        self.responses
+            .lock()
+            .unwrap()
+            .push_back(Err(message.into()));
+    }
+
+    /// これまでに受け取ったリクエスト
+    pub fn requests(&self) -> Vec<LlmRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        // トークン数は文字数で近似する
+        let input_tokens = (request.preamble.chars().count() + request.prompt.chars().count()) as u64;
+        self.requests.lock().unwrap().push(request);
+
+        let (text, tool_calls) = self
+            .responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or(LlmError::MockExhausted)?
+            .map_err(LlmError::Request)?;
+
+        Ok(LlmResponse {
+            usage: LlmUsage {
+                input_tokens,
+                output_tokens: text.chars().count() as u64,
+            },
+            text,
+            tool_calls,
+        })
+    }
+}
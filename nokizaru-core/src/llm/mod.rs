@@ -0,0 +1,113 @@
+//! LLM プロバイダ抽象化
+//!
+//! パイプライン（reflection → query rewriting → answer）は [`LlmProvider`] だけに依存し、
+//! OpenAI / Anthropic / Azure OpenAI / OpenAI互換のローカルサーバー（Ollama, llama.cpp）を
+//! スペースごとの設定で切り替えられるようにします。
+//! ステージごとのモデル選択とフォールバックは [`ModelRouter`] が担います。
+//! テストでは [`MockProvider`] でLLMを呼ばずにパイプラインを実行できます。
+
+pub mod config;
+pub mod mock;
+pub mod rig_provider;
+pub mod router;
+pub mod tokenizer;
+pub mod usage;
+
+pub use config::*;
+pub use mock::*;
+pub use rig_provider::*;
+pub use router::*;
+pub use tokenizer::*;
+pub use usage::*;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rig::completion::{message::ToolChoice, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ChatTurn;
+
+/// LLM 呼び出しのエラー
+#[derive(Error, Debug)]
+pub enum LlmError {
+    #[error("Provider configuration error: {0}")]
+    Configuration(String),
+
+    #[error("Completion request failed: {0}")]
+    Request(String),
+
+    #[error("Completion request timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("Request has no input message")]
+    EmptyRequest,
+
+    #[error("Empty response from model")]
+    EmptyResponse,
+
+    #[error("Mock provider has no scripted response left")]
+    MockExhausted,
+}
+
+/// LLM への1回の呼び出し
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmRequest {
+    /// モデル名（Azure の場合はデプロイメント名）
+    pub model: String,
+    /// システムプロンプト
+    pub preamble: String,
+    /// ユーザー入力（空の場合は `history` の最後のターンが入力になる）
+    pub prompt: String,
+    /// これまでの会話履歴
+    pub history: Vec<ChatTurn>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u64>,
+    /// 構造化出力のJSONスキーマ
+    ///
+    /// 指定した場合、レスポンスの `text` はスキーマに沿ったJSON文字列になります。
+    pub output_schema: Option<serde_json::Value>,
+    /// モデルが呼び出せるツール
+    pub tools: Vec<ToolDefinition>,
+    /// ツールを呼び出すかどうかの指定（未指定時はモデルに任せる）
+    pub tool_choice: Option<ToolChoice>,
+}
+
+/// モデルが要求したツール呼び出し
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmToolCall {
+    pub id: String,
+    /// OpenAI Responses API の call_id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// トークン使用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LlmUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// LLM のレスポンス
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmResponse {
+    pub text: String,
+    /// ツール呼び出し（ツールを渡した場合のみ）
+    pub tool_calls: Vec<LlmToolCall>,
+    pub usage: LlmUsage,
+}
+
+/// LLM プロバイダ
+///
+/// クライアントは起動時に一度だけ生成し、`Arc<dyn LlmProvider>` として共有します。
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// ログ・計測用のプロバイダ名
+    fn name(&self) -> &str;
+
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse, LlmError>;
+}
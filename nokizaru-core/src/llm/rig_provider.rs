@@ -0,0 +1,180 @@
+use async_trait::async_trait;
+use rig::{
+    completion::{
+        message::{AssistantContent, ToolCall, ToolChoice, ToolFunction},
+        CompletionModel, Message, ToolDefinition,
+    },
+    OneOrMany,
+};
+
+use super::{LlmError, LlmProvider, LlmRequest, LlmResponse, LlmToolCall, LlmUsage};
+use crate::{ChatRole, ChatTurn};
+
+/// 構造化出力に使うツール名
+const SUBMIT_TOOL_NAME: &str = "submit";
+
+type ModelFactory<M> = Box<dyn Fn(&str) -> M + Send + Sync>;
+
+/// rig のクライアントを使う [`LlmProvider`] 実装
+///
+/// クライアントは生成時に一度だけ作り、呼び出しごとにモデル名からモデルを取り出します。
+pub struct RigProvider<M> {
+    name: String,
+    model_factory: ModelFactory<M>,
+    /// max_tokens が必須のプロバイダ（Anthropic）向けの既定値
+    default_max_tokens: Option<u64>,
+}
+
+impl<M> RigProvider<M>
+where
+    M: CompletionModel + 'static,
+{
+    pub fn new(
+        name: impl Into<String>,
+        model_factory: impl Fn(&str) -> M + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            model_factory: Box::new(model_factory),
+            default_max_tokens: None,
+        }
+    }
+
+    pub fn with_default_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.default_max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+fn to_message(turn: &ChatTurn) -> Message {
+    match turn.role {
+        ChatRole::User => Message::user(turn.content.clone()),
+        ChatRole::Assistant if turn.tool_calls.is_empty() => {
+            Message::assistant(turn.content.clone())
+        }
+        ChatRole::Assistant => {
+            let text = (!turn.content.is_empty())
+                .then(|| AssistantContent::text(turn.content.clone()));
+            let calls = turn.tool_calls.iter().map(|call| {
+                AssistantContent::ToolCall(ToolCall {
+                    id: call.id.clone(),
+                    call_id: call.call_id.clone(),
+                    function: ToolFunction {
+                        name: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                    },
+                })
+            });
+            let content = OneOrMany::many(text.into_iter().chain(calls))
+                .unwrap_or_else(|_| OneOrMany::one(AssistantContent::text("")));
+            Message::Assistant { id: None, content }
+        }
+        ChatRole::Tool => match turn.tool_calls.first() {
+            Some(call) => Message::tool_result_with_call_id(
+                call.id.clone(),
+                call.call_id.clone(),
+                turn.content.clone(),
+            ),
+            None => Message::user(turn.content.clone()),
+        },
+    }
+}
+
+/// レスポンスからテキスト（構造化出力の場合は submit ツールの引数）とツール呼び出しを取り出す
+fn response_content(
+    choice: OneOrMany<AssistantContent>,
+    structured: bool,
+) -> Result<(String, Vec<LlmToolCall>), LlmError> {
+    if structured {
+        return choice
+            .into_iter()
+            .find_map(|content| match content {
+                AssistantContent::ToolCall(call) if call.function.name == SUBMIT_TOOL_NAME => {
+                    Some((call.function.arguments.to_string(), Vec::new()))
+                }
+                _ => None,
+            })
+            .ok_or(LlmError::EmptyResponse);
+    }
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for content in choice {
+        match content {
+            AssistantContent::Text(t) => text.push_str(&t.text),
+            AssistantContent::ToolCall(call) => tool_calls.push(LlmToolCall {
+                id: call.id,
+                call_id: call.call_id,
+                name: call.function.name,
+                arguments: call.function.arguments,
+            }),
+            _ => {}
+        }
+    }
+
+    if text.is_empty() && tool_calls.is_empty() {
+        Err(LlmError::EmptyResponse)
+    } else {
+        Ok((text, tool_calls))
+    }
+}
+
+#[async_trait]
+impl<M> LlmProvider for RigProvider<M>
+where
+    M: CompletionModel + 'static,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        let model = (self.model_factory)(&request.model);
+        let structured = request.output_schema.is_some();
+
+        // prompt が空の場合は履歴の最後のターン（ツール結果など）を入力にする
+        let mut messages: Vec<Message> = request.history.iter().map(to_message).collect();
+        if !request.prompt.is_empty() {
+            messages.push(Message::user(request.prompt));
+        }
+        let prompt = messages.pop().ok_or(LlmError::EmptyRequest)?;
+
+        let mut builder = model
+            .completion_request(prompt)
+            .preamble(request.preamble)
+            .messages(messages)
+            .tools(request.tools)
+            .temperature_opt(request.temperature)
+            .max_tokens_opt(request.max_tokens.or(self.default_max_tokens));
+        if let Some(tool_choice) = request.tool_choice {
+            builder = builder.tool_choice(tool_choice);
+        }
+
+        // 構造化出力は submit ツールの呼び出しを強制して受け取る
+        if let Some(schema) = request.output_schema {
+            builder = builder
+                .tool(ToolDefinition {
+                    name: SUBMIT_TOOL_NAME.to_string(),
+                    description: "Submit the structured data you extracted from the provided text."
+                        .to_string(),
+                    parameters: schema,
+                })
+                .tool_choice(ToolChoice::Required);
+        }
+
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| LlmError::Request(e.to_string()))?;
+
+        let (text, tool_calls) = response_content(response.choice, structured)?;
+        Ok(LlmResponse {
+            text,
+            tool_calls,
+            usage: LlmUsage {
+                input_tokens: response.usage.input_tokens,
+                output_tokens: response.usage.output_tokens,
+            },
+        })
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    LlmCallRecord, LlmError, LlmProvider, LlmRequest, LlmResponse, LlmSettings, ModelTarget,
+    UsageRecorder, UsageTags,
+};
+use crate::{RedactionMap, Redactor};
+
+/// パイプラインのステージ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LlmStage {
+    Reflection,
+    QueryRewriting,
+    Answer,
+    /// 回答が出典に裏付けられているかの検証
+    Verification,
+    /// 長いスレッドなどの要約
+    Summarization,
+}
+
+impl LlmStage {
+    pub const ALL: [LlmStage; 5] = [
+        LlmStage::Reflection,
+        LlmStage::QueryRewriting,
+        LlmStage::Answer,
+        LlmStage::Verification,
+        LlmStage::Summarization,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LlmStage::Reflection => "reflection",
+            LlmStage::QueryRewriting => "query_rewriting",
+            LlmStage::Answer => "answer",
+            LlmStage::Verification => "verification",
+            LlmStage::Summarization => "summarization",
+        }
+    }
+}
+
+impl fmt::Display for LlmStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 呼び出し先（プロバイダ + モデル名）
+#[derive(Clone)]
+pub struct ModelRoute {
+    pub provider: Arc<dyn LlmProvider>,
+    pub model: String,
+}
+
+impl ModelRoute {
+    pub fn new(provider: Arc<dyn LlmProvider>, model: impl Into<String>) -> Self {
+        Self {
+            provider,
+            model: model.into(),
+        }
+    }
+}
+
+/// ステージの呼び出し設定
+#[derive(Clone)]
+pub struct StageRoute {
+    pub primary: ModelRoute,
+    pub fallback: Option<ModelRoute>,
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u64>,
+    pub timeout: Duration,
+}
+
+impl StageRoute {
+    pub fn new(primary: ModelRoute) -> Self {
+        Self {
+            primary,
+            fallback: None,
+            temperature: None,
+            max_tokens: None,
+            timeout: Duration::from_secs(LlmSettings::default().timeout_secs),
+        }
+    }
+
+    pub fn with_fallback(mut self, fallback: ModelRoute) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+}
+
+/// ステージごとにモデルを振り分けるルーター
+///
+/// 主モデルがエラーまたはタイムアウトした場合はフォールバック先で再試行します。
+/// 記録先を指定した場合、呼び出しごとのトークン数・所要時間を記録します。
+/// マスキングを指定した場合、送信するテキストの個人情報・秘密情報を置き換え、応答では元に戻します。
+#[derive(Clone)]
+pub struct ModelRouter {
+    routes: HashMap<LlmStage, StageRoute>,
+    recorder: Option<Arc<dyn UsageRecorder>>,
+    redactor: Option<Arc<Redactor>>,
+}
+
+impl ModelRouter {
+    /// すべてのステージで同じモデルを使う
+    pub fn single(provider: Arc<dyn LlmProvider>, model: impl Into<String>) -> Self {
+        let route = StageRoute::new(ModelRoute::new(provider, model));
+        Self {
+            routes: LlmStage::ALL
+                .into_iter()
+                .map(|stage| (stage, route.clone()))
+                .collect(),
+            recorder: None,
+            redactor: None,
+        }
+    }
+
+    /// ステージの設定を上書きする
+    pub fn with_route(mut self, stage: LlmStage, route: StageRoute) -> Self {
+        self.routes.insert(stage, route);
+        self
+    }
+
+    /// 呼び出しの記録先を指定する
+    pub fn with_recorder(mut self, recorder: Arc<dyn UsageRecorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// 送信前のマスキングを指定する
+    pub fn with_redactor(mut self, redactor: Arc<Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// ステージの主モデル名（トークン数の見積もりなどに使う）
+    pub fn model(&self, stage: LlmStage) -> Option<&str> {
+        self.routes.get(&stage).map(|route| route.primary.model.as_str())
+    }
+
+    /// スペースの設定からルーターを構築する
+    ///
+    /// 接続先が同じモデル同士はクライアントを共有します。
+    pub fn from_settings(settings: &LlmSettings) -> Result<Self, LlmError> {
+        let mut providers: HashMap<String, Arc<dyn LlmProvider>> = HashMap::new();
+        let mut resolve = |target: &ModelTarget| -> Result<ModelRoute, LlmError> {
+            let key = target.connection_key();
+            let provider = match providers.get(&key) {
+                Some(provider) => provider.clone(),
+                None => {
+                    let provider = target.build_provider()?;
+                    providers.insert(key, provider.clone());
+                    provider
+                }
+            };
+            Ok(ModelRoute::new(provider, target.model.clone()))
+        };
+
+        let mut routes = HashMap::new();
+        for stage in LlmStage::ALL {
+            let stage_settings = settings.stage(stage);
+            let primary = resolve(stage_settings.primary.as_ref().unwrap_or(&settings.default))?;
+            let fallback = stage_settings
+                .fallback
+                .as_ref()
+                .or(settings.fallback.as_ref())
+                .map(&mut resolve)
+                .transpose()?;
+
+            routes.insert(
+                stage,
+                StageRoute {
+                    primary,
+                    fallback,
+                    temperature: stage_settings.temperature,
+                    max_tokens: stage_settings.max_tokens,
+                    timeout: Duration::from_secs(
+                        stage_settings.timeout_secs.unwrap_or(settings.timeout_secs),
+                    ),
+                },
+            );
+        }
+
+        Ok(Self {
+            routes,
+            recorder: None,
+            redactor: None,
+        })
+    }
+
+    /// ステージの設定でLLMを呼び出す
+    ///
+    /// リクエストに指定がない生成パラメータはステージの設定で補います。
+    pub async fn complete(
+        &self,
+        stage: LlmStage,
+        request: LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let route = self
+            .routes
+            .get(&stage)
+            .ok_or_else(|| LlmError::Configuration(format!("No route for stage {}", stage)))?;
+
+        let request = LlmRequest {
+            temperature: request.temperature.or(route.temperature),
+            max_tokens: request.max_tokens.or(route.max_tokens),
+            ..request
+        };
+
+        let Some(redactor) = &self.redactor else {
+            return self.complete_with_fallback(stage, route, request).await;
+        };
+        let mut redaction = RedactionMap::default();
+        let request = redact_request(redactor, request, &mut redaction);
+        if !redaction.is_empty() {
+            tracing::info!("[{}] Redacted before sending: {:?}", stage, redaction.counts());
+        }
+        let structured = request.output_schema.is_some();
+
+        let mut response = self.complete_with_fallback(stage, route, request).await?;
+        if !redaction.is_empty() {
+            response.text = match serde_json::from_str::<serde_json::Value>(&response.text) {
+                // JSON の文字列として埋め込んでもエスケープが崩れないよう、値ごとに戻す
+                Ok(mut value) if structured => {
+                    redaction.restore_json(&mut value);
+                    value.to_string()
+                }
+                _ => redaction.restore(&response.text),
+            };
+            for call in &mut response.tool_calls {
+                redaction.restore_json(&mut call.arguments);
+            }
+        }
+        Ok(response)
+    }
+
+    async fn complete_with_fallback(
+        &self,
+        stage: LlmStage,
+        route: &StageRoute,
+        request: LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let primary_result = self
+            .call(stage, &route.primary, route.timeout, request.clone())
+            .await;
+        match (primary_result, &route.fallback) {
+            (Ok(response), _) => Ok(response),
+            (Err(e), Some(fallback)) => {
+                tracing::warn!(
+                    "[{}] {}/{} failed, falling back to {}/{}: {}",
+                    stage,
+                    route.primary.provider.name(),
+                    route.primary.model,
+                    fallback.provider.name(),
+                    fallback.model,
+                    e
+                );
+                self.call(stage, fallback, route.timeout, request).await
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    async fn call(
+        &self,
+        stage: LlmStage,
+        route: &ModelRoute,
+        timeout: Duration,
+        request: LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let request = LlmRequest {
+            model: route.model.clone(),
+            ..request
+        };
+
+        let started = Instant::now();
+        let result = tokio::time::timeout(timeout, route.provider.complete(request))
+            .await
+            .map_err(|_| LlmError::Timeout(timeout))
+            .and_then(|result| result);
+
+        if let Some(recorder) = &self.recorder {
+            recorder.record(LlmCallRecord {
+                stage,
+                provider: route.provider.name().to_string(),
+                model: route.model.clone(),
+                usage: result
+                    .as_ref()
+                    .map(|response| response.usage)
+                    .unwrap_or_default(),
+                latency: started.elapsed(),
+                succeeded: result.is_ok(),
+                tags: UsageTags::current(),
+            });
+        }
+
+        result
+    }
+}
+
+/// 送信するテキスト（入力・会話履歴・ツール呼び出しの引数）をマスキングする
+///
+/// システムプロンプトは設定から組み立てるため対象外です。
+fn redact_request(redactor: &Redactor, request: LlmRequest, map: &mut RedactionMap) -> LlmRequest {
+    let prompt = redactor.redact(&request.prompt, map);
+    let history = request
+        .history
+        .into_iter()
+        .map(|mut turn| {
+            turn.content = redactor.redact(&turn.content, map);
+            for call in &mut turn.tool_calls {
+                redactor.redact_json(&mut call.arguments, map);
+            }
+            turn
+        })
+        .collect();
+
+    LlmRequest {
+        prompt,
+        history,
+        ..request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+
+    #[tokio::test]
+    async fn test_falls_back_on_error() {
+        let primary = Arc::new(MockProvider::default());
+        primary.push_error("rate limited");
+        let fallback = Arc::new(MockProvider::new(["fallback answer"]));
+
+        let router = ModelRouter::single(primary.clone(), "cheap").with_route(
+            LlmStage::Answer,
+            StageRoute::new(ModelRoute::new(primary.clone(), "strong"))
+                .with_fallback(ModelRoute::new(fallback.clone(), "backup")),
+        );
+
+        let response = router
+            .complete(LlmStage::Answer, LlmRequest::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.text, "fallback answer");
+        assert_eq!(primary.requests()[0].model, "strong");
+        assert_eq!(fallback.requests()[0].model, "backup");
+    }
+
+    #[tokio::test]
+    async fn test_redacts_request_and_restores_response() {
+        let provider = Arc::new(MockProvider::new([
+            r#"{"answer":"[EMAIL_1] に連絡してください"}"#,
+        ]));
+        let redactor = Redactor::from_settings(&Default::default()).unwrap();
+        let router =
+            ModelRouter::single(provider.clone(), "mock").with_redactor(Arc::new(redactor));
+
+        let response = router
+            .complete(
+                LlmStage::Answer,
+                LlmRequest {
+                    prompt: "担当は yamada@example.com です".to_string(),
+                    output_schema: Some(serde_json::json!({"type": "object"})),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(provider.requests()[0].prompt, "担当は [EMAIL_1] です");
+        assert_eq!(
+            response.text,
+            r#"{"answer":"yamada@example.com に連絡してください"}"#
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! トークン数の数え方
+//!
+//! コンテキストをモデルのコンテキスト長・コストの予算内に収めるために使います。
+//! OpenAI の系統（`o200k_base`・`cl100k_base`）は [`TiktokenCounter`] で正確に数え、
+//! 語彙が公開されていないモデル（Claude など）は [`EstimatedTokenCounter`] で近似します。
+//! モデル名から選ぶには [`token_counter_for_model`] を使います。
+
+use std::sync::Arc;
+
+use tiktoken_rs::CoreBPE;
+
+/// トークン数を数える
+pub trait TokenCounter: Send + Sync {
+    fn count(&self, text: &str) -> usize;
+
+    /// `max_tokens` 以内に収まるよう末尾を切り詰める（切り詰めた場合は「…」を付ける）
+    fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        if self.count(text) <= max_tokens {
+            return text.to_string();
+        }
+
+        // 収まる最長の先頭部分を二分探索する
+        let chars: Vec<char> = text.chars().collect();
+        let (mut low, mut high) = (0, chars.len());
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            let prefix: String = chars[..mid].iter().collect::<String>() + "…";
+            if self.count(&prefix) <= max_tokens {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        if low == 0 {
+            String::new()
+        } else {
+            chars[..low].iter().collect::<String>() + "…"
+        }
+    }
+}
+
+/// モデルのトークナイザで数える [`TokenCounter`]（語彙がわからないモデルは見積もり）
+pub fn token_counter_for_model(model: &str) -> Arc<dyn TokenCounter> {
+    match TiktokenCounter::for_model(model) {
+        Some(counter) => Arc::new(counter),
+        None => Arc::new(EstimatedTokenCounter::for_model(model)),
+    }
+}
+
+/// OpenAI の BPE の語彙で数える [`TokenCounter`]
+#[derive(Clone, Copy)]
+pub struct TiktokenCounter {
+    bpe: &'static CoreBPE,
+}
+
+impl TiktokenCounter {
+    /// モデル名から語彙を選ぶ（OpenAI の系統でなければ None）
+    pub fn for_model(model: &str) -> Option<Self> {
+        let model = model.to_ascii_lowercase();
+        let bpe = if ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix))
+        {
+            tiktoken_rs::o200k_base_singleton()
+        } else if model.starts_with("gpt-4") || model.starts_with("gpt-3.5") {
+            tiktoken_rs::cl100k_base_singleton()
+        } else {
+            return None;
+        };
+        Some(Self { bpe })
+    }
+}
+
+impl TokenCounter for TiktokenCounter {
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+}
+
+/// 文字種ごとの比率でトークン数を見積もる [`TokenCounter`]
+///
+/// 語彙が公開されていないモデル向けの代替です。OpenAI の系統の比率も残していますが、
+/// 通常は [`TiktokenCounter`] で数えます。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimatedTokenCounter {
+    /// 英字・記号など（CJK 以外）の何文字で1トークンになるか
+    pub latin_chars_per_token: f64,
+    /// かな・漢字・ハングル1文字あたりのトークン数
+    pub cjk_tokens_per_char: f64,
+}
+
+impl Default for EstimatedTokenCounter {
+    /// 系統がわからないモデル向けの、多めに見積もる設定
+    fn default() -> Self {
+        Self {
+            latin_chars_per_token: 3.5,
+            cjk_tokens_per_char: 1.3,
+        }
+    }
+}
+
+impl EstimatedTokenCounter {
+    /// モデル名から系統を推定して比率を決める
+    pub fn for_model(model: &str) -> Self {
+        let model = model.to_ascii_lowercase();
+        if ["gpt-4o", "gpt-4.1", "gpt-5", "o1", "o3", "o4"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix))
+        {
+            // o200k_base
+            Self {
+                latin_chars_per_token: 4.2,
+                cjk_tokens_per_char: 0.8,
+            }
+        } else if model.starts_with("gpt-4") || model.starts_with("gpt-3.5") {
+            // cl100k_base
+            Self {
+                latin_chars_per_token: 4.0,
+                cjk_tokens_per_char: 1.1,
+            }
+        } else if model.starts_with("claude") {
+            Self {
+                latin_chars_per_token: 3.5,
+                cjk_tokens_per_char: 1.2,
+            }
+        } else {
+            Self::default()
+        }
+    }
+}
+
+impl TokenCounter for EstimatedTokenCounter {
+    fn count(&self, text: &str) -> usize {
+        let (mut cjk, mut other) = (0usize, 0usize);
+        for c in text.chars() {
+            match c {
+                '\u{3040}'..='\u{30ff}'
+                | '\u{3400}'..='\u{4dbf}'
+                | '\u{4e00}'..='\u{9fff}'
+                | '\u{ac00}'..='\u{d7af}'
+                | '\u{ff00}'..='\u{ffef}' => cjk += 1,
+                _ => other += 1,
+            }
+        }
+
+        let tokens =
+            cjk as f64 * self.cjk_tokens_per_char + other as f64 / self.latin_chars_per_token;
+        tokens.ceil() as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_and_truncate() {
+        let counter = EstimatedTokenCounter::for_model("gpt-4.1-mini");
+        assert_eq!(counter.count(""), 0);
+        assert_eq!(counter.count("hello world!"), 3);
+        assert_eq!(counter.count("課長は山田さん"), 6);
+
+        let text = "課長は山田さんです。".repeat(20);
+        let truncated = counter.truncate(&text, 50);
+        assert!(counter.count(&truncated) <= 50);
+        assert!(truncated.ends_with('…'));
+        assert_eq!(counter.truncate("short", 50), "short");
+    }
+
+    #[test]
+    fn test_tiktoken_counter() {
+        let counter = TiktokenCounter::for_model("gpt-4.1-mini").unwrap();
+        assert_eq!(counter.count(""), 0);
+        assert_eq!(counter.count("hello world!"), 3);
+
+        let text = "課長は山田さんです。".repeat(20);
+        let truncated = counter.truncate(&text, 50);
+        assert!(counter.count(&truncated) <= 50);
+        assert!(truncated.ends_with('…'));
+
+        // 語彙がわからないモデルは見積もりで数える
+        assert!(TiktokenCounter::for_model("claude-sonnet-4").is_none());
+        assert_eq!(
+            token_counter_for_model("claude-sonnet-4").count("hello world!"),
+            4
+        );
+    }
+}
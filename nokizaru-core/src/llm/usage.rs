@@ -0,0 +1,59 @@
+//! LLM 呼び出しの計測
+//!
+//! [`ModelRouter`](super::ModelRouter) は呼び出しごとにモデル・トークン数・所要時間を
+//! [`UsageRecorder`] に渡します。どのチャンネル・ユーザーの質問に対する呼び出しかは、
+//! 呼び出し元が [`UsageTags::scope`] で設定したタグから取得します。
+
+use std::{future::Future, time::Duration};
+
+use super::{LlmStage, LlmUsage};
+
+tokio::task_local! {
+    static USAGE_TAGS: UsageTags;
+}
+
+/// 呼び出し元のタグ
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageTags {
+    pub channel_id: Option<String>,
+    pub user_id: Option<String>,
+}
+
+impl UsageTags {
+    pub fn new(channel_id: impl Into<String>, user_id: Option<String>) -> Self {
+        Self {
+            channel_id: Some(channel_id.into()),
+            user_id,
+        }
+    }
+
+    /// `future` の中で行われた LLM 呼び出しにタグを付ける
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        USAGE_TAGS.scope(self, future).await
+    }
+
+    /// 現在のタグ（[`Self::scope`] の外では空）
+    pub fn current() -> Self {
+        USAGE_TAGS.try_with(Clone::clone).unwrap_or_default()
+    }
+}
+
+/// 1回の LLM 呼び出しの記録
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmCallRecord {
+    pub stage: LlmStage,
+    pub provider: String,
+    pub model: String,
+    pub usage: LlmUsage,
+    pub latency: Duration,
+    /// エラー・タイムアウトの場合は false（フォールバック前の失敗も記録する）
+    pub succeeded: bool,
+    pub tags: UsageTags,
+}
+
+/// LLM 呼び出しの記録先
+///
+/// 呼び出しの応答を遅らせないよう、保存に時間がかかる実装はバックグラウンドで行ってください。
+pub trait UsageRecorder: Send + Sync {
+    fn record(&self, call: LlmCallRecord);
+}
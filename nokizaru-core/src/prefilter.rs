@@ -0,0 +1,305 @@
+//! LLM で分類する前のルールによる絞り込み
+//!
+//! チャンネルの全メッセージを LLM で分類すると、「ok」や絵文字だけの返信、長いお知らせにも
+//! 呼び出しが発生します。長さ・質問の目印・絵文字やリンクだけの投稿・メッセージの subtype・
+//! 対象チャンネルから、分類せずに無視するか、分類するか、分類せずに回答するかを決めます。
+//! 判定の件数は [`Prefilter::stats`] で確認できます。
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 絞り込みの設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrefilterSettings {
+    pub enabled: bool,
+    /// これより短いメッセージは無視する（文字数、前後の空白を除く）
+    pub min_chars: usize,
+    /// これより長いメッセージは無視する（お知らせなど）
+    pub max_chars: usize,
+    /// 質問の目印（含まれていれば質問らしいとみなす）
+    pub question_markers: Vec<String>,
+    /// 質問の目印があるメッセージは分類せずに回答する
+    pub answer_on_question_marker: bool,
+    /// 質問の目印がないメッセージは分類しない
+    pub require_question_marker: bool,
+    /// 絵文字だけのメッセージを無視する
+    pub skip_emoji_only: bool,
+    /// リンク・メンションだけのメッセージを無視する
+    pub skip_link_only: bool,
+    /// 無視するメッセージの subtype（`channel_join` など）
+    pub skip_subtypes: Vec<String>,
+    /// 対象のチャンネルのID（空なら全てのチャンネル）
+    pub channels: Vec<String>,
+}
+
+impl Default for PrefilterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_chars: 3,
+            max_chars: 1000,
+            question_markers: [
+                "?",
+                "？",
+                "か。",
+                "教えて",
+                "ですか",
+                "ますか",
+                "でしょうか",
+                "知りたい",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            answer_on_question_marker: false,
+            require_question_marker: false,
+            skip_emoji_only: true,
+            skip_link_only: true,
+            skip_subtypes: [
+                "bot_message",
+                "channel_join",
+                "channel_leave",
+                "channel_topic",
+                "channel_purpose",
+                "channel_name",
+                "channel_archive",
+                "channel_unarchive",
+                "pinned_item",
+                "unpinned_item",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            channels: Vec::new(),
+        }
+    }
+}
+
+/// 無視する理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkipReason {
+    Subtype,
+    Channel,
+    TooShort,
+    TooLong,
+    EmojiOnly,
+    LinkOnly,
+    NoQuestionMarker,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::Subtype => "subtype",
+            SkipReason::Channel => "channel",
+            SkipReason::TooShort => "too_short",
+            SkipReason::TooLong => "too_long",
+            SkipReason::EmojiOnly => "emoji_only",
+            SkipReason::LinkOnly => "link_only",
+            SkipReason::NoQuestionMarker => "no_question_marker",
+        }
+    }
+}
+
+/// 絞り込みの判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefilterDecision {
+    /// LLM を呼ばずに無視する
+    Skip(SkipReason),
+    /// LLM で意図を分類する
+    Classify,
+    /// 分類せずに回答する
+    Answer,
+}
+
+/// 判定の件数（起動してからの累計）
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PrefilterStats {
+    /// 理由ごとの無視した件数
+    pub skipped: BTreeMap<String, u64>,
+    pub classified: u64,
+    pub answered: u64,
+}
+
+impl PrefilterStats {
+    pub fn total(&self) -> u64 {
+        self.skipped.values().sum::<u64>() + self.classified + self.answered
+    }
+
+    /// 省けた分類の呼び出し回数
+    pub fn saved_calls(&self) -> u64 {
+        self.skipped.values().sum::<u64>() + self.answered
+    }
+}
+
+/// ルールによる絞り込み
+pub struct Prefilter {
+    settings: PrefilterSettings,
+    /// Slack の絵文字コード（`:thumbsup:` など）
+    emoji_code: Regex,
+    /// Slack のリンク・メンション（`<https://...>` / `<@U...>` / `<#C...>`）と URL
+    link: Regex,
+    stats: Mutex<PrefilterStats>,
+}
+
+impl Prefilter {
+    pub fn new(settings: PrefilterSettings) -> Self {
+        Self {
+            settings,
+            emoji_code: Regex::new(r":[a-z0-9_+'-]+:").unwrap(),
+            link: Regex::new(r"<[^>]+>|https?://\S+").unwrap(),
+            stats: Mutex::new(PrefilterStats::default()),
+        }
+    }
+
+    /// メッセージへの対応を決め、件数を記録する
+    pub fn decide(&self, channel: &str, subtype: Option<&str>, text: &str) -> PrefilterDecision {
+        let decision = self.evaluate(channel, subtype, text);
+
+        let mut stats = self.stats.lock().unwrap();
+        match decision {
+            PrefilterDecision::Skip(reason) => {
+                *stats
+                    .skipped
+                    .entry(reason.as_str().to_string())
+                    .or_default() += 1;
+            }
+            PrefilterDecision::Classify => stats.classified += 1,
+            PrefilterDecision::Answer => stats.answered += 1,
+        }
+        decision
+    }
+
+    fn evaluate(&self, channel: &str, subtype: Option<&str>, text: &str) -> PrefilterDecision {
+        let settings = &self.settings;
+        if !settings.enabled {
+            return PrefilterDecision::Classify;
+        }
+
+        if subtype.is_some_and(|subtype| settings.skip_subtypes.iter().any(|s| s == subtype)) {
+            return PrefilterDecision::Skip(SkipReason::Subtype);
+        }
+        if !settings.channels.is_empty() && !settings.channels.iter().any(|c| c == channel) {
+            return PrefilterDecision::Skip(SkipReason::Channel);
+        }
+
+        let text = text.trim();
+        if settings.skip_link_only && self.is_link_only(text) {
+            return PrefilterDecision::Skip(SkipReason::LinkOnly);
+        }
+        if settings.skip_emoji_only && self.is_emoji_only(text) {
+            return PrefilterDecision::Skip(SkipReason::EmojiOnly);
+        }
+        let chars = text.chars().count();
+        if chars < settings.min_chars {
+            return PrefilterDecision::Skip(SkipReason::TooShort);
+        }
+        if chars > settings.max_chars {
+            return PrefilterDecision::Skip(SkipReason::TooLong);
+        }
+
+        let has_marker = settings
+            .question_markers
+            .iter()
+            .any(|marker| text.contains(marker.as_str()));
+        match has_marker {
+            true if settings.answer_on_question_marker => PrefilterDecision::Answer,
+            false if settings.require_question_marker => {
+                PrefilterDecision::Skip(SkipReason::NoQuestionMarker)
+            }
+            _ => PrefilterDecision::Classify,
+        }
+    }
+
+    /// 絵文字（と空白・記号）だけのメッセージか
+    fn is_emoji_only(&self, text: &str) -> bool {
+        let rest = self.emoji_code.replace_all(text, "");
+        let mut has_emoji = rest.len() < text.len();
+        for c in rest.chars() {
+            if is_emoji(c) {
+                has_emoji = true;
+            } else if !(c.is_whitespace() || c.is_ascii_punctuation()) {
+                return false;
+            }
+        }
+        has_emoji
+    }
+
+    /// リンク・メンション（と空白）だけのメッセージか
+    fn is_link_only(&self, text: &str) -> bool {
+        self.link.is_match(text) && self.link.replace_all(text, "").trim().is_empty()
+    }
+
+    /// 起動してからの判定の件数
+    pub fn stats(&self) -> PrefilterStats {
+        self.stats.lock().unwrap().clone()
+    }
+}
+
+/// 絵文字とその修飾（異体字セレクタ・ZWJ・肌の色）
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x2190..=0x21FF
+            | 0x3030 | 0x303D | 0x3297 | 0x3299 | 0xFE0F | 0x200D | 0x20E3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide() {
+        let prefilter = Prefilter::new(PrefilterSettings {
+            channels: vec!["C1".to_string(), "C2".to_string()],
+            answer_on_question_marker: true,
+            ..Default::default()
+        });
+        let decide = |channel, subtype, text| prefilter.decide(channel, subtype, text);
+
+        assert_eq!(
+            decide("C1", None, "ok"),
+            PrefilterDecision::Skip(SkipReason::TooShort)
+        );
+        assert_eq!(
+            decide("C1", None, "👍🏻 🎉"),
+            PrefilterDecision::Skip(SkipReason::EmojiOnly)
+        );
+        assert_eq!(
+            decide("C1", None, ":pray: :+1:"),
+            PrefilterDecision::Skip(SkipReason::EmojiOnly)
+        );
+        assert_eq!(
+            decide("C1", None, "<https://example.com/doc|資料> <@U123>"),
+            PrefilterDecision::Skip(SkipReason::LinkOnly)
+        );
+        assert_eq!(
+            decide("C1", Some("channel_join"), "<@U123> さんが参加しました"),
+            PrefilterDecision::Skip(SkipReason::Subtype)
+        );
+        assert_eq!(
+            decide("C3", None, "デプロイの担当は誰ですか？"),
+            PrefilterDecision::Skip(SkipReason::Channel)
+        );
+        let announcement = "お知らせです。".repeat(200);
+        assert_eq!(
+            decide("C1", None, &announcement),
+            PrefilterDecision::Skip(SkipReason::TooLong)
+        );
+        assert_eq!(
+            decide("C2", None, "デプロイの担当は誰ですか？"),
+            PrefilterDecision::Answer
+        );
+        assert_eq!(
+            decide("C2", Some("thread_broadcast"), "本番が落ちています :fire:"),
+            PrefilterDecision::Classify
+        );
+
+        let stats = prefilter.stats();
+        assert_eq!(stats.total(), 9);
+        assert_eq!(stats.saved_calls(), 8);
+        assert_eq!(stats.skipped["emoji_only"], 2);
+    }
+}
@@ -0,0 +1,272 @@
+//! プロンプトテンプレート
+//!
+//! 各ステージのシステムプロンプトは `prompts/*.txt` に置いた組み込みテンプレートを既定とし、
+//! スペースごとに `spaces.settings` の `"prompts"` で上書きできます。
+//! テンプレートには `{space_name}` / `{today}` / `{language}` の変数を埋め込めます。
+//! 質問の言語によって例文などを変えたい場合は、言語ごとの `translations` を指定します。
+//!
+//! テンプレートのバージョンは回答ごとに記録されるため、
+//! 内容を変更した場合はバージョンも更新してください。
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Language;
+
+/// プロンプトの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptKind {
+    /// 質問かどうかの判定
+    Reflection,
+    /// 検索クエリの生成
+    QueryRewriting,
+    /// 回答（出典なし）
+    Answer,
+    /// 出典付きの回答
+    CitedAnswer,
+    /// 回答の検証
+    Verification,
+    /// エージェントモード
+    Agent,
+    /// 長いスレッドの要約
+    ThreadSummary,
+    /// メッセージの意図の分類
+    IntentClassification,
+    /// チャンネル・スレッドの要約（決定事項・未解決の質問・対応事項）
+    ConversationSummary,
+}
+
+impl PromptKind {
+    pub const ALL: [PromptKind; 9] = [
+        PromptKind::Reflection,
+        PromptKind::QueryRewriting,
+        PromptKind::Answer,
+        PromptKind::CitedAnswer,
+        PromptKind::Verification,
+        PromptKind::Agent,
+        PromptKind::ThreadSummary,
+        PromptKind::IntentClassification,
+        PromptKind::ConversationSummary,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptKind::Reflection => "reflection",
+            PromptKind::QueryRewriting => "query_rewriting",
+            PromptKind::Answer => "answer",
+            PromptKind::CitedAnswer => "cited_answer",
+            PromptKind::Verification => "verification",
+            PromptKind::Agent => "agent",
+            PromptKind::ThreadSummary => "thread_summary",
+            PromptKind::IntentClassification => "intent_classification",
+            PromptKind::ConversationSummary => "conversation_summary",
+        }
+    }
+}
+
+/// プロンプトテンプレート
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptTemplate {
+    /// テンプレートのバージョン（回答ごとに記録される）
+    #[serde(default = "PromptTemplate::default_version")]
+    pub version: String,
+    pub template: String,
+    /// 言語ごとのテンプレート（ない言語は `template` を使う）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<Language, String>,
+}
+
+impl PromptTemplate {
+    fn default_version() -> String {
+        "custom".to_string()
+    }
+
+    /// 組み込みテンプレート
+    pub fn builtin(kind: PromptKind) -> Self {
+        let (version, template) = match kind {
+            PromptKind::Reflection => ("builtin-1", include_str!("../prompts/reflection.txt")),
+            PromptKind::QueryRewriting => {
+                ("builtin-2", include_str!("../prompts/query_rewriting.txt"))
+            }
+            PromptKind::Answer => ("builtin-2", include_str!("../prompts/answer.txt")),
+            PromptKind::CitedAnswer => ("builtin-2", include_str!("../prompts/cited_answer.txt")),
+            PromptKind::Verification => ("builtin-1", include_str!("../prompts/verification.txt")),
+            PromptKind::Agent => ("builtin-2", include_str!("../prompts/agent.txt")),
+            PromptKind::ThreadSummary => {
+                ("builtin-1", include_str!("../prompts/thread_summary.txt"))
+            }
+            PromptKind::IntentClassification => (
+                "builtin-1",
+                include_str!("../prompts/intent_classification.txt"),
+            ),
+            PromptKind::ConversationSummary => (
+                "builtin-1",
+                include_str!("../prompts/conversation_summary.txt"),
+            ),
+        };
+        let translations = match kind {
+            PromptKind::QueryRewriting => BTreeMap::from([(
+                Language::English,
+                include_str!("../prompts/query_rewriting.en.txt"),
+            )]),
+            _ => BTreeMap::new(),
+        };
+        Self {
+            version: version.to_string(),
+            template: template.trim_end().to_string(),
+            translations: translations
+                .into_iter()
+                .map(|(language, text)| (language, text.trim_end().to_string()))
+                .collect(),
+        }
+    }
+
+    /// 変数を埋め込む（未知の `{...}` はそのまま残す）
+    ///
+    /// 言語が指定されていて、その言語のテンプレートがあればそちらを使います。
+    pub fn render(&self, vars: &PromptVars) -> String {
+        let template = vars
+            .language
+            .and_then(|language| self.translations.get(&language))
+            .unwrap_or(&self.template);
+        let today = vars.today.format("%Y-%m-%d").to_string();
+        let language = vars
+            .language
+            .map(|language| language.name())
+            .unwrap_or("the language of the question");
+        template
+            .replace("{space_name}", &vars.space_name)
+            .replace("{today}", &today)
+            .replace("{language}", language)
+    }
+}
+
+/// テンプレートに埋め込む変数
+#[derive(Debug, Clone)]
+pub struct PromptVars {
+    pub space_name: String,
+    pub today: chrono::NaiveDate,
+    /// 回答に使う言語（None の場合は質問と同じ言語）
+    pub language: Option<Language>,
+}
+
+/// スペースごとのプロンプト上書き（`spaces.settings` の `"prompts"`）
+///
+/// 例: `{"prompts": {"answer": {"version": "2024-06-a", "template": "..."}}}`
+pub type PromptSettings = BTreeMap<PromptKind, PromptTemplate>;
+
+/// スペースで使うプロンプト一式
+#[derive(Debug, Clone)]
+pub struct PromptLibrary {
+    overrides: PromptSettings,
+    space_name: String,
+}
+
+impl Default for PromptLibrary {
+    fn default() -> Self {
+        Self {
+            overrides: PromptSettings::new(),
+            space_name: "Slack".to_string(),
+        }
+    }
+}
+
+impl PromptLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// スペースの上書きを指定する
+    pub fn with_overrides(mut self, overrides: PromptSettings) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// `{space_name}` に埋め込むスペース名を指定する
+    pub fn with_space_name(mut self, space_name: impl Into<String>) -> Self {
+        self.space_name = space_name.into();
+        self
+    }
+
+    /// 使用するテンプレート（上書きがなければ組み込み）
+    pub fn template(&self, kind: PromptKind) -> PromptTemplate {
+        self.overrides
+            .get(&kind)
+            .cloned()
+            .unwrap_or_else(|| PromptTemplate::builtin(kind))
+    }
+
+    /// テンプレートのバージョン
+    pub fn version(&self, kind: PromptKind) -> String {
+        self.template(kind).version
+    }
+
+    /// 指定した種類のバージョン一覧（回答の記録用）
+    pub fn versions(&self, kinds: &[PromptKind]) -> BTreeMap<PromptKind, String> {
+        kinds
+            .iter()
+            .map(|&kind| (kind, self.version(kind)))
+            .collect()
+    }
+
+    /// 今日の日付で変数を埋め込んだプロンプト（言語は質問に合わせる）
+    pub fn render(&self, kind: PromptKind) -> String {
+        self.render_vars(kind, None)
+    }
+
+    /// 回答言語を指定してプロンプトを組み立てる
+    pub fn render_in(&self, kind: PromptKind, language: Language) -> String {
+        self.render_vars(kind, Some(language))
+    }
+
+    fn render_vars(&self, kind: PromptKind, language: Option<Language>) -> String {
+        self.template(kind).render(&PromptVars {
+            space_name: self.space_name.clone(),
+            today: chrono::Local::now().date_naive(),
+            language,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_and_variables() {
+        let overrides: PromptSettings = serde_json::from_value(serde_json::json!({
+            "answer": {"version": "v2", "template": "{space_name} の質問に {language} で答える ({today}) {unknown}"}
+        }))
+        .unwrap();
+        let prompts = PromptLibrary::new()
+            .with_overrides(overrides)
+            .with_space_name("開発部");
+
+        let rendered = prompts.template(PromptKind::Answer).render(&PromptVars {
+            space_name: "開発部".to_string(),
+            today: chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(),
+            language: Some(Language::Japanese),
+        });
+        assert_eq!(
+            rendered,
+            "開発部 の質問に Japanese で答える (2024-06-01) {unknown}"
+        );
+        assert_eq!(prompts.version(PromptKind::Answer), "v2");
+        assert_eq!(prompts.version(PromptKind::Reflection), "builtin-1");
+
+        // 組み込みテンプレートはすべて読み込める
+        for kind in PromptKind::ALL {
+            assert!(!prompts.render(kind).is_empty());
+        }
+        // 言語ごとのテンプレートがあればそちらを使う
+        assert!(prompts
+            .render_in(PromptKind::QueryRewriting, Language::English)
+            .contains("section manager"));
+        assert!(prompts
+            .render_in(PromptKind::QueryRewriting, Language::Japanese)
+            .contains("課長"));
+    }
+}
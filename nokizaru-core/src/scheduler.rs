@@ -0,0 +1,814 @@
+//! cron 式による定期実行
+//!
+//! ジョブ（cron 式・タイムゾーン・ペイロード・スペース）を Postgres に保存し、サーバーの中で動く
+//! [`Scheduler::run`] が実行時刻を過ぎたジョブを [`JobHandler`] で実行します。ハンドラーは
+//! ジョブの種類（`kind`）ごとに [`Scheduler::with_handler`] で登録します。
+//!
+//! 複数のレプリカで動かしても二重に実行しないよう、実行するジョブは
+//! [`ScheduledJobRepository::claim_due`] で `claimed_until` を更新して確保してから処理します。
+//! 実行中は確保の期限を延ばし続け、結果は確保したときの期限のままの場合だけ記録します。
+//! サーバーの停止中に実行時刻を過ぎたジョブは、[`MissedRunPolicy`] に従って1回だけ実行するか、
+//! 次の時刻まで待ちます。
+
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use diesel::{prelude::*, sql_types::Bool};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use shared_infrastructure::{schema::scheduled_jobs, DbPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 定期実行の設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerSettings {
+    pub enabled: bool,
+    /// 登録時にタイムゾーンを省略した場合のタイムゾーン（IANA 名）
+    pub time_zone: String,
+    /// 実行するジョブを確認する間隔（秒）
+    pub poll_interval_secs: u64,
+    /// 実行中のジョブを確保しておく期間（秒、30日まで。実行中は延長し続け、レプリカが止まって延長されなく
+    /// なると期限が切れたあとで他のレプリカが再試行する）
+    pub lease_secs: u64,
+    /// 実行時刻からこれ以上遅れたジョブを「実行し損ねた」とみなす（秒、30日まで）
+    pub missed_run_grace_secs: u64,
+}
+
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            time_zone: "Asia/Tokyo".to_string(),
+            poll_interval_secs: 30,
+            lease_secs: 600,
+            missed_run_grace_secs: 300,
+        }
+    }
+}
+
+impl SchedulerSettings {
+    /// 秒数で指定する期間の上限（30日）
+    const MAX_SECS: u64 = 30 * 24 * 60 * 60;
+
+    /// 実行中のジョブを確保しておく期間（1秒〜30日）
+    pub fn lease(&self) -> Duration {
+        Self::duration(self.lease_secs.max(1))
+    }
+
+    /// 実行し損ねたとみなすまでの遅れ（30日まで）
+    pub fn missed_run_grace(&self) -> Duration {
+        Self::duration(self.missed_run_grace_secs)
+    }
+
+    fn duration(secs: u64) -> Duration {
+        Duration::try_seconds(secs.min(Self::MAX_SECS) as i64).unwrap_or_default()
+    }
+}
+
+/// 定期実行のエラー
+#[derive(Error, Debug)]
+pub enum SchedulerError {
+    #[error("Invalid cron expression '{expression}': {reason}")]
+    InvalidCron { expression: String, reason: String },
+
+    #[error("Unknown time zone: {0}")]
+    UnknownTimeZone(String),
+
+    #[error("Unknown job kind: {0}")]
+    UnknownKind(String),
+
+    #[error("Unknown missed run policy: {0}")]
+    UnknownPolicy(String),
+
+    #[error("Invalid payload for {kind}: {reason}")]
+    InvalidPayload { kind: String, reason: String },
+
+    #[error(transparent)]
+    Database(#[from] anyhow::Error),
+}
+
+/// 停止中に実行時刻を過ぎたジョブの扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissedRunPolicy {
+    /// 何回分過ぎていても1回だけ実行する
+    #[default]
+    RunOnce,
+    /// 実行せずに次の時刻まで待つ
+    Skip,
+}
+
+impl MissedRunPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MissedRunPolicy::RunOnce => "run_once",
+            MissedRunPolicy::Skip => "skip",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, SchedulerError> {
+        match value {
+            "run_once" => Ok(MissedRunPolicy::RunOnce),
+            "skip" => Ok(MissedRunPolicy::Skip),
+            _ => Err(SchedulerError::UnknownPolicy(value.to_string())),
+        }
+    }
+}
+
+/// タイムゾーン付きの cron 式
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    schedule: cron::Schedule,
+    time_zone: Tz,
+}
+
+impl CronSchedule {
+    /// cron 式を解析する
+    ///
+    /// 一般的な5項目（分 時 日 月 曜日）のほか、秒から始まる6・7項目の式も受け付けます。
+    /// 5項目の式の曜日は一般的な cron と同じく 0（または 7）= 日曜 〜 6 = 土曜 です。
+    /// 6・7項目の式は `cron` クレートの書式（1 = 日曜 〜 7 = 土曜）のまま解釈します。
+    pub fn parse(expression: &str, time_zone: &str) -> Result<Self, SchedulerError> {
+        let expression = expression.trim();
+        let invalid = |reason: String| SchedulerError::InvalidCron {
+            expression: expression.to_string(),
+            reason,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let normalized = match fields.as_slice() {
+            [minute, hour, day, month, weekday] => format!(
+                "0 {} {} {} {} {}",
+                minute,
+                hour,
+                day,
+                month,
+                standard_weekdays(weekday).map_err(invalid)?
+            ),
+            _ => expression.to_string(),
+        };
+        let schedule = cron::Schedule::from_str(&normalized).map_err(|e| invalid(e.to_string()))?;
+        let time_zone = time_zone
+            .parse()
+            .map_err(|_| SchedulerError::UnknownTimeZone(time_zone.to_string()))?;
+        Ok(Self {
+            schedule,
+            time_zone,
+        })
+    }
+
+    /// `now` より後の次の実行時刻（以降に実行する時刻がない場合は None）
+    ///
+    /// cron 式はタイムゾーンの時計の時刻で評価します。夏時間の切り替えで存在しない時刻は
+    /// 1時間後に実行し、2回ある時刻は1回目だけ実行します。
+    pub fn next_after(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = now.with_timezone(&self.time_zone).naive_local();
+        self.schedule
+            .after(&Utc.from_utc_datetime(&local))
+            .filter_map(|time| {
+                let local = time.naive_utc();
+                self.time_zone
+                    .from_local_datetime(&local)
+                    .earliest()
+                    .or_else(|| {
+                        self.time_zone
+                            .from_local_datetime(&(local + Duration::hours(1)))
+                            .earliest()
+                    })
+            })
+            .map(|time| time.with_timezone(&Utc))
+            .find(|time| *time > now)
+    }
+}
+
+/// 一般的な cron の曜日（0・7 = 日曜 〜 6 = 土曜）を曜日名に置き換える
+///
+/// `cron` クレートは 1 = 日曜 〜 7 = 土曜 と数えるため、数字のままだと1日ずれます。
+/// 範囲・刻みは数字の列に展開してから置き換え、曜日名や `*`・`?` はそのまま残します。
+fn standard_weekdays(field: &str) -> Result<String, String> {
+    const NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    let day = |value: &str| match value.parse::<usize>() {
+        Ok(day) if day <= 7 => Ok(day),
+        _ => Err(format!("invalid day of week: {}", value)),
+    };
+
+    let mut items = Vec::new();
+    for item in field.split(',') {
+        if !item
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || c == '*')
+            || item == "*"
+        {
+            items.push(item.to_string());
+            continue;
+        }
+
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<usize>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step: {}", step))?,
+            ),
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (0, 6),
+            Some((start, end)) => (day(start)?, day(end)?),
+            // `5/2` は 5 から週末まで
+            None if step > 1 => (day(range)?, 6),
+            None => (day(range)?, day(range)?),
+        };
+        if start > end {
+            return Err(format!("invalid day of week range: {}", range));
+        }
+        items.extend(
+            (start..=end)
+                .step_by(step)
+                .map(|day| NAMES[day % 7].to_string()),
+        );
+    }
+    Ok(items.join(","))
+}
+
+/// ジョブを実行するハンドラー
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    /// 登録時にペイロードを検証する
+    fn validate(&self, _payload: &serde_json::Value) -> Result<()> {
+        Ok(())
+    }
+
+    async fn run(&self, job: &ScheduledJob) -> Result<()>;
+}
+
+/// 登録済みのジョブ
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = scheduled_jobs)]
+pub struct ScheduledJob {
+    pub id: Uuid,
+    pub space_id: Option<Uuid>,
+    pub name: String,
+    pub kind: String,
+    pub cron: String,
+    pub time_zone: String,
+    pub payload: serde_json::Value,
+    pub missed_run_policy: String,
+    pub paused: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub claimed_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScheduledJob {
+    pub fn schedule(&self) -> Result<CronSchedule, SchedulerError> {
+        CronSchedule::parse(&self.cron, &self.time_zone)
+    }
+}
+
+/// ジョブの登録内容
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = scheduled_jobs)]
+pub struct NewScheduledJob {
+    pub space_id: Option<Uuid>,
+    pub name: String,
+    pub kind: String,
+    pub cron: String,
+    pub time_zone: String,
+    pub payload: serde_json::Value,
+    pub missed_run_policy: String,
+    pub next_run_at: DateTime<Utc>,
+}
+
+/// 登録するジョブ
+#[derive(Debug, Clone)]
+pub struct JobSpec {
+    pub name: String,
+    pub kind: String,
+    pub cron: String,
+    /// 省略時は [`SchedulerSettings::time_zone`]
+    pub time_zone: Option<String>,
+    pub payload: serde_json::Value,
+    pub missed_run_policy: MissedRunPolicy,
+}
+
+/// 定期実行するジョブのリポジトリ
+pub struct ScheduledJobRepository {
+    pool: DbPool,
+}
+
+impl ScheduledJobRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(&self, job: &NewScheduledJob) -> Result<ScheduledJob> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let job = diesel::insert_into(scheduled_jobs::table)
+            .values(job)
+            .returning(ScheduledJob::as_returning())
+            .get_result(&mut conn)
+            .await?;
+        Ok(job)
+    }
+
+    /// スペースのジョブ（次の実行時刻順）
+    pub async fn list(&self, space_id: Option<Uuid>) -> Result<Vec<ScheduledJob>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let jobs = scheduled_jobs::table
+            .filter(scheduled_jobs::space_id.is_not_distinct_from(space_id))
+            .order(scheduled_jobs::next_run_at.asc())
+            .select(ScheduledJob::as_select())
+            .load(&mut conn)
+            .await?;
+        Ok(jobs)
+    }
+
+    pub async fn find(&self, space_id: Option<Uuid>, id: Uuid) -> Result<Option<ScheduledJob>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let job = scheduled_jobs::table
+            .filter(scheduled_jobs::space_id.is_not_distinct_from(space_id))
+            .filter(scheduled_jobs::id.eq(id))
+            .select(ScheduledJob::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?;
+        Ok(job)
+    }
+
+    /// 一時停止・再開する（再開する場合は次の実行時刻も更新する）
+    pub async fn set_paused(
+        &self,
+        space_id: Option<Uuid>,
+        id: Uuid,
+        paused: bool,
+        next_run_at: DateTime<Utc>,
+    ) -> Result<Option<ScheduledJob>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let job = diesel::update(
+            scheduled_jobs::table
+                .filter(scheduled_jobs::space_id.is_not_distinct_from(space_id))
+                .filter(scheduled_jobs::id.eq(id)),
+        )
+        .set((
+            scheduled_jobs::paused.eq(paused),
+            scheduled_jobs::next_run_at.eq(next_run_at),
+        ))
+        .returning(ScheduledJob::as_returning())
+        .get_result(&mut conn)
+        .await
+        .optional()?;
+        Ok(job)
+    }
+
+    pub async fn delete(&self, space_id: Option<Uuid>, id: Uuid) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let deleted = diesel::delete(
+            scheduled_jobs::table
+                .filter(scheduled_jobs::space_id.is_not_distinct_from(space_id))
+                .filter(scheduled_jobs::id.eq(id)),
+        )
+        .execute(&mut conn)
+        .await?;
+        Ok(deleted > 0)
+    }
+
+    /// 実行時刻を過ぎたジョブを `lease` の間確保する
+    ///
+    /// 一時停止中のジョブと、他のレプリカが確保中のジョブは返しません。
+    pub async fn claim_due(
+        &self,
+        space_id: Option<Uuid>,
+        now: DateTime<Utc>,
+        lease: Duration,
+    ) -> Result<Vec<ScheduledJob>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let claimed = diesel::update(
+            scheduled_jobs::table
+                .filter(scheduled_jobs::space_id.is_not_distinct_from(space_id))
+                .filter(scheduled_jobs::paused.eq(false))
+                .filter(scheduled_jobs::next_run_at.le(now))
+                .filter(
+                    scheduled_jobs::claimed_until
+                        .is_null()
+                        .or(scheduled_jobs::claimed_until.lt(now)),
+                ),
+        )
+        .set(scheduled_jobs::claimed_until.eq(now + lease))
+        .returning(ScheduledJob::as_returning())
+        .get_results(&mut conn)
+        .await?;
+        Ok(claimed)
+    }
+
+    /// 確保の期限を `until` まで延ばす
+    ///
+    /// `job` を確保したときの期限のままの場合だけ延ばし、延ばした期限を返します。
+    /// 期限が切れて他のレプリカが確保し直していた場合は None を返します。
+    pub async fn renew(
+        &self,
+        job: &ScheduledJob,
+        until: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let claimed_until = diesel::update(
+            scheduled_jobs::table
+                .filter(scheduled_jobs::id.eq(job.id))
+                .filter(scheduled_jobs::claimed_until.is_not_distinct_from(job.claimed_until)),
+        )
+        .set(scheduled_jobs::claimed_until.eq(Some(until)))
+        .returning(scheduled_jobs::claimed_until)
+        .get_result::<Option<DateTime<Utc>>>(&mut conn)
+        .await
+        .optional()?;
+        Ok(claimed_until.flatten())
+    }
+
+    /// 実行結果を記録して次の実行時刻を設定し、確保を解除する
+    ///
+    /// 以降に実行する時刻がない場合（`next_run_at` が None）は一時停止します。
+    /// `job` を確保したときの期限のままの場合だけ更新し、更新した場合は true を返します。
+    pub async fn complete(
+        &self,
+        job: &ScheduledJob,
+        ran_at: DateTime<Utc>,
+        error: Option<String>,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let updated = diesel::update(
+            scheduled_jobs::table
+                .filter(scheduled_jobs::id.eq(job.id))
+                .filter(scheduled_jobs::claimed_until.is_not_distinct_from(job.claimed_until)),
+        )
+        .set((
+            scheduled_jobs::last_run_at.eq(Some(ran_at)),
+            scheduled_jobs::last_error.eq(error),
+            scheduled_jobs::next_run_at.eq(next_run_at.unwrap_or(job.next_run_at)),
+            scheduled_jobs::paused
+                .eq(scheduled_jobs::paused.or(next_run_at.is_none().into_sql::<Bool>())),
+            scheduled_jobs::claimed_until.eq(None::<DateTime<Utc>>),
+        ))
+        .execute(&mut conn)
+        .await?;
+        Ok(updated > 0)
+    }
+
+    /// 実行せずに次の実行時刻を設定し、確保を解除する
+    ///
+    /// `job` を確保したときの期限のままの場合だけ更新し、更新した場合は true を返します。
+    pub async fn skip(&self, job: &ScheduledJob, next_run_at: DateTime<Utc>) -> Result<bool> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+        let updated = diesel::update(
+            scheduled_jobs::table
+                .filter(scheduled_jobs::id.eq(job.id))
+                .filter(scheduled_jobs::claimed_until.is_not_distinct_from(job.claimed_until)),
+        )
+        .set((
+            scheduled_jobs::next_run_at.eq(next_run_at),
+            scheduled_jobs::claimed_until.eq(None::<DateTime<Utc>>),
+        ))
+        .execute(&mut conn)
+        .await?;
+        Ok(updated > 0)
+    }
+}
+
+/// ジョブを登録・実行するスケジューラー
+pub struct Scheduler {
+    repository: Arc<ScheduledJobRepository>,
+    handlers: BTreeMap<String, Arc<dyn JobHandler>>,
+    space_id: Option<Uuid>,
+    settings: SchedulerSettings,
+}
+
+impl Scheduler {
+    pub fn new(
+        repository: Arc<ScheduledJobRepository>,
+        space_id: Option<Uuid>,
+        settings: SchedulerSettings,
+    ) -> Self {
+        Self {
+            repository,
+            handlers: BTreeMap::new(),
+            space_id,
+            settings,
+        }
+    }
+
+    /// ジョブの種類 `kind` のハンドラーを登録する
+    pub fn with_handler(mut self, kind: impl Into<String>, handler: Arc<dyn JobHandler>) -> Self {
+        self.handlers.insert(kind.into(), handler);
+        self
+    }
+
+    pub fn settings(&self) -> &SchedulerSettings {
+        &self.settings
+    }
+
+    /// 登録できるジョブの種類
+    pub fn kinds(&self) -> Vec<String> {
+        self.handlers.keys().cloned().collect()
+    }
+
+    pub async fn list(&self) -> Result<Vec<ScheduledJob>> {
+        self.repository.list(self.space_id).await
+    }
+
+    /// ジョブを検証して登録する
+    pub async fn create(&self, spec: JobSpec) -> Result<ScheduledJob, SchedulerError> {
+        let handler = self
+            .handlers
+            .get(&spec.kind)
+            .ok_or_else(|| SchedulerError::UnknownKind(spec.kind.clone()))?;
+        handler
+            .validate(&spec.payload)
+            .map_err(|e| SchedulerError::InvalidPayload {
+                kind: spec.kind.clone(),
+                reason: e.to_string(),
+            })?;
+        let time_zone = spec
+            .time_zone
+            .unwrap_or_else(|| self.settings.time_zone.clone());
+        let next_run_at = Self::next_run_at(&spec.cron, &time_zone)?;
+
+        let job = self
+            .repository
+            .create(&NewScheduledJob {
+                space_id: self.space_id,
+                name: spec.name,
+                kind: spec.kind,
+                cron: spec.cron.trim().to_string(),
+                time_zone,
+                payload: spec.payload,
+                missed_run_policy: spec.missed_run_policy.as_str().to_string(),
+                next_run_at,
+            })
+            .await?;
+        Ok(job)
+    }
+
+    /// 一時停止する（ジョブがない場合は None）
+    pub async fn pause(&self, id: Uuid) -> Result<Option<ScheduledJob>, SchedulerError> {
+        let Some(job) = self.repository.find(self.space_id, id).await? else {
+            return Ok(None);
+        };
+        Ok(self
+            .repository
+            .set_paused(self.space_id, id, true, job.next_run_at)
+            .await?)
+    }
+
+    /// 再開する（停止中に過ぎた時刻は実行せず、次の時刻から実行する）
+    pub async fn resume(&self, id: Uuid) -> Result<Option<ScheduledJob>, SchedulerError> {
+        let Some(job) = self.repository.find(self.space_id, id).await? else {
+            return Ok(None);
+        };
+        let next_run_at = Self::next_run_at(&job.cron, &job.time_zone)?;
+        Ok(self
+            .repository
+            .set_paused(self.space_id, id, false, next_run_at)
+            .await?)
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        self.repository.delete(self.space_id, id).await
+    }
+
+    fn next_run_at(cron: &str, time_zone: &str) -> Result<DateTime<Utc>, SchedulerError> {
+        CronSchedule::parse(cron, time_zone)?
+            .next_after(Utc::now())
+            .ok_or_else(|| SchedulerError::InvalidCron {
+                expression: cron.to_string(),
+                reason: "no upcoming run".to_string(),
+            })
+    }
+
+    /// 実行時刻を過ぎたジョブを定期的に確認して実行する
+    pub async fn run(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.settings.poll_interval_secs.max(1));
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        tracing::info!(
+            "⏰ Scheduler started (every {}s, kinds: {})",
+            interval.as_secs(),
+            self.kinds().join(", ")
+        );
+
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.run_due().await {
+                tracing::error!("❌ Failed to run scheduled jobs: {}", e);
+            }
+        }
+    }
+
+    async fn run_due(&self) -> Result<()> {
+        let now = Utc::now();
+        let lease = self.settings.lease();
+        let due = self.repository.claim_due(self.space_id, now, lease).await?;
+
+        for job in due {
+            // 解析できないジョブは確保の期限が切れたあとで再試行される
+            let schedule = match job.schedule() {
+                Ok(schedule) => schedule,
+                Err(e) => {
+                    tracing::error!(
+                        "❌ Scheduled job {} ({}) is invalid: {}",
+                        job.name,
+                        job.id,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let grace = self.settings.missed_run_grace();
+            let policy = MissedRunPolicy::parse(&job.missed_run_policy).unwrap_or_default();
+            if policy == MissedRunPolicy::Skip && now - job.next_run_at > grace {
+                if let Some(next_run_at) = schedule.next_after(now) {
+                    tracing::info!(
+                        "⏭️ Skipped missed run of {} (scheduled at {})",
+                        job.name,
+                        job.next_run_at
+                    );
+                    if !self.repository.skip(&job, next_run_at).await? {
+                        tracing::warn!("⚠️ Lost the lease of {} before skipping it", job.name);
+                    }
+                    continue;
+                }
+            }
+
+            let mut job = job;
+            let error = match self.handlers.get(&job.kind).cloned() {
+                Some(handler) => self
+                    .run_with_lease(handler.as_ref(), &mut job, lease)
+                    .await
+                    .err()
+                    .map(|e| e.to_string()),
+                None => Some(SchedulerError::UnknownKind(job.kind.clone()).to_string()),
+            };
+            match &error {
+                None => tracing::info!("⏰ Scheduled job {} ({}) finished", job.name, job.kind),
+                Some(e) => {
+                    tracing::error!("❌ Scheduled job {} ({}) failed: {}", job.name, job.kind, e)
+                }
+            }
+
+            let next_run_at = schedule.next_after(Utc::now());
+            if !self
+                .repository
+                .complete(&job, now, error, next_run_at)
+                .await?
+            {
+                // 他のレプリカが確保し直したジョブの結果で上書きしない
+                tracing::warn!(
+                    "⚠️ Lost the lease of {} ({}) while running it, result discarded",
+                    job.name,
+                    job.id
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// 確保の期限を延ばしながらハンドラーを実行する
+    ///
+    /// 実行中は期限の 1/3 ごとに `claimed_until` を延ばすため、`lease_secs` より長いジョブでも
+    /// 他のレプリカが二重に実行しません。延ばした期限は `job.claimed_until` に反映します。
+    async fn run_with_lease(
+        &self,
+        handler: &dyn JobHandler,
+        job: &mut ScheduledJob,
+        lease: Duration,
+    ) -> Result<()> {
+        let interval = std::time::Duration::from_secs((lease.num_seconds() as u64 / 3).max(1));
+        let mut renewal =
+            tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        renewal.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        let claimed = job.clone();
+        let run = handler.run(&claimed);
+        tokio::pin!(run);
+        loop {
+            tokio::select! {
+                result = &mut run => return result,
+                _ = renewal.tick() => {
+                    match self.repository.renew(job, Utc::now() + lease).await {
+                        Ok(Some(until)) => job.claimed_until = Some(until),
+                        Ok(None) => tracing::warn!(
+                            "⚠️ Lost the lease of {} ({}) while running it",
+                            job.name,
+                            job.id
+                        ),
+                        Err(e) => tracing::warn!("Failed to renew the lease of {}: {}", job.name, e),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cron_schedule() {
+        // 平日の9時（東京）
+        let schedule = CronSchedule::parse("0 9 * * Mon-Fri", "Asia/Tokyo").unwrap();
+        // 2026-10-16（金）01:00 UTC = 10:00 JST → 次は月曜 09:00 JST
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 1, 0, 0).unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap())
+        );
+
+        // 数字の曜日は一般的な cron と同じく 0 = 日曜（1-5 は月〜金）
+        let schedule = CronSchedule::parse("0 9 * * 1-5", "Asia/Tokyo").unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap())
+        );
+        let schedule = CronSchedule::parse("0 9 * * 0", "Asia/Tokyo").unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap())
+        );
+        assert!(CronSchedule::parse("0 9 * * 8", "UTC").is_err());
+
+        // 秒から始まる式
+        let schedule = CronSchedule::parse("30 */15 * * * *", "UTC").unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2026, 10, 16, 1, 0, 30).unwrap())
+        );
+
+        assert!(matches!(
+            CronSchedule::parse("every day", "UTC"),
+            Err(SchedulerError::InvalidCron { .. })
+        ));
+        assert!(matches!(
+            CronSchedule::parse("0 9 * * *", "Mars/Olympus"),
+            Err(SchedulerError::UnknownTimeZone(_))
+        ));
+        assert_eq!(
+            MissedRunPolicy::parse("skip").unwrap(),
+            MissedRunPolicy::Skip
+        );
+        assert!(MissedRunPolicy::parse("catch_up").is_err());
+
+        // 大きすぎる期間は30日に収める
+        let settings = SchedulerSettings {
+            lease_secs: u64::MAX,
+            missed_run_grace_secs: u64::MAX,
+            ..Default::default()
+        };
+        assert_eq!(settings.lease(), Duration::days(30));
+        assert_eq!(settings.missed_run_grace(), Duration::days(30));
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use shared_infrastructure::{schema::spaces, DbPool};
+use uuid::Uuid;
+
+use crate::{
+    llm::LlmSettings, AnswerCacheSettings, DigestSettings, FeedbackSettings, IntentRoutingSettings,
+    LanguageSettings, PrefilterSettings, PromptSettings, RedactionSettings, SchedulerSettings,
+    ToolAgentSettings, UsageSettings,
+};
+
+/// スペースごとの設定（`spaces.settings` に JSONB で保存）
+///
+/// 未設定の項目は既定値で補われるため、空の `{}` でも動作します。
+/// 綴りを誤った設定が黙って無視されないよう、未知の項目はエラーになります。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpaceSettings {
+    pub llm: LlmSettings,
+    pub retrieval: RetrievalSettings,
+    pub answer: AnswerSettings,
+    pub verification: VerificationSettings,
+    pub agent: ToolAgentSettings,
+    /// プロンプトテンプレートの上書き
+    pub prompts: PromptSettings,
+    /// 回答言語
+    pub language: LanguageSettings,
+    /// 回答キャッシュ
+    pub cache: AnswerCacheSettings,
+    /// LLM 利用量の記録と料金表
+    pub usage: UsageSettings,
+    /// LLM に送る前の個人情報・秘密情報のマスキング
+    pub redaction: RedactionSettings,
+    /// メッセージの意図ごとの対応
+    pub intents: IntentRoutingSettings,
+    /// 意図を分類する前のルールによる絞り込み
+    pub prefilter: PrefilterSettings,
+    /// チャンネルのダイジェストの定期配信
+    pub digest: DigestSettings,
+    /// cron 式による定期実行
+    pub scheduler: SchedulerSettings,
+    /// 回答へのフィードバック（👍/👎 ボタンとリアクション）
+    pub feedback: FeedbackSettings,
+}
+
+/// Slack 検索の設定
+///
+/// 書き換えた各クエリを関連度順・新しい順で検索し、
+/// reciprocal rank fusion（`weight / (rrf_k + 順位)` の合計）で統合します。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetrievalSettings {
+    /// 関連度順の検索結果の重み
+    pub relevance_weight: f64,
+    /// 新しい順の検索結果の重み
+    pub recency_weight: f64,
+    /// RRF の定数 k（大きいほど下位の結果も重視される）
+    pub rrf_k: f64,
+    /// 1クエリ・1ソートあたりの検索件数
+    pub per_query_limit: u32,
+    /// 統合後にコンテキストを取得するメッセージ数
+    pub max_results: usize,
+    /// LLM に渡すコンテキスト全体のトークン数の上限（関連度の高い検索結果ほど多く割り当てる）
+    pub max_context_tokens: usize,
+    /// 1メッセージあたりのトークン数の上限（超えた分は切り詰める）
+    pub max_message_tokens: usize,
+    /// これを超えるスレッドは要約してから含める
+    pub max_thread_tokens: usize,
+}
+
+impl Default for RetrievalSettings {
+    fn default() -> Self {
+        Self {
+            relevance_weight: 1.0,
+            recency_weight: 1.0,
+            rrf_k: 60.0,
+            per_query_limit: 5,
+            max_results: 10,
+            max_context_tokens: 6000,
+            max_message_tokens: 300,
+            max_thread_tokens: 1000,
+        }
+    }
+}
+
+/// 回答の確信度に応じた扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerDecision {
+    /// そのまま投稿する
+    #[default]
+    Post,
+    /// 注意書きを付けて投稿する
+    PostWithDisclaimer,
+    /// 回答しない（情報不足）
+    Abstain,
+}
+
+/// 回答の投稿可否を決める設定
+///
+/// 確信度が `post_threshold` 以上ならそのまま投稿、`disclaimer_threshold` 以上なら
+/// 注意書き付きで投稿、それ未満または情報不足と判定された場合は回答しません。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnswerSettings {
+    pub post_threshold: f64,
+    pub disclaimer_threshold: f64,
+}
+
+impl Default for AnswerSettings {
+    fn default() -> Self {
+        Self {
+            post_threshold: 0.7,
+            disclaimer_threshold: 0.4,
+        }
+    }
+}
+
+impl AnswerDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnswerDecision::Post => "post",
+            AnswerDecision::PostWithDisclaimer => "post_with_disclaimer",
+            AnswerDecision::Abstain => "abstain",
+        }
+    }
+}
+
+impl AnswerSettings {
+    /// 確信度（不明な場合は None）と情報不足の判定から扱いを決める
+    pub fn decide(&self, confidence: Option<f64>, insufficient_context: bool) -> AnswerDecision {
+        if insufficient_context {
+            return AnswerDecision::Abstain;
+        }
+
+        match confidence {
+            Some(c) if c < self.disclaimer_threshold => AnswerDecision::Abstain,
+            Some(c) if c < self.post_threshold => AnswerDecision::PostWithDisclaimer,
+            _ => AnswerDecision::Post,
+        }
+    }
+}
+
+/// 出典に裏付けられない主張の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsupportedClaimAction {
+    /// 回答はそのままにして、裏付けのない記述を注記する
+    #[default]
+    Flag,
+    /// 裏付けのない記述を取り除いた回答に差し替える
+    Strip,
+}
+
+/// 回答の検証（grounding verification）の設定
+///
+/// 有効にすると回答生成後に LLM をもう一度呼び出すため、コストと応答時間が増えます。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VerificationSettings {
+    pub enabled: bool,
+    pub on_unsupported: UnsupportedClaimAction,
+}
+
+/// スペース
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = spaces)]
+pub struct Space {
+    pub id: Uuid,
+    pub name: String,
+    pub slack_team_id: Option<String>,
+    pub settings: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Space {
+    /// 設定を読み込む
+    ///
+    /// 壊れた設定を既定値で黙って置き換えないよう、解釈できない場合はエラーを返します。
+    pub fn settings(&self) -> Result<SpaceSettings> {
+        serde_json::from_value(self.settings.clone())
+            .with_context(|| format!("Invalid settings for space {}", self.name))
+    }
+}
+
+/// スペースのリポジトリ
+pub struct SpaceRepository {
+    pool: DbPool,
+}
+
+impl SpaceRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Slack ワークスペースIDからスペースを取得
+    pub async fn find_by_slack_team_id(&self, team_id: &str) -> Result<Option<Space>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let space = spaces::table
+            .filter(spaces::slack_team_id.eq(team_id))
+            .select(Space::as_select())
+            .first(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_decision_thresholds() {
+        let settings = AnswerSettings::default();
+
+        assert_eq!(settings.decide(Some(0.9), false), AnswerDecision::Post);
+        assert_eq!(
+            settings.decide(Some(0.5), false),
+            AnswerDecision::PostWithDisclaimer
+        );
+        assert_eq!(settings.decide(Some(0.1), false), AnswerDecision::Abstain);
+        assert_eq!(settings.decide(Some(0.9), true), AnswerDecision::Abstain);
+        // 確信度を返さないモデルではこれまでどおり投稿する
+        assert_eq!(settings.decide(None, false), AnswerDecision::Post);
+    }
+
+    #[test]
+    fn test_space_settings() {
+        let space = |settings: serde_json::Value| Space {
+            id: Uuid::nil(),
+            name: "test".to_string(),
+            slack_team_id: None,
+            settings,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert_eq!(
+            space(serde_json::json!({})).settings().unwrap(),
+            SpaceSettings::default()
+        );
+        assert!(space(serde_json::json!({"digest": {"enabled": "yes"}}))
+            .settings()
+            .is_err());
+
+        // 綴りの誤りは無視せずにエラーにする
+        assert!(space(serde_json::json!({"redactoin": {"enabled": false}}))
+            .settings()
+            .is_err());
+        assert!(
+            space(serde_json::json!({"verification": {"enabled": true, "treshold": 0.9}}))
+                .settings()
+                .is_err()
+        );
+        assert!(space(serde_json::json!({"llm": {"modle": "gpt-4o"}}))
+            .settings()
+            .is_err());
+        let settings =
+            space(serde_json::json!({"llm": {"provider": "anthropic", "model": "claude"}}))
+                .settings()
+                .unwrap();
+        assert_eq!(
+            settings.llm.default.provider,
+            crate::llm::LlmProviderKind::Anthropic
+        );
+    }
+}
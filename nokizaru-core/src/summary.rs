@@ -0,0 +1,137 @@
+//! チャンネル・スレッドの要約
+//!
+//! 長い会話は一度に LLM に渡せないため、メッセージを [`chunk_lines`] でトークン数ごとに分け、
+//! 各まとまりを要約してから（map）、要約をまとめて決定事項・未解決の質問・対応事項を取り出します（reduce）。
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::TokenCounter;
+
+/// 会話の要約
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConversationSummary {
+    #[schemars(description = "Two or three sentences on what the conversation was about.")]
+    pub overview: String,
+    #[schemars(description = "Decisions and conclusions that were agreed on.")]
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[schemars(description = "Questions or issues that were left unresolved.")]
+    #[serde(default)]
+    pub open_questions: Vec<String>,
+    #[schemars(description = "Tasks someone took on or was asked to do.")]
+    #[serde(default)]
+    pub action_items: Vec<ActionItem>,
+}
+
+/// 対応事項
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ActionItem {
+    #[schemars(description = "What needs to be done.")]
+    pub task: String,
+    #[schemars(
+        description = "Who is responsible, as written in the conversation (keep Slack mentions like <@U0123ABC>). Null if nobody was named."
+    )]
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[schemars(description = "The deadline if one was mentioned, otherwise null.")]
+    #[serde(default)]
+    pub due: Option<String>,
+}
+
+impl ConversationSummary {
+    /// Slack に投稿する mrkdwn（空の項目は省く）
+    pub fn to_mrkdwn(&self, title: &str) -> String {
+        let mut lines = vec![format!("*{}*", title), self.overview.trim().to_string()];
+
+        let mut section = |heading: &str, items: Vec<String>| {
+            if !items.is_empty() {
+                lines.push(String::new());
+                lines.push(format!("*{}*", heading));
+                lines.extend(items.into_iter().map(|item| format!("• {}", item)));
+            }
+        };
+        section("決定事項", self.decisions.clone());
+        section("未解決の質問", self.open_questions.clone());
+        section(
+            "対応事項",
+            self.action_items
+                .iter()
+                .map(|item| {
+                    let mut line = item.task.clone();
+                    if let Some(owner) = &item.owner {
+                        line.push_str(&format!("（担当: {}）", owner));
+                    }
+                    if let Some(due) = &item.due {
+                        line.push_str(&format!("（期限: {}）", due));
+                    }
+                    line
+                })
+                .collect(),
+        );
+
+        lines.join("\n")
+    }
+}
+
+/// 行をトークン数が `max_tokens` 以内のまとまりに分ける
+///
+/// 1行で `max_tokens` を超える行は切り詰めます。
+pub fn chunk_lines(lines: &[String], counter: &dyn TokenCounter, max_tokens: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+
+    for line in lines {
+        let line = counter.truncate(line, max_tokens);
+        let tokens = counter.count(&line) + 1;
+        if !current.is_empty() && current_tokens + tokens > max_tokens {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        current.push_str(&line);
+        current.push('\n');
+        current_tokens += tokens;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::EstimatedTokenCounter;
+
+    #[test]
+    fn test_chunk_lines_and_mrkdwn() {
+        let counter = EstimatedTokenCounter::for_model("gpt-4.1");
+        let line = "[09:00] <@U1>: 本番のデプロイは金曜の17時に決まりました".to_string();
+        let line_tokens = counter.count(&line) + 1;
+        let lines = vec![line; 5];
+
+        let chunks = chunk_lines(&lines, &counter, line_tokens * 2);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].lines().count(), 2);
+        assert_eq!(chunks[2].lines().count(), 1);
+        assert_eq!(chunk_lines(&lines, &counter, 100_000).len(), 1);
+        assert!(chunk_lines(&[], &counter, 100).is_empty());
+
+        let summary = ConversationSummary {
+            overview: "デプロイ日程を決めました。".to_string(),
+            decisions: vec!["金曜17時にデプロイする".to_string()],
+            open_questions: Vec::new(),
+            action_items: vec![ActionItem {
+                task: "リリースノートを書く".to_string(),
+                owner: Some("<@U1>".to_string()),
+                due: Some("木曜".to_string()),
+            }],
+        };
+        assert_eq!(
+            summary.to_mrkdwn("#dev の要約"),
+            "*#dev の要約*\nデプロイ日程を決めました。\n\n*決定事項*\n• 金曜17時にデプロイする\n\n\
+             *対応事項*\n• リリースノートを書く（担当: <@U1>）（期限: 木曜）"
+        );
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use futures::future::join_all;
+use rig::{completion::message::ToolChoice, tool::ToolSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    llm::{LlmRequest, LlmStage, LlmToolCall, ModelRouter},
+    ChatTurn,
+};
+
+/// モデルに返すツール結果の最大文字数
+const MAX_TOOL_OUTPUT_CHARS: usize = 8000;
+/// トレースに記録するツール結果の最大文字数
+const MAX_TRACE_OUTPUT_CHARS: usize = 1000;
+
+/// エージェントモード（ツール呼び出し）の設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolAgentSettings {
+    /// 有効にすると、固定のパイプラインの代わりにエージェントが検索しながら回答する
+    pub enabled: bool,
+    /// モデルを呼び出す最大回数（ツール呼び出しのラウンド数）
+    pub max_iterations: usize,
+    /// ツール1回あたりのタイムアウト（秒）
+    pub tool_timeout_secs: u64,
+}
+
+impl Default for ToolAgentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_iterations: 6,
+            tool_timeout_secs: 15,
+        }
+    }
+}
+
+/// ツール呼び出しの記録
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallRecord {
+    /// 何回目のモデル呼び出しで要求されたか（1始まり）
+    pub iteration: usize,
+    pub name: String,
+    pub arguments: serde_json::Value,
+    /// 結果（長い場合は切り詰める）
+    pub output: String,
+    pub is_error: bool,
+    pub elapsed_ms: u64,
+}
+
+/// エージェントの実行結果
+#[derive(Debug, Clone)]
+pub struct ToolAgentOutcome {
+    pub answer: String,
+    pub trace: Vec<ToolCallRecord>,
+    /// 各ツール呼び出しの結果（切り詰める前、`trace` と同じ順）
+    pub outputs: Vec<String>,
+    /// 最大回数に達して打ち切ったかどうか
+    pub exhausted: bool,
+}
+
+/// ツールを呼び出しながら回答するエージェント
+///
+/// モデルがツール呼び出しを返す限り、ツールを実行して結果を渡すことを繰り返します。
+/// `max_iterations` に達した場合は、それまでに集めた情報で回答させます。
+pub struct ToolAgent {
+    router: Arc<ModelRouter>,
+    tools: ToolSet,
+    settings: ToolAgentSettings,
+}
+
+impl ToolAgent {
+    pub fn new(router: Arc<ModelRouter>, tools: ToolSet, settings: ToolAgentSettings) -> Self {
+        Self {
+            router,
+            tools,
+            settings,
+        }
+    }
+
+    pub async fn run(
+        &self,
+        preamble: &str,
+        question: &str,
+        history: &[ChatTurn],
+    ) -> Result<ToolAgentOutcome> {
+        let tools = self.tools.get_tool_definitions().await?;
+        let mut turns = history.to_vec();
+        turns.push(ChatTurn::user(question));
+        let mut trace = Vec::new();
+        let mut outputs = Vec::new();
+
+        for iteration in 1..=self.settings.max_iterations {
+            let response = self
+                .router
+                .complete(
+                    LlmStage::Answer,
+                    LlmRequest {
+                        preamble: preamble.to_string(),
+                        history: turns.clone(),
+                        tools: tools.clone(),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+
+            if response.tool_calls.is_empty() {
+                return Ok(ToolAgentOutcome {
+                    answer: response.text,
+                    trace,
+                    outputs,
+                    exhausted: false,
+                });
+            }
+
+            turns.push(ChatTurn::assistant_tool_calls(
+                response.text,
+                response.tool_calls.clone(),
+            ));
+
+            let results = join_all(
+                response
+                    .tool_calls
+                    .into_iter()
+                    .map(|call| self.call_tool(iteration, call)),
+            )
+            .await;
+            for (call, raw_output, record) in results {
+                trace.push(record);
+                turns.push(ChatTurn::tool_result(
+                    call,
+                    truncate(&raw_output, MAX_TOOL_OUTPUT_CHARS),
+                ));
+                outputs.push(raw_output);
+            }
+        }
+
+        tracing::warn!(
+            "Tool agent reached the iteration limit ({})",
+            self.settings.max_iterations
+        );
+
+        // ツール呼び出しを禁止して、集めた情報で回答させる
+        let response = self
+            .router
+            .complete(
+                LlmStage::Answer,
+                LlmRequest {
+                    preamble: format!(
+                        "{}\n\nYou have used all of your tool calls. \
+                        Answer now using only the information gathered so far.",
+                        preamble
+                    ),
+                    history: turns,
+                    tools,
+                    tool_choice: Some(ToolChoice::None),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        Ok(ToolAgentOutcome {
+            answer: response.text,
+            trace,
+            outputs,
+            exhausted: true,
+        })
+    }
+
+    /// ツールを実行し、結果（切り詰める前）とトレースを作る
+    ///
+    /// エラーやタイムアウトも結果としてモデルに返し、別の方法を試せるようにします。
+    async fn call_tool(
+        &self,
+        iteration: usize,
+        call: LlmToolCall,
+    ) -> (LlmToolCall, String, ToolCallRecord) {
+        let timeout = Duration::from_secs(self.settings.tool_timeout_secs);
+        let started = Instant::now();
+
+        let result = tokio::time::timeout(
+            timeout,
+            self.tools.call(&call.name, call.arguments.to_string()),
+        )
+        .await;
+        let (output, is_error) = match result {
+            Ok(Ok(output)) => (output, false),
+            Ok(Err(e)) => (format!("Error: {}", e), true),
+            Err(_) => (format!("Error: timed out after {:?}", timeout), true),
+        };
+
+        tracing::info!(
+            "[tool {}] {}({}) -> {}",
+            iteration,
+            call.name,
+            call.arguments,
+            if is_error { &output } else { "ok" }
+        );
+
+        let record = ToolCallRecord {
+            iteration,
+            name: call.name.clone(),
+            arguments: call.arguments.clone(),
+            output: truncate(&output, MAX_TRACE_OUTPUT_CHARS),
+            is_error,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        };
+
+        (call, output, record)
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        text.chars().take(max_chars).collect::<String>() + "…"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockProvider;
+    use rig::{completion::ToolDefinition, tool::Tool};
+    use serde_json::json;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("lookup failed")]
+    struct LookupError;
+
+    #[derive(Deserialize)]
+    struct LookupArgs {
+        name: String,
+    }
+
+    struct LookupTool;
+
+    impl Tool for LookupTool {
+        const NAME: &'static str = "lookup";
+        type Error = LookupError;
+        type Args = LookupArgs;
+        type Output = String;
+
+        async fn definition(&self, _prompt: String) -> ToolDefinition {
+            ToolDefinition {
+                name: Self::NAME.to_string(),
+                description: "Look up a person's role".to_string(),
+                parameters: json!({"type": "object", "properties": {"name": {"type": "string"}}}),
+            }
+        }
+
+        async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+            match args.name.as_str() {
+                "山田" => Ok("課長".to_string()),
+                _ => Err(LookupError),
+            }
+        }
+    }
+
+    fn tool_call(id: &str, name: &str) -> LlmToolCall {
+        LlmToolCall {
+            id: id.to_string(),
+            call_id: None,
+            name: "lookup".to_string(),
+            arguments: json!({ "name": name }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_runs_tools_until_answer() {
+        let provider = Arc::new(MockProvider::default());
+        provider.push_tool_calls(vec![tool_call("1", "山田"), tool_call("2", "佐藤")]);
+        provider.push_response("山田さんは課長です。");
+
+        let agent = ToolAgent::new(
+            Arc::new(ModelRouter::single(provider.clone(), "mock-model")),
+            ToolSet::from_tools(vec![LookupTool]),
+            ToolAgentSettings::default(),
+        );
+        let outcome = agent.run("", "山田さんの役職は？", &[]).await.unwrap();
+
+        assert_eq!(outcome.answer, "山田さんは課長です。");
+        assert!(!outcome.exhausted);
+        assert_eq!(outcome.trace.len(), 2);
+        assert!(!outcome.trace[0].is_error);
+        assert!(outcome.trace[1].is_error);
+
+        // 2回目の呼び出しには、ツール結果が履歴として渡される
+        let history = &provider.requests()[1].history;
+        assert_eq!(history.len(), 4);
+        assert!(history[2].content.contains("課長"));
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_iteration_limit() {
+        let provider = Arc::new(MockProvider::default());
+        provider.push_tool_calls(vec![tool_call("1", "山田")]);
+        provider.push_response("わかりませんでした。");
+
+        let agent = ToolAgent::new(
+            Arc::new(ModelRouter::single(provider.clone(), "mock-model")),
+            ToolSet::from_tools(vec![LookupTool]),
+            ToolAgentSettings {
+                max_iterations: 1,
+                ..Default::default()
+            },
+        );
+        let outcome = agent.run("", "山田さんの役職は？", &[]).await.unwrap();
+
+        assert!(outcome.exhausted);
+        assert_eq!(provider.requests()[1].tool_choice, Some(ToolChoice::None));
+    }
+}
@@ -0,0 +1,388 @@
+//! LLM の利用量とコスト
+//!
+//! [`ModelRouter`](crate::llm::ModelRouter) の呼び出しごとにトークン数・所要時間と
+//! 料金表から計算したコストを `llm_usage_logs` に記録し、日ごと・ステージごとに集計します。
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::{
+    prelude::*,
+    sql_types::{BigInt, Date, Double, Nullable, Text, Timestamptz, Uuid as SqlUuid, Varchar},
+};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use shared_infrastructure::{schema::llm_usage_logs, DbPool};
+use uuid::Uuid;
+
+use crate::llm::{LlmCallRecord, LlmUsage, UsageRecorder};
+
+/// モデルの料金（米ドル / 100万トークン）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+
+    /// トークン数からコストを計算する
+    pub fn cost(&self, usage: LlmUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_million
+            + usage.output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// 組み込みの料金表（モデル名の前方一致、公開されている定価）
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-4.1", ModelPrice::new(2.0, 8.0)),
+    ("gpt-4.1-mini", ModelPrice::new(0.4, 1.6)),
+    ("gpt-4.1-nano", ModelPrice::new(0.1, 0.4)),
+    ("gpt-4o", ModelPrice::new(2.5, 10.0)),
+    ("gpt-4o-mini", ModelPrice::new(0.15, 0.6)),
+    ("o3", ModelPrice::new(2.0, 8.0)),
+    ("o4-mini", ModelPrice::new(1.1, 4.4)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0)),
+    ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0)),
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0)),
+    ("claude-haiku-4-5", ModelPrice::new(1.0, 5.0)),
+];
+
+/// 利用量の記録の設定
+///
+/// `prices` はモデル名（前方一致）ごとの料金で、組み込みの料金表より優先されます。
+/// Azure のデプロイメント名やローカルモデルなど、料金表にないモデルのコストは記録されません。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageSettings {
+    pub enabled: bool,
+    pub prices: BTreeMap<String, ModelPrice>,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prices: BTreeMap::new(),
+        }
+    }
+}
+
+impl UsageSettings {
+    /// モデルの料金（最も長く一致する名前を使う）
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        fn longest_match<'a>(
+            model: &str,
+            prices: impl Iterator<Item = (&'a str, ModelPrice)>,
+        ) -> Option<ModelPrice> {
+            prices
+                .filter(|(name, _)| model.starts_with(name))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, price)| price)
+        }
+
+        longest_match(
+            model,
+            self.prices
+                .iter()
+                .map(|(name, price)| (name.as_str(), *price)),
+        )
+        .or_else(|| longest_match(model, BUILTIN_PRICES.iter().copied()))
+    }
+}
+
+/// LLM 呼び出しの記録
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = llm_usage_logs)]
+pub struct LlmUsageLog {
+    pub id: Uuid,
+    pub space_id: Option<Uuid>,
+    pub channel_id: Option<String>,
+    pub user_id: Option<String>,
+    /// パイプラインのステージ（[`LlmStage`](crate::llm::LlmStage) の文字列表現）
+    pub stage: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub latency_ms: i32,
+    /// 料金表から計算したコスト（米ドル、料金表にないモデルは None）
+    pub cost_usd: Option<f64>,
+    pub succeeded: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// LLM 呼び出しの記録の登録内容
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = llm_usage_logs)]
+pub struct NewLlmUsageLog {
+    pub space_id: Option<Uuid>,
+    pub channel_id: Option<String>,
+    pub user_id: Option<String>,
+    pub stage: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub latency_ms: i32,
+    pub cost_usd: Option<f64>,
+    pub succeeded: bool,
+}
+
+/// 集計の条件
+#[derive(Debug, Clone)]
+pub struct UsageFilter {
+    pub space_id: Option<Uuid>,
+    /// この日時以降の呼び出しを集計する
+    pub since: DateTime<Utc>,
+    pub channel_id: Option<String>,
+    pub user_id: Option<String>,
+}
+
+impl UsageFilter {
+    /// 直近 `days` 日（今日を含む、UTC）の集計条件
+    pub fn last_days(space_id: Option<Uuid>, days: u32) -> Self {
+        let today = Utc::now().date_naive();
+        let since = today - chrono::Duration::days(days.saturating_sub(1) as i64);
+        Self {
+            space_id,
+            since: since.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+            channel_id: None,
+            user_id: None,
+        }
+    }
+}
+
+/// 日ごとの利用量（UTC）
+#[derive(Debug, Clone, PartialEq, QueryableByName, Serialize)]
+pub struct DailyUsage {
+    #[diesel(sql_type = Date)]
+    pub day: NaiveDate,
+    #[diesel(sql_type = BigInt)]
+    pub calls: i64,
+    #[diesel(sql_type = BigInt)]
+    pub input_tokens: i64,
+    #[diesel(sql_type = BigInt)]
+    pub output_tokens: i64,
+    #[diesel(sql_type = Double)]
+    pub cost_usd: f64,
+    /// 料金表にないモデルの呼び出し数（`cost_usd` に含まれない）
+    #[diesel(sql_type = BigInt)]
+    pub unpriced_calls: i64,
+}
+
+/// ステージ・モデルごとの利用量
+#[derive(Debug, Clone, PartialEq, QueryableByName, Serialize)]
+pub struct StageUsage {
+    #[diesel(sql_type = Text)]
+    pub stage: String,
+    #[diesel(sql_type = Text)]
+    pub model: String,
+    #[diesel(sql_type = BigInt)]
+    pub calls: i64,
+    #[diesel(sql_type = BigInt)]
+    pub input_tokens: i64,
+    #[diesel(sql_type = BigInt)]
+    pub output_tokens: i64,
+    #[diesel(sql_type = Double)]
+    pub cost_usd: f64,
+    #[diesel(sql_type = Double)]
+    pub avg_latency_ms: f64,
+    #[diesel(sql_type = BigInt)]
+    pub failed_calls: i64,
+}
+
+/// 集計の共通の条件（$1: space_id, $2: since, $3: channel_id, $4: user_id）
+const USAGE_CONDITIONS: &str = "WHERE space_id IS NOT DISTINCT FROM $1 AND created_at >= $2 \
+     AND ($3::VARCHAR IS NULL OR channel_id = $3) AND ($4::VARCHAR IS NULL OR user_id = $4)";
+
+/// LLM 利用量のリポジトリ
+pub struct UsageRepository {
+    pool: DbPool,
+}
+
+impl UsageRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// 呼び出しを記録
+    pub async fn create(&self, log: &NewLlmUsageLog) -> Result<LlmUsageLog> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let log = diesel::insert_into(llm_usage_logs::table)
+            .values(log)
+            .returning(LlmUsageLog::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(log)
+    }
+
+    /// 日ごとに集計（古い順）
+    pub async fn daily(&self, filter: &UsageFilter) -> Result<Vec<DailyUsage>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let query = format!(
+            "SELECT (created_at AT TIME ZONE 'UTC')::DATE AS day, \
+               COUNT(*) AS calls, \
+               COALESCE(SUM(input_tokens), 0)::BIGINT AS input_tokens, \
+               COALESCE(SUM(output_tokens), 0)::BIGINT AS output_tokens, \
+               COALESCE(SUM(cost_usd), 0)::DOUBLE PRECISION AS cost_usd, \
+               COUNT(*) FILTER (WHERE cost_usd IS NULL) AS unpriced_calls \
+             FROM llm_usage_logs {} GROUP BY day ORDER BY day",
+            USAGE_CONDITIONS
+        );
+        let rows = diesel::sql_query(query)
+            .bind::<Nullable<SqlUuid>, _>(filter.space_id)
+            .bind::<Timestamptz, _>(filter.since)
+            .bind::<Nullable<Varchar>, _>(filter.channel_id.as_deref())
+            .bind::<Nullable<Varchar>, _>(filter.user_id.as_deref())
+            .load(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// ステージ・モデルごとに集計（コストの大きい順）
+    pub async fn by_stage(&self, filter: &UsageFilter) -> Result<Vec<StageUsage>> {
+        let mut conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get connection from pool: {}", e))?;
+
+        let query = format!(
+            "SELECT stage::TEXT AS stage, model::TEXT AS model, \
+               COUNT(*) AS calls, \
+               COALESCE(SUM(input_tokens), 0)::BIGINT AS input_tokens, \
+               COALESCE(SUM(output_tokens), 0)::BIGINT AS output_tokens, \
+               COALESCE(SUM(cost_usd), 0)::DOUBLE PRECISION AS cost_usd, \
+               COALESCE(AVG(latency_ms), 0)::DOUBLE PRECISION AS avg_latency_ms, \
+               COUNT(*) FILTER (WHERE NOT succeeded) AS failed_calls \
+             FROM llm_usage_logs {} GROUP BY stage, model ORDER BY cost_usd DESC, calls DESC",
+            USAGE_CONDITIONS
+        );
+        let rows = diesel::sql_query(query)
+            .bind::<Nullable<SqlUuid>, _>(filter.space_id)
+            .bind::<Timestamptz, _>(filter.since)
+            .bind::<Nullable<Varchar>, _>(filter.channel_id.as_deref())
+            .bind::<Nullable<Varchar>, _>(filter.user_id.as_deref())
+            .load(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+}
+
+/// LLM 呼び出しを `llm_usage_logs` に記録する [`UsageRecorder`]
+///
+/// 保存はバックグラウンドで行い、失敗してもログ出力のみ行います。
+pub struct UsageLogRecorder {
+    repository: Arc<UsageRepository>,
+    space_id: Option<Uuid>,
+    settings: UsageSettings,
+}
+
+impl UsageLogRecorder {
+    pub fn new(
+        repository: Arc<UsageRepository>,
+        space_id: Option<Uuid>,
+        settings: UsageSettings,
+    ) -> Self {
+        Self {
+            repository,
+            space_id,
+            settings,
+        }
+    }
+}
+
+impl UsageRecorder for UsageLogRecorder {
+    fn record(&self, call: LlmCallRecord) {
+        let cost_usd = self
+            .settings
+            .price(&call.model)
+            .map(|price| price.cost(call.usage));
+        tracing::debug!(
+            "[{}] {}/{}: {} in / {} out tokens, {:?}, cost: {:?}",
+            call.stage,
+            call.provider,
+            call.model,
+            call.usage.input_tokens,
+            call.usage.output_tokens,
+            call.latency,
+            cost_usd
+        );
+
+        let log = NewLlmUsageLog {
+            space_id: self.space_id,
+            channel_id: call.tags.channel_id,
+            user_id: call.tags.user_id,
+            stage: call.stage.as_str().to_string(),
+            provider: call.provider,
+            model: call.model,
+            input_tokens: call.usage.input_tokens.min(i64::MAX as u64) as i64,
+            output_tokens: call.usage.output_tokens.min(i64::MAX as u64) as i64,
+            latency_ms: call.latency.as_millis().min(i32::MAX as u128) as i32,
+            cost_usd,
+            succeeded: call.succeeded,
+        };
+        let repository = Arc::clone(&self.repository);
+        tokio::spawn(async move {
+            if let Err(e) = repository.create(&log).await {
+                tracing::warn!("Failed to record LLM usage: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_lookup_and_cost() {
+        let settings: UsageSettings = serde_json::from_value(serde_json::json!({
+            "prices": {"my-deployment": {"input_per_million": 1.0, "output_per_million": 2.0}}
+        }))
+        .unwrap();
+
+        // 最も長く一致するモデル名の料金を使う
+        assert_eq!(
+            settings.price("gpt-4.1-mini-2025-04-14"),
+            Some(ModelPrice::new(0.4, 1.6))
+        );
+        assert_eq!(settings.price("gpt-4.1"), Some(ModelPrice::new(2.0, 8.0)));
+        assert_eq!(
+            settings.price("my-deployment"),
+            Some(ModelPrice::new(1.0, 2.0))
+        );
+        assert_eq!(settings.price("llama3.1:8b"), None);
+
+        let cost = ModelPrice::new(2.0, 8.0).cost(LlmUsage {
+            input_tokens: 1_000,
+            output_tokens: 500,
+        });
+        assert!((cost - 0.006).abs() < 1e-12);
+    }
+}
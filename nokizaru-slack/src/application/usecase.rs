@@ -1,6 +1,9 @@
 use crate::{
-    domain::{SlackCommand, SlackCommandService, SlackError, SlackEvent},
-    EventService,
+    domain::{
+        SlackCommand, SlackCommandResponse, SlackCommandService, SlackError, SlackEvent,
+        SlackInteraction,
+    },
+    EventService, InteractionService,
 };
 use std::sync::Arc;
 
@@ -31,8 +34,24 @@ impl ExecuteCommandUsecase {
         Self { command_service }
     }
 
-    pub async fn execute(&self, command: SlackCommand) -> Result<String, SlackError> {
+    pub async fn execute(&self, command: SlackCommand) -> Result<SlackCommandResponse, SlackError> {
         tracing::debug!("Executing command usecase: {}", command.command);
         self.command_service.execute_command(command).await
     }
 }
+
+/// インタラクション処理ユースケース
+pub struct ProcessInteractionUsecase {
+    interaction_service: Arc<InteractionService>,
+}
+
+impl ProcessInteractionUsecase {
+    pub fn new(interaction_service: Arc<InteractionService>) -> Self {
+        Self { interaction_service }
+    }
+
+    pub async fn execute(&self, interaction: SlackInteraction) -> Result<(), SlackError> {
+        tracing::debug!("Executing interaction usecase: {}", interaction.action_id);
+        self.interaction_service.execute(interaction).await
+    }
+}
@@ -1,4 +1,11 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nokizaru_core::{AnswerDecision, Language, PromptKind, ToolCallRecord};
+
+use crate::slack_api::Block;
 
 /// Slackメッセージのドメインモデル
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +17,25 @@ pub struct SlackMessage {
     pub thread_ts: Option<String>,
 }
 
+/// 会話の種類（検索結果を回答に使えるかの判定に使う）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversationKind {
+    /// 公開チャンネル
+    Public,
+    /// プライベートチャンネル
+    Private,
+    /// ダイレクトメッセージ
+    DirectMessage,
+    /// グループダイレクトメッセージ
+    GroupDirectMessage,
+}
+
+impl ConversationKind {
+    pub fn is_public(&self) -> bool {
+        matches!(self, ConversationKind::Public)
+    }
+}
+
 /// Slackイベントのドメインモデル
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -21,10 +47,21 @@ pub enum SlackEvent {
         user: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
         bot_id: Option<String>,
+        /// 編集・削除などの通知では本文がない
+        #[serde(default)]
         text: String,
         ts: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         thread_ts: Option<String>,
+        /// `message_changed` / `message_deleted` など（通常の投稿では None）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        subtype: Option<String>,
+        /// 編集後のメッセージ（`message_changed` のみ）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<ChangedMessage>,
+        /// 削除されたメッセージの ts（`message_deleted` のみ）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        deleted_ts: Option<String>,
     },
     #[serde(rename = "app_mention")]
     AppMention {
@@ -32,9 +69,46 @@ pub enum SlackEvent {
         user: String,
         text: String,
         ts: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        thread_ts: Option<String>,
+    },
+    #[serde(rename = "reaction_added")]
+    ReactionAdded {
+        user: String,
+        reaction: String,
+        item: ReactionItem,
+        /// リアクションされたメッセージの投稿者
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        item_user: Option<String>,
+    },
+    #[serde(rename = "reaction_removed")]
+    ReactionRemoved {
+        user: String,
+        reaction: String,
+        item: ReactionItem,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        item_user: Option<String>,
     },
 }
 
+/// リアクションされたアイテム（`reaction_added` / `reaction_removed` の `item`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReactionItem {
+    /// `message` / `file` など
+    #[serde(rename = "type")]
+    pub item_type: String,
+    #[serde(default)]
+    pub channel: String,
+    #[serde(default)]
+    pub ts: String,
+}
+
+/// 編集されたメッセージ（`message_changed` イベントの `message`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedMessage {
+    pub ts: String,
+}
+
 /// Slackコマンドのドメインモデル
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackCommand {
@@ -46,6 +120,39 @@ pub struct SlackCommand {
     pub trigger_id: String,
 }
 
+/// Slackコマンドの応答種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlackResponseType {
+    /// チャンネル全体に表示
+    InChannel,
+    /// 実行したユーザーにだけ表示
+    Ephemeral,
+}
+
+/// Slackコマンドの応答
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackCommandResponse {
+    pub response_type: SlackResponseType,
+    pub text: String,
+}
+
+impl SlackCommandResponse {
+    pub fn in_channel(text: impl Into<String>) -> Self {
+        Self {
+            response_type: SlackResponseType::InChannel,
+            text: text.into(),
+        }
+    }
+
+    pub fn ephemeral(text: impl Into<String>) -> Self {
+        Self {
+            response_type: SlackResponseType::Ephemeral,
+            text: text.into(),
+        }
+    }
+}
+
 /// Slackインタラクションのドメインモデル
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackInteraction {
@@ -54,6 +161,252 @@ pub struct SlackInteraction {
     pub channel_id: String,
     pub action_id: String,
     pub value: Option<String>,
+    pub response_url: Option<String>,
+    /// モーダルを開くための trigger_id（ボタン操作・ショートカットのみ）
+    #[serde(default)]
+    pub trigger_id: Option<String>,
+    /// 送信されたモーダル（view_submission のみ）
+    #[serde(default)]
+    pub view: Option<SlackViewSubmission>,
+}
+
+/// 送信されたモーダルの内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlackViewSubmission {
+    pub private_metadata: String,
+    /// 入力欄の action_id → 入力値（選択肢の場合は選ばれた value）
+    pub values: BTreeMap<String, String>,
+}
+
+/// 「チャンネルに共有」ボタンに埋め込む回答
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedAnswer {
+    #[serde(rename = "q")]
+    pub question: String,
+    #[serde(rename = "a")]
+    pub answer: String,
+    /// 出典欄のテキスト
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<String>,
+    /// 質問応答ログのID（共有した回答にもフィードバックのボタンを付ける）
+    #[serde(rename = "i", default, skip_serializing_if = "Option::is_none")]
+    pub interaction_id: Option<Uuid>,
+}
+
+impl SharedAnswer {
+    /// ボタンの value に設定できる最大長
+    const MAX_VALUE_LEN: usize = 2000;
+    /// 埋め込む質問の最大長
+    const MAX_QUESTION_LEN: usize = 200;
+
+    /// ボタンの value 用にシリアライズする
+    ///
+    /// value は2000文字までのため、超える場合は出典を省き、それでも超える場合は回答を切り詰めます。
+    pub fn to_button_value(&self) -> String {
+        let mut shared = self.clone();
+        if shared.question.chars().count() > Self::MAX_QUESTION_LEN {
+            shared.question = shared
+                .question
+                .chars()
+                .take(Self::MAX_QUESTION_LEN)
+                .collect::<String>()
+                + "…";
+        }
+
+        loop {
+            let value = serde_json::to_string(&shared).unwrap_or_default();
+            let len = value.chars().count();
+            if len <= Self::MAX_VALUE_LEN || shared.answer.is_empty() {
+                return value;
+            }
+            if shared.sources.take().is_some() {
+                continue;
+            }
+
+            let keep = shared
+                .answer
+                .chars()
+                .count()
+                .saturating_sub(len - Self::MAX_VALUE_LEN + 1);
+            shared.answer = if keep == 0 {
+                String::new()
+            } else {
+                shared.answer.chars().take(keep).collect::<String>() + "…"
+            };
+        }
+    }
+}
+
+/// 質問の経路
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InteractionSource {
+    /// /ask コマンド
+    Ask,
+    /// メンション
+    Mention,
+    /// チャンネルのメッセージへの自発的な回答
+    Message,
+}
+
+impl InteractionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InteractionSource::Ask => "ask",
+            InteractionSource::Mention => "mention",
+            InteractionSource::Message => "message",
+        }
+    }
+}
+
+/// 回答のコンテキストに含めたメッセージ（出典の候補）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextSource {
+    /// コンテキスト内の番号（`[n]`）
+    pub index: usize,
+    pub channel_id: String,
+    pub channel_name: Option<String>,
+    /// 投稿者（表示名が取れない場合はユーザーID）
+    pub author: Option<String>,
+    pub ts: String,
+    pub permalink: Option<String>,
+}
+
+impl ContextSource {
+    /// 出典一覧の1行（チャンネル・投稿者・日付）
+    ///
+    /// 日付は閲覧者のタイムゾーンで表示されるよう Slack の日付フォーマットを使います。
+    pub fn to_mrkdwn(&self) -> String {
+        let channel = match &self.channel_name {
+            Some(name) => format!("#{}", name),
+            None => format!("<#{}>", self.channel_id),
+        };
+        let channel = match &self.permalink {
+            Some(link) => format!("<{}|{}>", link, channel),
+            None => channel,
+        };
+        let author = self.author.as_deref().unwrap_or("unknown");
+        let date = self
+            .ts
+            .split('.')
+            .next()
+            .and_then(|secs| secs.parse::<i64>().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|dt| {
+                format!(
+                    "<!date^{}^{{date_short}}|{}>",
+                    dt.timestamp(),
+                    dt.format("%Y-%m-%d")
+                )
+            })
+            .unwrap_or_else(|| self.ts.clone());
+
+        format!("{} · {} · {}", channel, author, date)
+    }
+}
+
+/// 検索結果を LLM 向けに整形したコンテキスト
+#[derive(Debug, Clone, Default)]
+pub struct RetrievedContext {
+    /// `[n]` の番号付きで整形したテキスト
+    pub text: String,
+    /// 番号順のメッセージ一覧（`sources[n - 1]` が `[n]`）
+    pub sources: Vec<ContextSource>,
+    /// 検索結果の本文から求めた指紋（回答キャッシュのキーに使う）
+    pub fingerprint: String,
+}
+
+/// 出典付きの回答
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Answer {
+    pub text: String,
+    /// 回答の根拠になったメッセージ
+    pub sources: Vec<ContextSource>,
+    /// LLM が自己評価した確信度（0.0〜1.0、不明な場合は None）
+    pub confidence: Option<f64>,
+    /// 確信度に応じた扱い
+    pub decision: AnswerDecision,
+    /// 出典に裏付けられた主張の割合（検証を行った場合のみ）
+    pub faithfulness: Option<f64>,
+    /// 出典で裏付けられなかった主張（検証で注記する場合のみ）
+    pub unsupported_claims: Vec<String>,
+    /// ツール呼び出しの記録（エージェントモードのみ）
+    pub tool_trace: Vec<ToolCallRecord>,
+    /// 回答に使ったプロンプトのバージョン
+    pub prompt_versions: BTreeMap<PromptKind, String>,
+    /// 回答を生成したモデル
+    pub model: Option<String>,
+    /// 回答キャッシュから返した回答かどうか
+    #[serde(skip)]
+    pub cached: bool,
+}
+
+impl Answer {
+    /// 情報が足りず回答しない場合の応答（質問の言語で返す）
+    pub fn abstain(language: Language) -> Self {
+        let text = match language {
+            Language::Japanese => "関連する情報が見つからなかったため、回答できませんでした。",
+            Language::English => "I couldn't find enough related information to answer this.",
+        };
+        Self {
+            text: text.to_string(),
+            decision: AnswerDecision::Abstain,
+            ..Default::default()
+        }
+    }
+
+    /// 回答しない判定かどうか
+    pub fn is_abstained(&self) -> bool {
+        self.decision == AnswerDecision::Abstain
+    }
+
+    /// 表示する出典の最大件数
+    const MAX_SOURCES: usize = 10;
+
+    /// 「出典」欄のテキスト（出典がない場合は None）
+    pub fn sources_mrkdwn(&self) -> Option<String> {
+        if self.sources.is_empty() {
+            return None;
+        }
+
+        let lines: Vec<String> = self
+            .sources
+            .iter()
+            .take(Self::MAX_SOURCES)
+            .map(|source| format!("• {}", source.to_mrkdwn()))
+            .collect();
+        Some(format!("*出典*\n{}", lines.join("\n")))
+    }
+
+    /// 回答本文と出典の Block Kit ブロック
+    pub fn blocks(&self) -> Vec<Block> {
+        let mut blocks = vec![Block::section(self.text.clone())];
+        if self.decision == AnswerDecision::PostWithDisclaimer {
+            let confidence = self
+                .confidence
+                .map(|c| format!("（確信度 {:.0}%）", c * 100.0))
+                .unwrap_or_default();
+            blocks.push(Block::context(vec![format!(
+                "⚠️ 確信度の低い回答です{}。出典を確認してください。",
+                confidence
+            )]));
+        }
+        if !self.unsupported_claims.is_empty() {
+            let claims: Vec<String> = self
+                .unsupported_claims
+                .iter()
+                .map(|claim| format!("• {}", claim))
+                .collect();
+            blocks.push(Block::context(vec![format!(
+                "⚠️ 出典で確認できなかった記述\n{}",
+                claims.join("\n")
+            )]));
+        }
+        if let Some(sources) = self.sources_mrkdwn() {
+            blocks.push(Block::context(vec![sources]));
+        }
+        blocks
+    }
 }
 
 /// Slackチャンネル履歴メッセージ
@@ -1,4 +1,4 @@
-use super::{SlackError, SlackHistoryMessage, SlackMessage};
+use super::{ConversationKind, SlackError, SlackHistoryMessage, SlackMessage};
 use async_trait::async_trait;
 
 /// Slackメッセージ送信のためのリポジトリインターフェース
@@ -14,3 +14,56 @@ pub trait SlackMessageRepository: Send + Sync {
         limit: Option<i32>,
     ) -> Result<Vec<SlackHistoryMessage>, SlackError>;
 }
+
+/// 質問応答の検索に使うメッセージ取得のリポジトリインターフェース
+///
+/// 本番では Slack API（ユーザートークン）、評価では記録したワークスペース
+/// （[`WorkspaceFixture`](crate::WorkspaceFixture)）を使います。
+#[async_trait]
+pub trait MessageSearchRepository: Send + Sync {
+    /// search.messages 相当の検索（`sort` は `score`（関連度順）または `timestamp`（新しい順））
+    async fn search_messages(
+        &self,
+        query: &str,
+        count: u32,
+        sort: &str,
+    ) -> Result<Vec<crate::slack_api::SlackMessage>, SlackError>;
+
+    /// メッセージの前後のメッセージ
+    async fn messages_around(
+        &self,
+        channel_id: &str,
+        ts: &str,
+    ) -> Result<crate::slack_api::MessagesAround, SlackError>;
+
+    /// 各メッセージのスレッド
+    async fn threads(
+        &self,
+        channel_id: &str,
+        messages: &[crate::slack_api::SlackHistoryMessage],
+    ) -> Result<Vec<crate::slack_api::ThreadInfo>, SlackError>;
+
+    /// メッセージのパーマリンク
+    async fn permalink(&self, channel_id: &str, ts: &str) -> Result<String, SlackError>;
+}
+
+/// 会話の種類とメンバーを取得するリポジトリインターフェース
+///
+/// 検索結果を質問したユーザーが閲覧できるかの判定に使います。
+#[async_trait]
+pub trait ChannelAccessRepository: Send + Sync {
+    /// 会話の種類
+    async fn conversation_kind(&self, channel_id: &str) -> Result<ConversationKind, SlackError>;
+
+    /// ユーザーが参加している会話のID
+    async fn user_conversations(&self, user_id: &str) -> Result<Vec<String>, SlackError>;
+}
+
+/// ユーザーとボットの DM を取得するリポジトリインターフェース
+///
+/// 非公開の内容を DM に回答してよいか（相手がボットだけか）の判定に使います。
+#[async_trait]
+pub trait BotDirectMessageRepository: Send + Sync {
+    /// ユーザーとボットの DM の会話ID
+    async fn bot_direct_message(&self, user_id: &str) -> Result<String, SlackError>;
+}
@@ -0,0 +1,308 @@
+//! 検索結果の閲覧権限のチェック
+//!
+//! 検索はユーザートークンで行うため、質問したユーザーが参加していないプライベートチャンネルや
+//! DM のメッセージも見つかります。回答に使う前に、次の条件を満たさないメッセージを除きます。
+//!
+//! - 公開チャンネルのメッセージは常に使える
+//! - プライベートチャンネル・DM・グループDM のメッセージは、質問したユーザーが参加しており、
+//!   かつ回答を同じ会話か、質問したユーザー自身とボットの DM（質問したユーザーしか見られない）に
+//!   投稿する場合のみ使える（他の人との DM は含まない）
+//!
+//! 質問したユーザーと回答先は [`Audience::scope`] で設定します。設定されていない場合は
+//! 公開チャンネルのメッセージだけを使います。
+
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::slack_api::SlackMessage;
+use crate::{BotDirectMessageRepository, ChannelAccessRepository, ConversationKind, SlackError};
+
+/// 会話の種類・ユーザーの参加状況をキャッシュする期間
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// 取得した時刻付きのキャッシュ
+type Cache<T> = Mutex<HashMap<String, (Instant, T)>>;
+
+tokio::task_local! {
+    static AUDIENCE: Audience;
+}
+
+/// 回答を受け取る相手
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audience {
+    /// 質問したユーザー
+    pub user_id: String,
+    /// 回答を投稿するチャンネル
+    pub channel_id: String,
+}
+
+impl Audience {
+    pub fn new(user_id: impl Into<String>, channel_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            channel_id: channel_id.into(),
+        }
+    }
+
+    /// `future` の中で行われる検索を、この相手が閲覧できるメッセージに限定する
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        AUDIENCE.scope(self, future).await
+    }
+
+    /// 現在の相手（[`Self::scope`] の外では None）
+    pub fn current() -> Option<Self> {
+        AUDIENCE.try_with(Clone::clone).ok()
+    }
+}
+
+/// 検索結果の閲覧権限をチェックするドメインサービス
+pub struct ChannelAccessService {
+    repository: Arc<dyn ChannelAccessRepository>,
+    direct_messages: Arc<dyn BotDirectMessageRepository>,
+    kinds: Cache<ConversationKind>,
+    /// ユーザーごとの参加している会話
+    memberships: Cache<Arc<HashSet<String>>>,
+    /// ユーザーごとのボットとの DM
+    bot_dms: Cache<String>,
+}
+
+impl ChannelAccessService {
+    pub fn new(
+        repository: Arc<dyn ChannelAccessRepository>,
+        direct_messages: Arc<dyn BotDirectMessageRepository>,
+    ) -> Self {
+        Self {
+            repository,
+            direct_messages,
+            kinds: Mutex::new(HashMap::new()),
+            memberships: Mutex::new(HashMap::new()),
+            bot_dms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn bot_dm(&self, user_id: &str) -> Result<String, SlackError> {
+        if let Some((fetched, channel_id)) = self.bot_dms.lock().unwrap().get(user_id) {
+            if fetched.elapsed() < CACHE_TTL {
+                return Ok(channel_id.clone());
+            }
+        }
+
+        let channel_id = self.direct_messages.bot_direct_message(user_id).await?;
+        self.bot_dms
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), (Instant::now(), channel_id.clone()));
+        Ok(channel_id)
+    }
+
+    async fn kind(&self, channel_id: &str) -> Result<ConversationKind, SlackError> {
+        if let Some((fetched, kind)) = self.kinds.lock().unwrap().get(channel_id) {
+            if fetched.elapsed() < CACHE_TTL {
+                return Ok(*kind);
+            }
+        }
+
+        let kind = self.repository.conversation_kind(channel_id).await?;
+        self.kinds
+            .lock()
+            .unwrap()
+            .insert(channel_id.to_string(), (Instant::now(), kind));
+        Ok(kind)
+    }
+
+    async fn conversations(&self, user_id: &str) -> Result<Arc<HashSet<String>>, SlackError> {
+        if let Some((fetched, channels)) = self.memberships.lock().unwrap().get(user_id) {
+            if fetched.elapsed() < CACHE_TTL {
+                return Ok(channels.clone());
+            }
+        }
+
+        let channels: Arc<HashSet<String>> = Arc::new(
+            self.repository
+                .user_conversations(user_id)
+                .await?
+                .into_iter()
+                .collect(),
+        );
+        self.memberships
+            .lock()
+            .unwrap()
+            .insert(user_id.to_string(), (Instant::now(), channels.clone()));
+        Ok(channels)
+    }
+
+    async fn check(
+        &self,
+        audience: Option<&Audience>,
+        channel_id: &str,
+    ) -> Result<bool, SlackError> {
+        if self.kind(channel_id).await?.is_public() {
+            return Ok(true);
+        }
+        let Some(audience) = audience else {
+            return Ok(false);
+        };
+        if !self
+            .conversations(&audience.user_id)
+            .await?
+            .contains(channel_id)
+        {
+            return Ok(false);
+        }
+
+        // 非公開の内容は同じ会話か、質問したユーザー自身とボットの DM にだけ投稿する
+        // （他の人との DM で /ask して「チャンネルに共有」すると相手にも見えるため）
+        if audience.channel_id == channel_id {
+            return Ok(true);
+        }
+        Ok(
+            self.kind(&audience.channel_id).await? == ConversationKind::DirectMessage
+                && self.bot_dm(&audience.user_id).await? == audience.channel_id,
+        )
+    }
+
+    /// 現在の相手（[`Audience::current`]）がチャンネルのメッセージを回答に使えるか
+    ///
+    /// 判定に失敗した場合は使えないものとして扱います。
+    pub async fn is_allowed(&self, channel_id: &str) -> bool {
+        let audience = Audience::current();
+        self.check(audience.as_ref(), channel_id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to check access to {}: {}", channel_id, e);
+                false
+            })
+    }
+
+    /// 回答に使えないチャンネルのメッセージを除く
+    pub async fn filter_messages(&self, messages: Vec<SlackMessage>) -> Vec<SlackMessage> {
+        let channel_of = |msg: &SlackMessage| {
+            msg.channel
+                .as_ref()
+                .and_then(|channel| channel.id.clone())
+                .unwrap_or_default()
+        };
+
+        let mut allowed = HashMap::new();
+        for msg in &messages {
+            let channel_id = channel_of(msg);
+            if let Entry::Vacant(entry) = allowed.entry(channel_id) {
+                let is_allowed = !entry.key().is_empty() && self.is_allowed(entry.key()).await;
+                entry.insert(is_allowed);
+            }
+        }
+
+        let total = messages.len();
+        let messages: Vec<_> = messages
+            .into_iter()
+            .filter(|msg| allowed[&channel_of(msg)])
+            .collect();
+        if messages.len() < total {
+            tracing::info!(
+                "Dropped {} search results from conversations the asker cannot see here",
+                total - messages.len()
+            );
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slack_api::ChannelInfo;
+    use async_trait::async_trait;
+
+    struct FakeRepository;
+
+    #[async_trait]
+    impl ChannelAccessRepository for FakeRepository {
+        async fn conversation_kind(
+            &self,
+            channel_id: &str,
+        ) -> Result<ConversationKind, SlackError> {
+            Ok(match channel_id {
+                "CPUB" | "CPUB2" => ConversationKind::Public,
+                "GPRIV" | "GOTHER" => ConversationKind::Private,
+                "DBOT" | "DOTHER" => ConversationKind::DirectMessage,
+                _ => return Err(SlackError::ApiError("channel_not_found".to_string())),
+            })
+        }
+
+        async fn user_conversations(&self, user_id: &str) -> Result<Vec<String>, SlackError> {
+            assert_eq!(user_id, "U1");
+            Ok(vec!["GPRIV".to_string(), "DBOT".to_string()])
+        }
+    }
+
+    #[async_trait]
+    impl BotDirectMessageRepository for FakeRepository {
+        async fn bot_direct_message(&self, user_id: &str) -> Result<String, SlackError> {
+            assert_eq!(user_id, "U1");
+            Ok("DBOT".to_string())
+        }
+    }
+
+    fn message(channel: &str) -> SlackMessage {
+        SlackMessage {
+            msg_type: "message".to_string(),
+            user: None,
+            bot_id: None,
+            text: String::new(),
+            ts: "1.0".to_string(),
+            channel: Some(ChannelInfo {
+                id: Some(channel.to_string()),
+                name: None,
+            }),
+            username: None,
+            permalink: None,
+        }
+    }
+
+    async fn allowed_channels(service: &ChannelAccessService) -> Vec<String> {
+        let messages = ["CPUB", "GPRIV", "GOTHER", "DOTHER", "CUNKNOWN"]
+            .into_iter()
+            .map(message)
+            .collect();
+        service
+            .filter_messages(messages)
+            .await
+            .into_iter()
+            .filter_map(|msg| msg.channel.and_then(|c| c.id))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_filter_messages() {
+        let service = ChannelAccessService::new(Arc::new(FakeRepository), Arc::new(FakeRepository));
+
+        // 相手が不明なら公開チャンネルのみ
+        assert_eq!(allowed_channels(&service).await, vec!["CPUB"]);
+
+        // 公開チャンネルへの回答には非公開の内容を含めない
+        let public = Audience::new("U1", "CPUB2")
+            .scope(allowed_channels(&service))
+            .await;
+        assert_eq!(public, vec!["CPUB"]);
+
+        // 同じプライベートチャンネルやボットとの DM なら、参加している会話の内容を使える
+        let same = Audience::new("U1", "GPRIV")
+            .scope(allowed_channels(&service))
+            .await;
+        assert_eq!(same, vec!["CPUB", "GPRIV"]);
+        let dm = Audience::new("U1", "DBOT")
+            .scope(allowed_channels(&service))
+            .await;
+        assert_eq!(dm, vec!["CPUB", "GPRIV"]);
+
+        // 他の人との DM への回答（共有ボタンで相手にも見える）には非公開の内容を含めない
+        let other_dm = Audience::new("U1", "DOTHER")
+            .scope(allowed_channels(&service))
+            .await;
+        assert_eq!(other_dm, vec!["CPUB"]);
+    }
+}
@@ -1,17 +1,130 @@
-use crate::domain::{SlackCommand, SlackError};
+use std::sync::Arc;
+
+use crate::domain::{
+    Answer, AnswerService, Audience, DigestService, FeedbackService, InteractionSource, SharedAnswer, SlackCommand, SlackCommandResponse, SlackError,
+    SummarizeArgs, SummaryService, SHARE_ANSWER_ACTION_ID,
+};
+use crate::slack_api::{Block, BlockElement, ResponseUrlMessage, SlackApi};
+use nokizaru_core::{
+    llm::UsageTags, DailyUsage, Prefilter, PrefilterStats, StageUsage, UsageFilter, UsageRepository,
+};
+use uuid::Uuid;
+
+/// /nokizaru usage の既定の集計日数
+const DEFAULT_USAGE_DAYS: u32 = 7;
+/// /nokizaru usage で指定できる最大の集計日数
+const MAX_USAGE_DAYS: u32 = 90;
 
 /// Slackコマンド処理のドメインサービス
-pub struct SlackCommandService {}
+pub struct SlackCommandService {
+    answer_service: Arc<AnswerService>,
+    slack_api: Arc<SlackApi>,
+    usage: Option<(Arc<UsageRepository>, Option<Uuid>)>,
+    prefilter: Option<Arc<Prefilter>>,
+    summaries: Option<Arc<SummaryService>>,
+    digests: Option<Arc<DigestService>>,
+    feedback: Option<Arc<FeedbackService>>,
+}
+
+/// /ask コマンドの引数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AskArgs {
+    pub question: String,
+    /// Slack 検索の修飾子（例: `in:#general`）
+    pub scope: Option<String>,
+}
+
+impl AskArgs {
+    /// `/ask <質問> [in:#channel]` のテキスト部分を解析する
+    ///
+    /// チャンネルはエスケープされた `<#C0123|general>` 形式にも対応します。
+    pub fn parse(text: &str) -> Self {
+        let mut scope = None;
+        let mut words = Vec::new();
+
+        for word in text.split_whitespace() {
+            match word.strip_prefix("in:") {
+                Some(channel) if scope.is_none() && !channel.is_empty() => {
+                    scope = Some(Self::channel_scope(channel));
+                }
+                _ => words.push(word),
+            }
+        }
+
+        Self {
+            question: words.join(" "),
+            scope,
+        }
+    }
+
+    fn channel_scope(channel: &str) -> String {
+        match channel.strip_prefix("<#").and_then(|c| c.strip_suffix('>')) {
+            // <#C0123|general> → in:#general, <#C0123> → in:<#C0123>
+            Some(escaped) => match escaped.split_once('|') {
+                Some((_, name)) if !name.is_empty() => format!("in:#{}", name),
+                Some((id, _)) => format!("in:<#{}>", id),
+                None => format!("in:<#{}>", escaped),
+            },
+            None => format!("in:#{}", channel.trim_start_matches('#')),
+        }
+    }
+}
 
 impl SlackCommandService {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(answer_service: Arc<AnswerService>, slack_api: Arc<SlackApi>) -> Self {
+        Self {
+            answer_service,
+            slack_api,
+            usage: None,
+            prefilter: None,
+            summaries: None,
+            digests: None,
+            feedback: None,
+        }
+    }
+
+    /// /nokizaru usage で集計する LLM 利用量の記録先を指定する
+    pub fn with_usage(mut self, repository: Arc<UsageRepository>, space_id: Option<Uuid>) -> Self {
+        self.usage = Some((repository, space_id));
+        self
+    }
+
+    /// /nokizaru prefilter で判定の件数を表示する絞り込みを指定する
+    pub fn with_prefilter(mut self, prefilter: Arc<Prefilter>) -> Self {
+        self.prefilter = Some(prefilter);
+        self
+    }
+
+    /// /nokizaru summarize で使う要約サービスを指定する
+    pub fn with_summaries(mut self, summaries: Arc<SummaryService>) -> Self {
+        self.summaries = Some(summaries);
+        self
+    }
+
+    /// /nokizaru digest で登録するダイジェストの配信サービスを指定する
+    pub fn with_digests(mut self, digests: Arc<DigestService>) -> Self {
+        self.digests = Some(digests);
+        self
     }
 
-    pub async fn execute_command(&self, command: SlackCommand) -> Result<String, SlackError> {
+    /// /ask の回答に 👍/👎 ボタンを付け、/nokizaru feedback で集計するフィードバックサービスを指定する
+    pub fn with_feedback(mut self, feedback: Arc<FeedbackService>) -> Self {
+        self.feedback = Some(feedback);
+        self
+    }
+
+    pub async fn execute_command(
+        &self,
+        command: SlackCommand,
+    ) -> Result<SlackCommandResponse, SlackError> {
         match command.command.as_str() {
-            "/hello" => Ok(format!("こんにちは、<@{}>さん！", command.user_id)),
-            "/help" => Ok(self.get_help_text()),
+            "/hello" => Ok(SlackCommandResponse::in_channel(format!(
+                "こんにちは、<@{}>さん！",
+                command.user_id
+            ))),
+            "/help" => Ok(SlackCommandResponse::in_channel(self.get_help_text())),
+            "/ask" => Ok(self.ask(command)),
+            "/nokizaru" => Ok(self.nokizaru(command).await),
             _ => Err(SlackError::CommandExecutionFailed(format!(
                 "Unknown command: {}",
                 command.command
@@ -19,12 +132,351 @@ impl SlackCommandService {
         }
     }
 
+    /// /ask コマンド
+    ///
+    /// Slackの3秒タイムアウトに収まらないため、受付メッセージだけを即座に返し、
+    /// 回答はバックグラウンドで生成して response_url に送信します。
+    fn ask(&self, command: SlackCommand) -> SlackCommandResponse {
+        let args = AskArgs::parse(&command.text);
+        if args.question.is_empty() {
+            return SlackCommandResponse::ephemeral("使い方: /ask <質問> [in:#channel]");
+        }
+
+        let acknowledgement = format!("🔍 「{}」について調べています…", args.question);
+
+        let answer_service = Arc::clone(&self.answer_service);
+        let slack_api = Arc::clone(&self.slack_api);
+        let feedback_enabled = self.feedback.is_some();
+        let tags = UsageTags::new(&command.channel_id, Some(command.user_id.clone()));
+        // 回答は「チャンネルに共有」で投稿される可能性があるため、実行したチャンネルを回答先とみなす
+        let audience = Audience::new(&command.user_id, &command.channel_id);
+        tokio::spawn(tags.scope(audience.scope(async move {
+            let language = answer_service.language_for(
+                &command.user_id,
+                &command.channel_id,
+                &args.question,
+            );
+            let message = match answer_service
+                .answer(&args.question, args.scope.as_deref(), language)
+                .await
+            {
+                Ok(answer) => {
+                    let interaction_id = answer_service
+                        .record_interaction(
+                            InteractionSource::Ask,
+                            &command.channel_id,
+                            &command.user_id,
+                            &args.question,
+                            &answer,
+                        )
+                        .await;
+                    let feedback_id = interaction_id.filter(|_| feedback_enabled);
+                    Self::ask_answer_message(&args.question, answer, feedback_id)
+                }
+                Err(e) => {
+                    tracing::error!("❌ /ask failed: {}", e);
+                    ResponseUrlMessage::ephemeral("❌ 回答の生成に失敗しました", None)
+                }
+            };
+
+            let message = ResponseUrlMessage {
+                replace_original: true,
+                ..message
+            };
+            if let Err(e) = slack_api.respond(&command.response_url, &message).await {
+                tracing::error!("Failed to respond to /ask: {}", e);
+            }
+        })));
+
+        SlackCommandResponse::ephemeral(acknowledgement)
+    }
+
+    /// /nokizaru コマンド（管理用のサブコマンド）
+    async fn nokizaru(&self, command: SlackCommand) -> SlackCommandResponse {
+        let mut args = command.text.split_whitespace();
+        match args.next() {
+            Some("usage") => match Self::parse_days(args.next()) {
+                Ok(days) => self.usage(days).await,
+                Err(response) => response,
+            },
+            Some("feedback") => match (&self.feedback, Self::parse_days(args.next())) {
+                (Some(feedback), Ok(days)) => {
+                    SlackCommandResponse::ephemeral(feedback.report(days).await)
+                }
+                (Some(_), Err(response)) => response,
+                (None, _) => {
+                    SlackCommandResponse::ephemeral("回答へのフィードバックが有効になっていません")
+                }
+            },
+            Some("prefilter") => match &self.prefilter {
+                Some(prefilter) => {
+                    SlackCommandResponse::ephemeral(Self::prefilter_report(&prefilter.stats()))
+                }
+                None => SlackCommandResponse::ephemeral("絞り込みが設定されていません"),
+            },
+            Some("summarize") => {
+                let rest = command
+                    .text
+                    .trim_start()
+                    .strip_prefix("summarize")
+                    .unwrap_or_default()
+                    .to_string();
+                self.summarize(command, &rest)
+            }
+            Some("digest") => match &self.digests {
+                Some(digests) => {
+                    let rest = command
+                        .text
+                        .trim_start()
+                        .strip_prefix("digest")
+                        .unwrap_or_default();
+                    SlackCommandResponse::ephemeral(
+                        digests
+                            .command(&command.user_id, &command.channel_id, rest)
+                            .await,
+                    )
+                }
+                None => SlackCommandResponse::ephemeral("ダイジェストが有効になっていません"),
+            },
+            _ => SlackCommandResponse::ephemeral(
+                "使い方: /nokizaru usage [日数] | /nokizaru feedback [日数] | /nokizaru prefilter | /nokizaru summarize [#channel] [期間] | /nokizaru digest add|list|remove",
+            ),
+        }
+    }
+
+    /// 集計日数の引数（省略時は既定の日数）
+    fn parse_days(arg: Option<&str>) -> Result<u32, SlackCommandResponse> {
+        match arg.map(str::parse::<u32>) {
+            None => Ok(DEFAULT_USAGE_DAYS),
+            Some(Ok(days)) if (1..=MAX_USAGE_DAYS).contains(&days) => Ok(days),
+            Some(_) => Err(SlackCommandResponse::ephemeral(format!(
+                "日数は 1〜{} で指定してください",
+                MAX_USAGE_DAYS
+            ))),
+        }
+    }
+
+    /// /nokizaru summarize: チャンネルの最近のやり取りを要約してチャンネルに投稿する
+    ///
+    /// /ask と同じく受付メッセージだけを即座に返し、要約は response_url に送信します。
+    fn summarize(&self, command: SlackCommand, args: &str) -> SlackCommandResponse {
+        let Some(summaries) = self.summaries.clone() else {
+            return SlackCommandResponse::ephemeral("要約が有効になっていません");
+        };
+        let args = match SummarizeArgs::parse(args, chrono::Local::now()) {
+            Ok(args) => args,
+            Err(message) => return SlackCommandResponse::ephemeral(message),
+        };
+        let channel_id = args
+            .channel_id
+            .clone()
+            .unwrap_or_else(|| command.channel_id.clone());
+        let acknowledgement = format!("📝 <#{}> の{}を要約しています…", channel_id, args.label);
+
+        let slack_api = Arc::clone(&self.slack_api);
+        let tags = UsageTags::new(&command.channel_id, Some(command.user_id.clone()));
+        // 要約は実行したチャンネルに投稿するため、そこを回答先とみなす
+        let audience = Audience::new(&command.user_id, &command.channel_id);
+        tokio::spawn(tags.scope(audience.scope(async move {
+            let message = match summaries
+                .summarize_channel(
+                    &command.user_id,
+                    &channel_id,
+                    args.oldest.to_utc(),
+                    &args.label,
+                )
+                .await
+            {
+                Ok(text) => ResponseUrlMessage::in_channel(text, None),
+                Err(e) => {
+                    tracing::error!("❌ /nokizaru summarize failed: {}", e);
+                    ResponseUrlMessage::ephemeral("❌ 要約の作成に失敗しました", None)
+                }
+            };
+            if let Err(e) = slack_api.respond(&command.response_url, &message).await {
+                tracing::error!("Failed to respond to /nokizaru summarize: {}", e);
+            }
+        })));
+
+        SlackCommandResponse::ephemeral(acknowledgement)
+    }
+
+    /// /nokizaru usage: 直近の LLM 利用量とコストを日ごとに集計する
+    async fn usage(&self, days: u32) -> SlackCommandResponse {
+        let Some((repository, space_id)) = &self.usage else {
+            return SlackCommandResponse::ephemeral("利用量の記録が有効になっていません");
+        };
+
+        let filter = UsageFilter::last_days(*space_id, days);
+        let result = tokio::try_join!(repository.daily(&filter), repository.by_stage(&filter));
+        match result {
+            Ok((daily, stages)) => {
+                SlackCommandResponse::ephemeral(Self::usage_report(days, &daily, &stages))
+            }
+            Err(e) => {
+                tracing::error!("❌ Failed to summarize usage: {}", e);
+                SlackCommandResponse::ephemeral("❌ 利用量の集計に失敗しました")
+            }
+        }
+    }
+
+    /// 利用量のレポート
+    fn usage_report(days: u32, daily: &[DailyUsage], stages: &[StageUsage]) -> String {
+        if daily.is_empty() {
+            return format!("直近{}日の LLM 呼び出しはありません", days);
+        }
+
+        let mut lines = vec![format!("*LLM 利用状況（直近{}日、UTC）*", days)];
+        for day in daily {
+            lines.push(format!(
+                "• {}: {}回 / 入力 {}・出力 {} トークン / ${:.4}",
+                day.day, day.calls, day.input_tokens, day.output_tokens, day.cost_usd
+            ));
+        }
+        let calls: i64 = daily.iter().map(|day| day.calls).sum();
+        let cost: f64 = daily.iter().map(|day| day.cost_usd).sum();
+        lines.push(format!("*合計*: {}回 / ${:.4}", calls, cost));
+
+        if !stages.is_empty() {
+            lines.push("*ステージ別*".to_string());
+            for stage in stages {
+                let failed = if stage.failed_calls > 0 {
+                    format!("（失敗 {}回）", stage.failed_calls)
+                } else {
+                    String::new()
+                };
+                lines.push(format!(
+                    "• {} ({}): {}回{} / ${:.4} / 平均 {:.0}ms",
+                    stage.stage,
+                    stage.model,
+                    stage.calls,
+                    failed,
+                    stage.cost_usd,
+                    stage.avg_latency_ms
+                ));
+            }
+        }
+
+        let unpriced: i64 = daily.iter().map(|day| day.unpriced_calls).sum();
+        if unpriced > 0 {
+            lines.push(format!(
+                "⚠️ 料金表にないモデルの呼び出し {}回はコストに含まれていません",
+                unpriced
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /// 絞り込みの判定件数のレポート
+    fn prefilter_report(stats: &PrefilterStats) -> String {
+        let total = stats.total();
+        if total == 0 {
+            return "起動してから判定したメッセージはありません".to_string();
+        }
+
+        let mut lines = vec![
+            "*意図の分類前の絞り込み（起動してから）*".to_string(),
+            format!(
+                "• 判定 {}件 / 分類 {}件 / 分類せずに回答 {}件",
+                total, stats.classified, stats.answered
+            ),
+        ];
+        for (reason, count) in &stats.skipped {
+            lines.push(format!("• 無視（{}）: {}件", reason, count));
+        }
+        lines.push(format!(
+            "*省けた分類の呼び出し*: {}回（{:.0}%）",
+            stats.saved_calls(),
+            stats.saved_calls() as f64 / total as f64 * 100.0
+        ));
+        lines.join("\n")
+    }
+
+    /// 「チャンネルに共有」ボタン付きの回答メッセージ
+    ///
+    /// `feedback_id`（質問応答ログのID）を指定した場合は 👍/👎 ボタンも付けます。
+    fn ask_answer_message(
+        question: &str,
+        answer: Answer,
+        feedback_id: Option<Uuid>,
+    ) -> ResponseUrlMessage {
+        let shared = SharedAnswer {
+            question: question.to_string(),
+            answer: answer.text.clone(),
+            sources: answer.sources_mrkdwn(),
+            interaction_id: feedback_id,
+        };
+        let mut blocks = vec![Block::context(vec![format!("質問: {}", question)])];
+        blocks.extend(answer.blocks());
+        // 回答できなかった場合は共有しても意味がないのでボタンを出さない
+        if !answer.is_abstained() {
+            blocks.push(Block::actions(vec![BlockElement::primary_button(
+                "チャンネルに共有",
+                SHARE_ANSWER_ACTION_ID,
+                Some(shared.to_button_value()),
+            )]));
+            if let Some(feedback_id) = feedback_id {
+                blocks.push(FeedbackService::actions_block(feedback_id));
+            }
+        }
+
+        ResponseUrlMessage::ephemeral(answer.text, Some(blocks))
+    }
+
     fn get_help_text(&self) -> String {
         r#"
 利用可能なコマンド:
 • /hello - 挨拶を返します
 • /help - このヘルプメッセージを表示します
+• /ask <質問> [in:#channel] - Slackの過去のやり取りから質問に回答します
+• /nokizaru usage [日数] - LLM の利用量とコストを日ごとに表示します
+• /nokizaru feedback [日数] - 回答へのフィードバック（👍/👎）と役に立った割合を表示します
+• /nokizaru prefilter - 意図の分類前に絞り込んだメッセージの件数を表示します
+• /nokizaru summarize [#channel] [期間] - チャンネルのやり取りを要約します（期間は 6h・3d・yesterday など、既定は24時間）
+• /nokizaru digest add <#channel> daily|weekly [曜日] HH:MM [<#投稿先>|dm] [タイムゾーン] - チャンネルのダイジェストを定期的に投稿します
+• /nokizaru digest list | remove <ID> - 登録したダイジェストを表示・削除します
         "#
         .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ask_args() {
+        let args = AskArgs::parse("課長はだれですか？ in:#general");
+        assert_eq!(args.question, "課長はだれですか？");
+        assert_eq!(args.scope.as_deref(), Some("in:#general"));
+
+        let args = AskArgs::parse("in:<#C0123|dev> デプロイ手順は？");
+        assert_eq!(args.question, "デプロイ手順は？");
+        assert_eq!(args.scope.as_deref(), Some("in:#dev"));
+
+        let args = AskArgs::parse("  ");
+        assert!(args.question.is_empty());
+        assert!(args.scope.is_none());
+    }
+
+    #[test]
+    fn test_usage_report() {
+        assert_eq!(
+            SlackCommandService::usage_report(7, &[], &[]),
+            "直近7日の LLM 呼び出しはありません"
+        );
+
+        let daily = [DailyUsage {
+            day: chrono::NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(),
+            calls: 3,
+            input_tokens: 1200,
+            output_tokens: 300,
+            cost_usd: 0.0048,
+            unpriced_calls: 1,
+        }];
+        let report = SlackCommandService::usage_report(7, &daily, &[]);
+        assert!(report.contains("• 2026-10-18: 3回 / 入力 1200・出力 300 トークン / $0.0048"));
+        assert!(report.contains("*合計*: 3回 / $0.0048"));
+        assert!(report.contains("呼び出し 1回はコストに含まれていません"));
+    }
+}
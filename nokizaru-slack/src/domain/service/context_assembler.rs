@@ -0,0 +1,577 @@
+use std::{collections::HashSet, sync::Arc};
+
+use futures::future::join_all;
+use nokizaru_core::{
+    llm::{EstimatedTokenCounter, TokenCounter},
+    AgentService, RetrievalSettings,
+};
+
+use crate::{
+    slack_api::{MessageContext, SlackHistoryMessage, SlackMessage},
+    ContextSource, MessageContextService, RetrievedContext,
+};
+
+/// 検索結果のまとまりの区切り
+const BLOCK_SEPARATOR: &str = "\n---\n";
+/// スレッドの返信の見出し
+const THREADS_HEADER: &str = "\nThreads:\n";
+
+/// コンテキストに含めるメッセージ（出典の候補）
+struct Entry {
+    kind: EntryKind,
+    ts: String,
+    author: Option<String>,
+    permalink: Option<String>,
+    /// 番号を除いた本文（切り詰め済み）
+    line: String,
+    /// 番号・目印・改行を含めたトークン数
+    tokens: usize,
+    /// 予算が足りない場合に優先して残す順（小さいほど優先）
+    priority: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    /// 検索でヒットしたメッセージ
+    Target,
+    /// 前後のメッセージ
+    Neighbour,
+    /// スレッドの返信（または要約）
+    Reply,
+}
+
+/// 1件の検索結果から作るコンテキストのまとまり
+struct ContextBlock {
+    channel_id: String,
+    channel_name: Option<String>,
+    header: String,
+    entries: Vec<Entry>,
+}
+
+/// 要約が必要なスレッド
+struct OversizedThread {
+    block: usize,
+    priority: usize,
+    parent_ts: String,
+    reply_count: usize,
+    text: String,
+}
+
+/// 検索結果を LLM 向けのコンテキストに組み立てる
+///
+/// トークン数を対象モデルに合わせて見積もり、`max_context_tokens` の予算に収めます。
+/// - 予算は検索結果の順位（RRF と同じ `1 / (k + 順位)`）に比例して割り当て、使い残しは下位に回す
+/// - 各メッセージは `max_message_tokens` で切り詰める
+/// - `max_thread_tokens` を超えるスレッドは、要約できる場合は要約に置き換える
+/// - 予算に入りきらない場合は、ヒットしたメッセージ → 近くのメッセージ → スレッドの順に残す
+pub struct ContextAssembler {
+    counter: Arc<dyn TokenCounter>,
+    summarizer: Option<Arc<AgentService>>,
+}
+
+impl Default for ContextAssembler {
+    fn default() -> Self {
+        Self::new(Arc::new(EstimatedTokenCounter::default()))
+    }
+}
+
+impl ContextAssembler {
+    pub fn new(counter: Arc<dyn TokenCounter>) -> Self {
+        Self {
+            counter,
+            summarizer: None,
+        }
+    }
+
+    /// 長いスレッドの要約に使うサービスを指定する
+    pub fn with_summarizer(mut self, summarizer: Arc<AgentService>) -> Self {
+        self.summarizer = Some(summarizer);
+        self
+    }
+
+    /// 対象モデルでのトークン数を見積もる
+    pub fn count_tokens(&self, text: &str) -> usize {
+        self.counter.count(text)
+    }
+
+    pub async fn assemble(
+        &self,
+        contexts: Vec<MessageContext>,
+        settings: &RetrievalSettings,
+    ) -> RetrievedContext {
+        let fingerprint = Self::fingerprint(&contexts);
+        let mut seen = HashSet::new();
+        let mut oversized = Vec::new();
+        let mut blocks: Vec<ContextBlock> = contexts
+            .iter()
+            .enumerate()
+            .map(|(i, context)| self.candidates(i, context, settings, &mut seen, &mut oversized))
+            .collect();
+
+        self.summarize_threads(oversized, settings, &mut blocks)
+            .await;
+
+        // 順位に応じた重みで予算を割り当てる
+        let weights: Vec<f64> = (0..blocks.len())
+            .map(|i| 1.0 / (settings.rrf_k + (i + 1) as f64))
+            .collect();
+        let mut remaining = settings.max_context_tokens;
+        let mut dropped = 0;
+        let mut selected_blocks = Vec::new();
+        for (i, mut block) in blocks.into_iter().enumerate() {
+            let share = weights[i] / weights[i..].iter().sum::<f64>();
+            let allocation = (remaining as f64 * share).floor() as usize;
+            // 区切り・見出しも予算に含める
+            let separator = if selected_blocks.is_empty() {
+                0
+            } else {
+                self.counter.count(BLOCK_SEPARATOR)
+            };
+            let threads_header = self.counter.count(THREADS_HEADER);
+            let mut used = separator + self.counter.count(&block.header);
+
+            block.entries.sort_by_key(|entry| entry.priority);
+            let mut selected = Vec::new();
+            let mut has_replies = false;
+            for entry in block.entries {
+                // ヒットしたメッセージは割り当てを超えても全体の予算内なら含める
+                let limit = if entry.kind == EntryKind::Target {
+                    remaining
+                } else {
+                    allocation
+                };
+                let tokens = if entry.kind == EntryKind::Reply && !has_replies {
+                    entry.tokens + threads_header
+                } else {
+                    entry.tokens
+                };
+                if used + tokens <= limit {
+                    used += tokens;
+                    has_replies |= entry.kind == EntryKind::Reply;
+                    selected.push(entry);
+                } else {
+                    dropped += 1;
+                }
+            }
+
+            if selected.is_empty() {
+                continue;
+            }
+            remaining = remaining.saturating_sub(used);
+            block.entries = selected;
+            selected_blocks.push(block);
+        }
+
+        let mut context = Self::render(selected_blocks);
+        context.fingerprint = fingerprint;
+        tracing::info!(
+            "Context assembled: ~{} / {} tokens, {} sources, {} messages dropped",
+            settings.max_context_tokens - remaining,
+            settings.max_context_tokens,
+            context.sources.len(),
+            dropped
+        );
+        context
+    }
+
+    /// 1件の検索結果から候補のメッセージを作る
+    ///
+    /// 既に他の検索結果で含めたメッセージは除きます。
+    fn candidates(
+        &self,
+        block: usize,
+        context: &MessageContext,
+        settings: &RetrievalSettings,
+        seen: &mut HashSet<(String, String)>,
+        oversized: &mut Vec<OversizedThread>,
+    ) -> ContextBlock {
+        let target = &context.target_message;
+        let channel_name = target.channel.as_ref().and_then(|c| c.name.clone());
+        let channel_id = target
+            .channel
+            .as_ref()
+            .and_then(|c| c.id.clone())
+            .unwrap_or_default();
+        let mut entries = Vec::new();
+
+        if seen.insert((channel_id.clone(), target.ts.clone())) {
+            entries.push(
+                self.entry(
+                    EntryKind::Target,
+                    &target.ts,
+                    target
+                        .username
+                        .clone()
+                        .or_else(|| target.user.as_ref().map(|u| format!("<@{}>", u))),
+                    target.permalink.clone(),
+                    &Self::format_message(target),
+                    0,
+                    settings,
+                ),
+            );
+        }
+
+        // ヒットしたメッセージに近いものほど優先する
+        let mut neighbours: Vec<&SlackHistoryMessage> = context
+            .before_messages
+            .iter()
+            .chain(&context.after_messages)
+            .collect();
+        neighbours.sort_by(|a, b| a.ts.cmp(&b.ts));
+        let target_position = neighbours.partition_point(|msg| msg.ts < target.ts);
+        for (i, msg) in neighbours.into_iter().enumerate() {
+            if !seen.insert((channel_id.clone(), msg.ts.clone())) {
+                continue;
+            }
+            let distance = if i < target_position {
+                target_position - i
+            } else {
+                i + 1 - target_position
+            };
+            entries.push(self.entry(
+                EntryKind::Neighbour,
+                &msg.ts,
+                Self::history_author(msg),
+                None,
+                &MessageContextService::format_history_message(msg),
+                distance,
+                settings,
+            ));
+        }
+
+        for (t, thread) in context.threads.iter().enumerate() {
+            let replies: Vec<&SlackHistoryMessage> = thread
+                .replies
+                .iter()
+                .filter(|reply| !seen.contains(&(channel_id.clone(), reply.ts.clone())))
+                .collect();
+            if replies.is_empty() {
+                continue;
+            }
+            // ヒットしたメッセージのスレッドは近くのメッセージと同程度、それ以外は後回し
+            let base = if thread.thread_ts == target.ts {
+                1
+            } else {
+                1000 * (t + 1)
+            };
+
+            let lines: Vec<String> = replies
+                .iter()
+                .map(|reply| MessageContextService::format_history_message(reply))
+                .collect();
+            let thread_tokens: usize = lines.iter().map(|line| self.counter.count(line)).sum();
+            for reply in &replies {
+                seen.insert((channel_id.clone(), reply.ts.clone()));
+            }
+
+            if self.summarizer.is_some() && thread_tokens > settings.max_thread_tokens {
+                oversized.push(OversizedThread {
+                    block,
+                    priority: base,
+                    parent_ts: thread.thread_ts.clone(),
+                    reply_count: replies.len(),
+                    text: lines.join("\n"),
+                });
+                continue;
+            }
+
+            for (i, (reply, line)) in replies.into_iter().zip(lines).enumerate() {
+                entries.push(self.entry(
+                    EntryKind::Reply,
+                    &reply.ts,
+                    Self::history_author(reply),
+                    None,
+                    &line,
+                    base + i,
+                    settings,
+                ));
+            }
+        }
+
+        ContextBlock {
+            header: format!("#{}:\n", channel_name.as_deref().unwrap_or("unknown")),
+            channel_id,
+            channel_name,
+            entries,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn entry(
+        &self,
+        kind: EntryKind,
+        ts: &str,
+        author: Option<String>,
+        permalink: Option<String>,
+        line: &str,
+        priority: usize,
+        settings: &RetrievalSettings,
+    ) -> Entry {
+        let line = self.counter.truncate(line, settings.max_message_tokens);
+        let tokens = self.counter.count(&line) + self.prefix_tokens(kind);
+        Entry {
+            kind,
+            ts: ts.to_string(),
+            author,
+            permalink,
+            line,
+            tokens,
+            priority,
+        }
+    }
+
+    /// 1件のメッセージに付ける番号・目印・改行のトークン数（番号は2桁で見積もる）
+    fn prefix_tokens(&self, kind: EntryKind) -> usize {
+        let prefix = match kind {
+            EntryKind::Target => ">>> [10] \n",
+            EntryKind::Neighbour => "[10] \n",
+            EntryKind::Reply => "  [10] \n",
+        };
+        self.counter.count(prefix)
+    }
+
+    /// 長いスレッドを並列に要約し、要約を1件のメッセージとして追加する
+    ///
+    /// 要約に失敗したスレッドは、返信のまま（切り詰めて）含めます。
+    async fn summarize_threads(
+        &self,
+        threads: Vec<OversizedThread>,
+        settings: &RetrievalSettings,
+        blocks: &mut [ContextBlock],
+    ) {
+        let Some(summarizer) = &self.summarizer else {
+            return;
+        };
+        let max_tokens = settings.max_thread_tokens / 2;
+
+        let summaries = join_all(
+            threads
+                .iter()
+                .map(|thread| summarizer.summarize_thread(&thread.text, max_tokens as u64)),
+        )
+        .await;
+
+        for (thread, summary) in threads.into_iter().zip(summaries) {
+            let line = match summary {
+                Ok(summary) => {
+                    let summary = self.counter.truncate(summary.trim(), max_tokens);
+                    format!("(summary of {} replies) {}", thread.reply_count, summary)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to summarize thread {}: {}", thread.parent_ts, e);
+                    self.counter
+                        .truncate(&thread.text, settings.max_thread_tokens)
+                }
+            };
+            let tokens = self.counter.count(&line) + self.prefix_tokens(EntryKind::Reply);
+            blocks[thread.block].entries.push(Entry {
+                kind: EntryKind::Reply,
+                ts: thread.parent_ts,
+                author: None,
+                permalink: None,
+                line,
+                tokens,
+                priority: thread.priority,
+            });
+        }
+    }
+
+    /// 検索結果の指紋（回答キャッシュのキー）
+    ///
+    /// 要約は実行ごとに変わりうるため、組み立て前の検索結果の本文から求めます。
+    fn fingerprint(contexts: &[MessageContext]) -> String {
+        let mut parts = Vec::new();
+        for context in contexts {
+            let target = &context.target_message;
+            let channel_id = target.channel.as_ref().and_then(|c| c.id.as_deref());
+            parts.extend([channel_id.unwrap_or_default(), &target.ts, &target.text]);
+            for msg in context
+                .before_messages
+                .iter()
+                .chain(&context.after_messages)
+                .chain(context.threads.iter().flat_map(|thread| &thread.replies))
+            {
+                parts.extend([msg.ts.as_str(), msg.text.as_str()]);
+            }
+        }
+        nokizaru_core::context_fingerprint(parts)
+    }
+
+    /// 番号（`[n]`）を振って整形する
+    fn render(blocks: Vec<ContextBlock>) -> RetrievedContext {
+        let mut output = String::new();
+        let mut sources: Vec<ContextSource> = Vec::new();
+
+        for (i, block) in blocks.into_iter().enumerate() {
+            if i > 0 {
+                output.push_str(BLOCK_SEPARATOR);
+            }
+            output.push_str(&block.header);
+
+            let mut cite = |entry: &Entry| {
+                sources.push(ContextSource {
+                    index: sources.len() + 1,
+                    channel_id: block.channel_id.clone(),
+                    channel_name: block.channel_name.clone(),
+                    author: entry.author.clone(),
+                    ts: entry.ts.clone(),
+                    permalink: entry.permalink.clone(),
+                });
+                sources.len()
+            };
+
+            let (mut messages, replies): (Vec<Entry>, Vec<Entry>) = block
+                .entries
+                .into_iter()
+                .partition(|entry| entry.kind != EntryKind::Reply);
+            messages.sort_by(|a, b| a.ts.cmp(&b.ts));
+            for entry in &messages {
+                let n = cite(entry);
+                let marker = if entry.kind == EntryKind::Target {
+                    ">>> "
+                } else {
+                    ""
+                };
+                output.push_str(&format!("{}[{}] {}\n", marker, n, entry.line));
+            }
+
+            if !replies.is_empty() {
+                output.push_str(THREADS_HEADER);
+                // 優先度はスレッドごとに連番なので、スレッドごと・時系列順に並ぶ
+                let mut replies = replies;
+                replies.sort_by(|a, b| a.priority.cmp(&b.priority).then(a.ts.cmp(&b.ts)));
+                for entry in &replies {
+                    let n = cite(entry);
+                    output.push_str(&format!("  [{}] {}\n", n, entry.line));
+                }
+            }
+        }
+
+        RetrievedContext {
+            text: output,
+            sources,
+            ..Default::default()
+        }
+    }
+
+    /// Format a single message for LLM consumption
+    fn format_message(msg: &SlackMessage) -> String {
+        let user = msg
+            .username
+            .as_deref()
+            .or(msg.user.as_deref())
+            .unwrap_or("unknown");
+
+        format!("[{}] {}: {}", msg.ts, user, msg.text)
+    }
+
+    fn history_author(msg: &SlackHistoryMessage) -> Option<String> {
+        msg.user.as_ref().map(|u| format!("<@{}>", u))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slack_api::{ChannelInfo, ThreadInfo};
+    use nokizaru_core::llm::{MockProvider, ModelRouter};
+
+    fn history(ts: &str, text: &str) -> SlackHistoryMessage {
+        SlackHistoryMessage {
+            msg_type: "message".to_string(),
+            user: Some("U1".to_string()),
+            bot_id: None,
+            text: text.to_string(),
+            ts: ts.to_string(),
+            reply_count: 0,
+        }
+    }
+
+    fn context(
+        channel: &str,
+        ts: &str,
+        neighbours: usize,
+        thread: Vec<SlackHistoryMessage>,
+    ) -> MessageContext {
+        let long = "長い説明が続くメッセージです。".repeat(10);
+        MessageContext {
+            target_message: SlackMessage {
+                msg_type: "message".to_string(),
+                user: Some("U1".to_string()),
+                bot_id: None,
+                text: "課長は山田さんです".to_string(),
+                ts: ts.to_string(),
+                channel: Some(ChannelInfo {
+                    id: Some(channel.to_string()),
+                    name: Some("general".to_string()),
+                }),
+                username: None,
+                permalink: None,
+            },
+            before_messages: (0..neighbours)
+                .map(|i| history(&format!("{}.{}", ts, i), &long))
+                .collect(),
+            after_messages: Vec::new(),
+            threads: vec![ThreadInfo {
+                thread_ts: ts.to_string(),
+                message_ts: ts.to_string(),
+                reply_count: thread.len(),
+                replies: thread,
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assemble_within_budget() {
+        let counter = Arc::new(EstimatedTokenCounter::default());
+        let settings = RetrievalSettings {
+            max_context_tokens: 400,
+            max_message_tokens: 50,
+            ..Default::default()
+        };
+        let contexts = vec![
+            context("C1", "100", 6, Vec::new()),
+            context("C2", "200", 6, Vec::new()),
+        ];
+
+        let assembled = ContextAssembler::new(counter.clone())
+            .assemble(contexts, &settings)
+            .await;
+
+        assert!(counter.count(&assembled.text) <= settings.max_context_tokens);
+        // 予算が足りなくても、ヒットしたメッセージは残る
+        assert!(assembled.text.contains(">>> [1] [100]"));
+        assert!(assembled.text.contains("[200] U1: 課長は山田さんです"));
+        // 番号と出典の対応は保たれる
+        for source in &assembled.sources {
+            assert!(assembled.text.contains(&format!("[{}] ", source.index)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summarizes_oversized_threads() {
+        let provider = Arc::new(MockProvider::new(["山田さんが課長に決まった"]));
+        let agent = Arc::new(AgentService::new(Arc::new(ModelRouter::single(
+            provider.clone(),
+            "mock-model",
+        ))));
+        let replies = (0..30)
+            .map(|i| history(&format!("100.{:03}", i), "課長の件について議論しています"))
+            .collect();
+        let settings = RetrievalSettings {
+            max_thread_tokens: 100,
+            ..Default::default()
+        };
+
+        let assembled = ContextAssembler::default()
+            .with_summarizer(agent)
+            .assemble(vec![context("C1", "100", 0, replies)], &settings)
+            .await;
+
+        assert!(assembled
+            .text
+            .contains("(summary of 30 replies) 山田さんが課長に決まった"));
+        assert_eq!(assembled.sources.len(), 2);
+        assert_eq!(provider.requests()[0].max_tokens, Some(50));
+    }
+}
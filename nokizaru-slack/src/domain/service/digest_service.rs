@@ -0,0 +1,371 @@
+//! チャンネルのダイジェストの定期配信
+//!
+//! `/nokizaru digest add <#channel> daily|weekly [曜日] HH:MM [<#投稿先>|dm] [タイムゾーン]` で登録し、
+//! 登録は定期実行ジョブ `channel_digest` として保存し、[`nokizaru_core::Scheduler`] から
+//! [`JobHandler`] として呼ばれて投稿します。
+//! 要約は [`SummaryService::digest_channel`] で作り、閲覧権限は登録したユーザーと投稿先で確認します。
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{NaiveTime, Weekday};
+use chrono_tz::Tz;
+use nokizaru_core::{
+    llm::UsageTags, DigestFrequency, DigestPayload, DigestSchedule, DigestSettings,
+    DigestSubscription, JobHandler, NewDigestSubscription, ScheduledJob, ScheduledJobRepository,
+    CHANNEL_DIGEST_JOB,
+};
+use uuid::Uuid;
+
+use crate::slack_api::{PostMessageRequest, SlackApi};
+use crate::{Audience, SummaryService};
+
+/// ダイジェストの投稿先
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DigestTarget {
+    Channel(String),
+    /// 登録したユーザーとボットの DM
+    DirectMessage,
+}
+
+/// /nokizaru digest add の引数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestArgs {
+    pub source_channel_id: String,
+    /// 投稿先（省略時は要約するチャンネル）
+    pub target: Option<DigestTarget>,
+    pub schedule: DigestSchedule,
+}
+
+impl DigestArgs {
+    /// `<#channel> daily|weekly [曜日] HH:MM [<#投稿先>|dm] [タイムゾーン]` を解析する
+    ///
+    /// 曜日は `mon`・`monday` などで、毎週の場合のみ指定します。タイムゾーンは
+    /// `Asia/Tokyo` のような IANA 名で、省略時は `time_zone` です。
+    pub fn parse(text: &str, time_zone: Tz) -> Result<Self, String> {
+        let usage = || {
+            "使い方: /nokizaru digest add <#channel> daily|weekly [曜日] HH:MM [<#投稿先>|dm] [タイムゾーン]"
+                .to_string()
+        };
+        let mut words = text.split_whitespace();
+
+        let source_channel_id = words.next().and_then(channel_id).ok_or_else(usage)?;
+        let frequency = words
+            .next()
+            .and_then(DigestFrequency::parse)
+            .ok_or_else(usage)?;
+        let weekday = match frequency {
+            DigestFrequency::Daily => None,
+            DigestFrequency::Weekly => Some(
+                words
+                    .next()
+                    .and_then(|word| word.parse::<Weekday>().ok())
+                    .ok_or("毎週の場合は曜日（mon〜sun）を指定してください")?,
+            ),
+        };
+        let time = words
+            .next()
+            .and_then(|word| NaiveTime::parse_from_str(word, "%H:%M").ok())
+            .ok_or("時刻は 09:00 のように指定してください")?;
+
+        let mut args = Self {
+            source_channel_id,
+            target: None,
+            schedule: DigestSchedule {
+                frequency,
+                weekday,
+                time,
+                time_zone,
+            },
+        };
+        let mut explicit_time_zone = false;
+        for word in words {
+            if args.target.is_none() {
+                if word == "dm" {
+                    args.target = Some(DigestTarget::DirectMessage);
+                    continue;
+                }
+                if let Some(id) = channel_id(word) {
+                    args.target = Some(DigestTarget::Channel(id));
+                    continue;
+                }
+            }
+            match word.parse::<Tz>() {
+                Ok(tz) if !explicit_time_zone => {
+                    args.schedule.time_zone = tz;
+                    explicit_time_zone = true;
+                }
+                _ => return Err(format!("解釈できない引数です: {}", word)),
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// `<#C0123|general>` 形式のチャンネルのID
+fn channel_id(word: &str) -> Option<String> {
+    let escaped = word.strip_prefix("<#")?.strip_suffix('>')?;
+    let id = escaped.split('|').next().filter(|id| !id.is_empty())?;
+    Some(id.to_string())
+}
+
+/// チャンネルのダイジェストを登録・配信するドメインサービス
+pub struct DigestService {
+    jobs: Arc<ScheduledJobRepository>,
+    summaries: Arc<SummaryService>,
+    slack_api: Arc<SlackApi>,
+    space_id: Option<Uuid>,
+    time_zone: Tz,
+}
+
+impl DigestService {
+    pub fn new(
+        jobs: Arc<ScheduledJobRepository>,
+        summaries: Arc<SummaryService>,
+        slack_api: Arc<SlackApi>,
+        space_id: Option<Uuid>,
+        settings: DigestSettings,
+    ) -> Self {
+        let time_zone = settings.time_zone.parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Unknown digest time zone {}, falling back to UTC",
+                settings.time_zone
+            );
+            Tz::UTC
+        });
+        Self {
+            jobs,
+            summaries,
+            slack_api,
+            space_id,
+            time_zone,
+        }
+    }
+
+    /// /nokizaru digest のサブコマンドを実行し、返信するメッセージを返す
+    pub async fn command(&self, user_id: &str, channel_id: &str, text: &str) -> String {
+        let text = text.trim();
+        let (subcommand, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let result = match subcommand {
+            "add" => self.add(user_id, channel_id, rest).await,
+            "list" => self.list().await,
+            "remove" => self.remove(rest.trim()).await,
+            _ => Ok("使い方: /nokizaru digest add <#channel> daily|weekly [曜日] HH:MM [<#投稿先>|dm] [タイムゾーン] | list | remove <ID>".to_string()),
+        };
+        result.unwrap_or_else(|e| {
+            tracing::error!("❌ /nokizaru digest failed: {}", e);
+            "❌ ダイジェストの操作に失敗しました".to_string()
+        })
+    }
+
+    async fn add(&self, user_id: &str, channel_id: &str, text: &str) -> anyhow::Result<String> {
+        let args = match DigestArgs::parse(text, self.time_zone) {
+            Ok(args) => args,
+            Err(message) => return Ok(message),
+        };
+        let target_channel_id = match &args.target {
+            None => args.source_channel_id.clone(),
+            Some(DigestTarget::Channel(id)) => id.clone(),
+            Some(DigestTarget::DirectMessage) => {
+                self.slack_api.open_direct_message(user_id).await?
+            }
+        };
+
+        // 投稿先で非公開の内容が見えないよう、登録したユーザーと投稿先で確認する
+        let audience = Audience::new(user_id, &target_channel_id);
+        if let Some(message) = audience
+            .scope(self.summaries.reject(&args.source_channel_id))
+            .await
+        {
+            return Ok(message);
+        }
+
+        let job = NewDigestSubscription::new(
+            self.space_id,
+            &args.source_channel_id,
+            &target_channel_id,
+            &args.schedule,
+            user_id,
+        )
+        .job()?;
+        let subscription = DigestSubscription::from_job(&self.jobs.create(&job).await?)?;
+        tracing::info!(
+            "📰 Digest {} registered by {} in {}",
+            subscription.short_id(),
+            user_id,
+            channel_id
+        );
+
+        Ok(format!(
+            "📰 <#{}> のダイジェストを{}に{}へ投稿します（ID: {}）",
+            args.source_channel_id,
+            args.schedule,
+            Self::target_label(&subscription),
+            subscription.short_id()
+        ))
+    }
+
+    /// 登録済みの配信（登録順）
+    async fn subscriptions(&self) -> anyhow::Result<Vec<DigestSubscription>> {
+        let mut jobs: Vec<ScheduledJob> = self
+            .jobs
+            .list(self.space_id)
+            .await?
+            .into_iter()
+            .filter(|job| job.kind == CHANNEL_DIGEST_JOB)
+            .collect();
+        jobs.sort_by_key(|job| job.created_at);
+        Ok(jobs
+            .iter()
+            .filter_map(|job| match DigestSubscription::from_job(job) {
+                Ok(subscription) => Some(subscription),
+                Err(e) => {
+                    tracing::warn!("Invalid digest job {}: {}", job.id, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    async fn list(&self) -> anyhow::Result<String> {
+        let subscriptions = self.subscriptions().await?;
+        if subscriptions.is_empty() {
+            return Ok("登録されているダイジェストはありません".to_string());
+        }
+
+        let mut lines = vec!["*ダイジェスト*".to_string()];
+        for subscription in &subscriptions {
+            lines.push(format!(
+                "• `{}` <#{}> → {} {}（登録: <@{}>）",
+                subscription.short_id(),
+                subscription.source_channel_id,
+                Self::target_label(subscription),
+                subscription.schedule,
+                subscription.created_by
+            ));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    async fn remove(&self, short_id: &str) -> anyhow::Result<String> {
+        if short_id.is_empty() {
+            return Ok("使い方: /nokizaru digest remove <ID>".to_string());
+        }
+        let subscription = self
+            .subscriptions()
+            .await?
+            .into_iter()
+            .find(|subscription| subscription.short_id() == short_id);
+        let Some(subscription) = subscription else {
+            return Ok(format!("ID {} のダイジェストは見つかりません", short_id));
+        };
+
+        self.jobs.delete(self.space_id, subscription.id).await?;
+        Ok(format!(
+            "🗑️ <#{}> のダイジェスト（{}）を削除しました",
+            subscription.source_channel_id, short_id
+        ))
+    }
+
+    fn target_label(subscription: &DigestSubscription) -> String {
+        if subscription.target_channel_id.starts_with('D') {
+            "DM".to_string()
+        } else {
+            format!("<#{}>", subscription.target_channel_id)
+        }
+    }
+
+    /// ダイジェストを投稿する
+    async fn deliver(&self, subscription: &DigestSubscription) -> anyhow::Result<()> {
+        let period = subscription.schedule.frequency.period();
+        // 配信が遅れても、予定していた時刻までの期間を要約する
+        let oldest = subscription.next_run_at - period;
+        let label = match subscription.schedule.frequency {
+            DigestFrequency::Daily => "直近24時間",
+            DigestFrequency::Weekly => "直近1週間",
+        };
+
+        let tags = UsageTags::new(
+            &subscription.target_channel_id,
+            Some(subscription.created_by.clone()),
+        );
+        let audience = Audience::new(&subscription.created_by, &subscription.target_channel_id);
+        let digest = tags
+            .scope(audience.scope(self.summaries.digest_channel(
+                &subscription.created_by,
+                &subscription.source_channel_id,
+                oldest,
+                label,
+            )))
+            .await?;
+
+        match digest {
+            Some(text) => {
+                self.slack_api
+                    .post_message(&PostMessageRequest {
+                        channel_id: subscription.target_channel_id.clone(),
+                        text,
+                        thread_ts: None,
+                        blocks: None,
+                    })
+                    .await?;
+                tracing::info!("📰 Digest {} delivered", subscription.short_id());
+            }
+            None => tracing::info!(
+                "📰 Digest {} skipped: no messages in <#{}>",
+                subscription.short_id(),
+                subscription.source_channel_id
+            ),
+        }
+
+        Ok(())
+    }
+}
+
+/// 配信時刻になった登録を [`nokizaru_core::Scheduler`] から配信する
+#[async_trait]
+impl JobHandler for DigestService {
+    fn validate(&self, payload: &serde_json::Value) -> anyhow::Result<()> {
+        DigestPayload::from_value(payload).map(|_| ())
+    }
+
+    async fn run(&self, job: &ScheduledJob) -> anyhow::Result<()> {
+        self.deliver(&DigestSubscription::from_job(job)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_digest_args() {
+        let tokyo: Tz = "Asia/Tokyo".parse().unwrap();
+
+        let args = DigestArgs::parse("<#C1|dev> daily 09:00", tokyo).unwrap();
+        assert_eq!(args.source_channel_id, "C1");
+        assert_eq!(args.target, None);
+        assert_eq!(args.schedule.to_string(), "毎日 09:00（Asia/Tokyo）");
+
+        let args = DigestArgs::parse(
+            "<#C1|dev> weekly mon 18:30 <#C2|leads> America/New_York",
+            tokyo,
+        )
+        .unwrap();
+        assert_eq!(args.target, Some(DigestTarget::Channel("C2".to_string())));
+        assert_eq!(args.schedule.weekday, Some(Weekday::Mon));
+        assert_eq!(
+            args.schedule.to_string(),
+            "毎週月曜 18:30（America/New_York）"
+        );
+
+        let args = DigestArgs::parse("<#C1> weekly friday 17:00 dm", tokyo).unwrap();
+        assert_eq!(args.target, Some(DigestTarget::DirectMessage));
+        assert_eq!(args.schedule.weekday, Some(Weekday::Fri));
+
+        assert!(DigestArgs::parse("<#C1> weekly 09:00", tokyo).is_err());
+        assert!(DigestArgs::parse("<#C1> daily 9時", tokyo).is_err());
+        assert!(DigestArgs::parse("dev daily 09:00", tokyo).is_err());
+        assert!(DigestArgs::parse("<#C1> daily 09:00 Mars/Olympus", tokyo).is_err());
+    }
+}
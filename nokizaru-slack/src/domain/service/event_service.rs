@@ -1,31 +1,200 @@
 use std::sync::Arc;
 
-use crate::slack_api::SlackApi;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::slack_api::{AuthTestResponse, SlackApi, SlackHistoryMessage};
 use crate::{
-    slack_api::PostMessageRequest, MessageContextService, SlackError, SlackEvent,
+    slack_api::PostMessageRequest, Answer, AnswerService, Audience, ChannelAccessService,
+    FeedbackService, InteractionSource, MessageContextService, ReactionItem, SlackError,
+    SlackEvent,
+};
+use nokizaru_core::{
+    llm::UsageTags, AgentService, ChatTurn, IntentAction, IntentClassification,
+    IntentRoutingSettings, Prefilter, PrefilterDecision, PrefilterSettings,
 };
-use nokizaru_core::{AgentService, MessageCategory};
 
 pub struct EventService {
-    context_service: Arc<MessageContextService>,
     agent_service: Arc<AgentService>,
+    answer_service: Arc<AnswerService>,
     slack_api: Arc<SlackApi>,
+    /// メッセージの意図ごとの対応
+    intents: IntentRoutingSettings,
+    /// 意図を分類する前のルールによる絞り込み
+    prefilter: Arc<Prefilter>,
+    /// 回答へのフィードバック（👍/👎 ボタンとリアクション）
+    feedback: Option<Arc<FeedbackService>>,
+    /// チケット用のチャンネルに転記してよい会話かのチェック
+    access: Option<Arc<ChannelAccessService>>,
+    /// ボット自身の認証情報（auth.test の結果をキャッシュ）
+    bot_identity: OnceCell<AuthTestResponse>,
 }
 
 impl EventService {
     pub fn new(
-        context_service: Arc<MessageContextService>,
         agent_service: Arc<AgentService>,
+        answer_service: Arc<AnswerService>,
         slack_api: Arc<SlackApi>,
     ) -> Self {
         Self {
-            context_service,
             agent_service,
+            answer_service,
             slack_api,
+            intents: IntentRoutingSettings::default(),
+            prefilter: Arc::new(Prefilter::new(PrefilterSettings::default())),
+            feedback: None,
+            access: None,
+            bot_identity: OnceCell::new(),
+        }
+    }
+
+    /// メッセージの意図ごとの対応を設定する
+    pub fn with_intent_routing(mut self, intents: IntentRoutingSettings) -> Self {
+        self.intents = intents;
+        self
+    }
+
+    /// 意図を分類する前の絞り込みを設定する（判定の件数を /nokizaru prefilter と共有する）
+    pub fn with_prefilter(mut self, prefilter: Arc<Prefilter>) -> Self {
+        self.prefilter = prefilter;
+        self
+    }
+
+    /// 回答に 👍/👎 ボタンを付け、回答へのリアクションを記録する
+    pub fn with_feedback(mut self, feedback: Arc<FeedbackService>) -> Self {
+        self.feedback = Some(feedback);
+        self
+    }
+
+    /// チケットに転記するメッセージの閲覧権限のチェックを設定する
+    ///
+    /// 設定しない場合、チケットには元のメッセージへのリンクだけを載せます。
+    pub fn with_access(mut self, access: Arc<ChannelAccessService>) -> Self {
+        self.access = Some(access);
+        self
+    }
+
+    /// ボット自身の認証情報を取得
+    async fn bot_identity(&self) -> Result<&AuthTestResponse, SlackError> {
+        self.bot_identity
+            .get_or_try_init(|| async {
+                self.slack_api
+                    .auth_test()
+                    .await
+                    .map_err(|e| SlackError::ApiError(format!("auth.test failed: {}", e)))
+            })
+            .await
+    }
+
+    /// テキストからボットへのメンションを取り除く
+    fn strip_mention(text: &str, bot_user_id: &str) -> String {
+        text.replace(&format!("<@{}>", bot_user_id), "")
+            .trim()
+            .to_string()
+    }
+
+    /// スレッドの親メッセージ
+    ///
+    /// API の返す順序に頼らず `thread_ts` と一致するものを選び、
+    /// 見つからない場合（親が削除された場合など）は最も古いメッセージを使います。
+    fn thread_parent<'a>(
+        messages: &'a [SlackHistoryMessage],
+        thread_ts: &str,
+    ) -> Option<&'a SlackHistoryMessage> {
+        messages
+            .iter()
+            .find(|msg| msg.ts == thread_ts)
+            .or_else(|| messages.iter().min_by(|a, b| a.ts.cmp(&b.ts)))
+    }
+
+    /// 返信するスレッドの ts
+    ///
+    /// スレッド内の投稿にはそのスレッドに、スレッド外の投稿にはその投稿を親にして返信します。
+    fn reply_thread_ts(ts: String, thread_ts: Option<String>) -> String {
+        thread_ts
+            .filter(|thread_ts| !thread_ts.is_empty())
+            .unwrap_or(ts)
+    }
+
+    /// ボット自身の投稿かどうか
+    fn is_own_message(msg: &SlackHistoryMessage, bot: &AuthTestResponse) -> bool {
+        msg.user.as_deref() == Some(bot.user_id.as_str())
+            || (msg.bot_id.is_some() && msg.bot_id == bot.bot_id)
+    }
+
+    /// スレッドのメッセージをボットとの会話履歴に変換する
+    ///
+    /// ボットの投稿は assistant、それ以外は発言者付きの user として扱い、投稿順（ts 順）に並べます。
+    /// `current_ts` 以降のメッセージは含めません。
+    fn conversation_history(
+        messages: &[SlackHistoryMessage],
+        bot: &AuthTestResponse,
+        current_ts: &str,
+    ) -> Vec<ChatTurn> {
+        let mut messages: Vec<&SlackHistoryMessage> = messages
+            .iter()
+            .filter(|msg| msg.ts.as_str() < current_ts)
+            .collect();
+        messages.sort_by(|a, b| a.ts.cmp(&b.ts));
+        messages
+            .into_iter()
+            .map(|msg| {
+                if Self::is_own_message(msg, bot) {
+                    ChatTurn::assistant(msg.text.clone())
+                } else {
+                    let user = msg.user.as_deref().unwrap_or("unknown");
+                    ChatTurn::user(format!(
+                        "<@{}>: {}",
+                        user,
+                        Self::strip_mention(&msg.text, &bot.user_id)
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// ボットが回答済みのスレッドであれば、その会話履歴を返す
+    async fn bot_conversation(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        current_ts: &str,
+    ) -> Result<Option<Vec<ChatTurn>>, SlackError> {
+        let bot = self.bot_identity().await?;
+        let messages = self
+            .slack_api
+            .get_thread_messages(channel, thread_ts)
+            .await
+            .map_err(|e| SlackError::ApiError(format!("Failed to get thread: {}", e)))?;
+
+        if !messages.iter().any(|msg| Self::is_own_message(msg, bot)) {
+            return Ok(None);
         }
+
+        Ok(Some(Self::conversation_history(&messages, bot, current_ts)))
     }
 
+    /// イベントを処理する
+    ///
+    /// LLM の利用量はイベントのチャンネル・ユーザーで記録し、
+    /// 検索結果は投稿したユーザーがそのチャンネルで閲覧できるものに限定する。
     pub async fn execute(&self, event: SlackEvent) -> Result<(), SlackError> {
+        let (channel, user) = match &event {
+            SlackEvent::Message { channel, user, .. } => (channel.clone(), user.clone()),
+            SlackEvent::AppMention { channel, user, .. } => (channel.clone(), Some(user.clone())),
+            SlackEvent::ReactionAdded { item, user, .. }
+            | SlackEvent::ReactionRemoved { item, user, .. } => {
+                (item.channel.clone(), Some(user.clone()))
+            }
+        };
+        let dispatch = UsageTags::new(&channel, user.clone()).scope(self.dispatch(event));
+        match user {
+            Some(user) => Audience::new(user, channel).scope(dispatch).await,
+            None => dispatch.await,
+        }
+    }
+
+    async fn dispatch(&self, event: SlackEvent) -> Result<(), SlackError> {
         match event {
             SlackEvent::Message {
                 channel,
@@ -34,27 +203,138 @@ impl EventService {
                 text,
                 ts,
                 thread_ts,
-            } => {
-                self.handle_message(channel, user, bot_id, text, ts, thread_ts)
-                    .await
-            }
+                subtype,
+                message,
+                deleted_ts,
+            } => match subtype.as_deref() {
+                // 編集・削除されたメッセージを出典とする回答キャッシュを破棄する
+                Some("message_changed") => {
+                    let edited_ts = message.map(|m| m.ts).unwrap_or(ts);
+                    self.answer_service
+                        .invalidate_message(&channel, &edited_ts)
+                        .await;
+                    Ok(())
+                }
+                Some("message_deleted") => {
+                    let deleted_ts = deleted_ts.unwrap_or(ts);
+                    self.answer_service
+                        .invalidate_message(&channel, &deleted_ts)
+                        .await;
+                    Ok(())
+                }
+                _ => {
+                    self.handle_message(channel, user, bot_id, text, ts, thread_ts, subtype)
+                        .await
+                }
+            },
             SlackEvent::AppMention {
                 channel,
                 user,
                 text,
                 ts,
-            } => self.handle_app_mention(channel, user, text, ts).await,
+                thread_ts,
+            } => {
+                self.handle_app_mention(channel, user, text, ts, thread_ts)
+                    .await
+            }
+            SlackEvent::ReactionAdded {
+                user,
+                reaction,
+                item,
+                item_user,
+            } => {
+                self.handle_reaction(user, reaction, item, item_user, true)
+                    .await
+            }
+            SlackEvent::ReactionRemoved {
+                user,
+                reaction,
+                item,
+                item_user,
+            } => {
+                self.handle_reaction(user, reaction, item, item_user, false)
+                    .await
+            }
         }
     }
 
+    /// ボットの回答へのリアクションをフィードバックとして記録する
+    async fn handle_reaction(
+        &self,
+        user: String,
+        reaction: String,
+        item: ReactionItem,
+        item_user: Option<String>,
+        added: bool,
+    ) -> Result<(), SlackError> {
+        let Some(feedback) = &self.feedback else {
+            return Ok(());
+        };
+        if item.item_type != "message" {
+            return Ok(());
+        }
+        // ボット以外のメッセージへのリアクションは回答の記録を探すまでもなく無視する
+        if let Some(item_user) = item_user {
+            if item_user != self.bot_identity().await?.user_id {
+                return Ok(());
+            }
+        }
+
+        feedback
+            .record_reaction(&user, &reaction, &item.channel, &item.ts, added)
+            .await
+    }
+
+    /// 回答を投稿する（フィードバックが有効なら 👍/👎 ボタンを付け、投稿したメッセージを記録する）
+    async fn post_answer(
+        &self,
+        channel: String,
+        thread_ts: String,
+        answer: Answer,
+        interaction_id: Option<Uuid>,
+    ) -> Result<(), SlackError> {
+        let feedback = self
+            .feedback
+            .as_ref()
+            .zip(interaction_id)
+            .filter(|_| !answer.is_abstained());
+        let mut blocks = answer.blocks();
+        if let Some((_, interaction_id)) = feedback {
+            blocks.push(FeedbackService::actions_block(interaction_id));
+        }
+
+        let posted = self
+            .slack_api
+            .post_message(&PostMessageRequest {
+                channel_id: channel,
+                blocks: Some(blocks),
+                text: answer.text,
+                thread_ts: Some(thread_ts),
+            })
+            .await
+            .map_err(|e| SlackError::MessageSendFailed(e.to_string()))?;
+        if let Some((feedback, interaction_id)) = feedback {
+            feedback.attach_message(interaction_id, &posted.ts).await;
+        }
+        Ok(())
+    }
+
+    /// チャンネルメッセージへの応答
+    ///
+    /// メッセージの意図を分類し、スペースの振り分け表に従って対応します。
+    /// 回答する場合はスレッドで回答します。
+    /// ボットが回答済みのスレッド内のメッセージは続きの質問として扱い、
+    /// それまでのやり取りを会話履歴として回答に使います。
+    #[allow(clippy::too_many_arguments)]
     async fn handle_message(
         &self,
         channel: String,
         user: Option<String>,
         bot_id: Option<String>,
         text: String,
-        _ts: String,
-        _thread_ts: Option<String>,
+        ts: String,
+        thread_ts: Option<String>,
+        subtype: Option<String>,
     ) -> Result<(), SlackError> {
         // ボット自身のメッセージは無視（無限ループ防止）
         if bot_id.is_some() {
@@ -71,56 +351,81 @@ impl EventService {
             }
         };
 
+        // ボットへのメンションは app_mention イベントで処理する（二重回答防止）
+        let bot = self.bot_identity().await?;
+        if text.contains(&format!("<@{}>", bot.user_id)) {
+            tracing::debug!("Message mentions the bot, handled by app_mention");
+            return Ok(());
+        }
+
         tracing::info!(
             "Processing message from user {} in channel {}",
             user_id,
             channel
         );
 
-        tracing::info!("Starting agent test for input: {}", text);
-
-        let reflection_result =
-            self.agent_service.reflection(&text).await.map_err(|e| {
-                SlackError::ApiError(format!("Reflection failed: {}", e.to_string()))
-            })?;
-
-        if !matches!(reflection_result.category, MessageCategory::Question) {
-            println!(
-                "No further action for category: {:?}",
-                reflection_result.category
-            );
-            return Ok(());
+        match self.prefilter.decide(&channel, subtype.as_deref(), &text) {
+            PrefilterDecision::Skip(reason) => {
+                tracing::debug!(
+                    "Skipping message before classification: {}",
+                    reason.as_str()
+                );
+                return Ok(());
+            }
+            PrefilterDecision::Answer => {}
+            PrefilterDecision::Classify => {
+                let thread = Self::reply_thread_ts(ts.clone(), thread_ts.clone());
+                if !self.route(&channel, &user_id, &text, &ts, &thread).await? {
+                    return Ok(());
+                }
+            }
         }
 
-        let search_query = self
-            .agent_service
-            .query_rewriting(&text)
-            .await
-            .map_err(|e| {
-                SlackError::ApiError(format!("Query rewriting failed: {}", e.to_string()))
-            })?;
-
-        println!("Final rewritten queries: {:?}", search_query.queries);
-
-        let contexts = self
-            .context_service
-            .execute(search_query.queries[0].as_str())
-            .await?;
+        // スレッド内の返信であれば、ボットとの会話の続きかどうかを確認する
+        let history = match thread_ts.as_deref() {
+            Some(thread_ts) if thread_ts != ts => self
+                .bot_conversation(&channel, thread_ts, &ts)
+                .await?
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
 
-        println!("Retrieved contexts: {:?}", contexts.len());
+        if !history.is_empty() {
+            tracing::info!("Follow-up question with {} prior turns", history.len());
+        }
 
-        let result = self.agent_service.answer(&text, &contexts).await;
+        let language = self.answer_service.language_for(&user_id, &channel, &text);
+        let result = self
+            .answer_service
+            .answer_with_thread(&text, None, None, &history, language)
+            .await;
+        let interaction_id = match &result {
+            Ok(answer) => {
+                self.answer_service
+                    .record_interaction(
+                        InteractionSource::Message,
+                        &channel,
+                        &user_id,
+                        &text,
+                        answer,
+                    )
+                    .await
+            }
+            Err(_) => None,
+        };
         match result {
+            // 自発的な回答なので、確信が持てない場合は黙っておく
+            Ok(answer) if answer.is_abstained() => {
+                tracing::info!("Not enough information to answer, staying silent");
+            }
             Ok(answer) => {
-                self.slack_api
-                    .post_message(&PostMessageRequest {
-                        channel_id: channel,
-                        text: answer,
-                        thread_ts: None,
-                    })
-                    .await.map_err(|e| {
-                        SlackError::ApiError(format!("Failed to post message: {}", e.to_string()))
-                    })?;
+                self.post_answer(
+                    channel,
+                    Self::reply_thread_ts(ts, thread_ts),
+                    answer,
+                    interaction_id,
+                )
+                .await?;
             }
             Err(e) => {
                 let error_text = format!("❌ Agent processing failed: {}", e);
@@ -131,12 +436,152 @@ impl EventService {
         Ok(())
     }
 
+    /// メッセージの意図を分類し、振り分け表に従って対応する
+    ///
+    /// 回答する場合は true を返します（回答は呼び出し元で行う）。
+    async fn route(
+        &self,
+        channel: &str,
+        user_id: &str,
+        text: &str,
+        ts: &str,
+        thread_ts: &str,
+    ) -> Result<bool, SlackError> {
+        // 意図を判定できない場合は、自発的な対応は控える
+        let classification = match self.agent_service.classify_intent(text).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Intent classification failed, skipping message: {}", e);
+                return Ok(false);
+            }
+        };
+
+        match self.intents.action_for(&classification) {
+            IntentAction::Answer => return Ok(true),
+            IntentAction::CreateTicket => {
+                self.create_ticket(&classification, channel, user_id, text, ts)
+                    .await?
+            }
+            IntentAction::NotifyOnCall => {
+                self.notify_on_call(&classification, channel, thread_ts.to_string())
+                    .await?
+            }
+            IntentAction::Ignore => tracing::debug!(
+                "No further action for intent: {} ({:.2})",
+                classification.intent.as_str(),
+                classification.confidence
+            ),
+        }
+        Ok(false)
+    }
+
+    /// チケット用のチャンネルにメッセージを転記する
+    ///
+    /// 公開チャンネル以外のメッセージは本文を載せず、元のメッセージへのリンクだけを載せます。
+    async fn create_ticket(
+        &self,
+        classification: &IntentClassification,
+        channel: &str,
+        user_id: &str,
+        text: &str,
+        ts: &str,
+    ) -> Result<(), SlackError> {
+        let Some(ticket_channel) = self.intents.ticket_channel.clone() else {
+            return Ok(());
+        };
+        tracing::info!(
+            "Creating ticket for {} in {}",
+            classification.intent.as_str(),
+            ticket_channel
+        );
+
+        // 転記先から元のメッセージを辿れるようにする（取得できなければチャンネルだけ示す）
+        let source = match self.slack_api.get_permalink(channel, ts).await {
+            Ok(permalink) => format!("<{}|元のメッセージ>", permalink),
+            Err(e) => {
+                tracing::warn!("Failed to get permalink: {}", e);
+                format!("<#{}>", channel)
+            }
+        };
+        // 非公開の会話のメッセージは、転記先で読めるとは限らないのでリンクだけにする
+        let quotable = match &self.access {
+            Some(access) => {
+                Audience::new(user_id, &ticket_channel)
+                    .scope(access.is_allowed(channel))
+                    .await
+            }
+            None => false,
+        };
+        let quoted = if quotable {
+            text.lines()
+                .map(|line| format!("> {}", line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            "_非公開の会話のため、本文は元のメッセージで確認してください_".to_string()
+        };
+
+        self.slack_api
+            .post_message(&PostMessageRequest {
+                channel_id: ticket_channel,
+                blocks: None,
+                text: format!(
+                    "🎫 *{}* from <@{}>（{}）\n{}",
+                    classification.intent.label(),
+                    user_id,
+                    source,
+                    quoted
+                ),
+                thread_ts: None,
+            })
+            .await
+            .map_err(|e| SlackError::ApiError(format!("Failed to create ticket: {}", e)))?;
+        Ok(())
+    }
+
+    /// スレッドで当番をメンションする
+    async fn notify_on_call(
+        &self,
+        classification: &IntentClassification,
+        channel: &str,
+        thread_ts: String,
+    ) -> Result<(), SlackError> {
+        let Some(mention) = self.intents.on_call_mention() else {
+            return Ok(());
+        };
+        tracing::info!(
+            "Notifying on-call for {} in {}",
+            classification.intent.as_str(),
+            channel
+        );
+
+        self.slack_api
+            .post_message(&PostMessageRequest {
+                channel_id: channel.to_string(),
+                blocks: None,
+                text: format!(
+                    "🚨 {} {}の可能性があります。確認をお願いします。",
+                    mention,
+                    classification.intent.label()
+                ),
+                thread_ts: Some(thread_ts),
+            })
+            .await
+            .map_err(|e| SlackError::ApiError(format!("Failed to notify on-call: {}", e)))?;
+        Ok(())
+    }
+
+    /// メンションへの応答
+    ///
+    /// メンションは明示的な依頼なので reflection を行わずに回答し、スレッドに返信します。
+    /// スレッド内でメンションされた場合はスレッドの内容も回答のコンテキストに含めます。
     async fn handle_app_mention(
         &self,
         channel: String,
         user: String,
         text: String,
-        _ts: String,
+        ts: String,
+        thread_ts: Option<String>,
     ) -> Result<(), SlackError> {
         tracing::info!(
             "Processing app mention from user {} in channel {}. text: {}",
@@ -145,8 +590,216 @@ impl EventService {
             text
         );
 
-        // なんか
+        let bot = self.bot_identity().await?;
+        let question = Self::strip_mention(&text, &bot.user_id);
 
-        Ok(())
+        let thread_messages = match thread_ts.as_deref() {
+            Some(thread_ts) => self
+                .slack_api
+                .get_thread_messages(&channel, thread_ts)
+                .await
+                .map_err(|e| SlackError::ApiError(format!("Failed to get thread: {}", e)))?
+                .into_iter()
+                .filter(|msg| msg.ts != ts)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // メンションだけでスレッドもない場合は使い方を返す
+        let (reply, interaction_id) = if question.is_empty() && thread_messages.is_empty() {
+            let usage = Answer {
+                text: "質問をメンションと一緒に送ってください（例: @nokizaru 課長はだれですか？）"
+                    .to_string(),
+                ..Default::default()
+            };
+            (usage, None)
+        } else {
+            // メンションだけの場合はスレッドの親メッセージを質問とみなす
+            let parent = thread_ts
+                .as_deref()
+                .and_then(|thread_ts| Self::thread_parent(&thread_messages, thread_ts));
+            let question = match parent {
+                Some(parent) if question.is_empty() => {
+                    Self::strip_mention(&parent.text, &bot.user_id)
+                }
+                _ => question,
+            };
+            // ボットが回答済みのスレッドなら会話履歴として、そうでなければ参考情報として渡す
+            let (thread_context, history) = if thread_messages
+                .iter()
+                .any(|msg| Self::is_own_message(msg, bot))
+            {
+                (None, Self::conversation_history(&thread_messages, bot, &ts))
+            } else {
+                (
+                    Some(MessageContextService::format_thread(&thread_messages)),
+                    Vec::new(),
+                )
+            };
+
+            let language = self.answer_service.language_for(&user, &channel, &question);
+            match self
+                .answer_service
+                .answer_with_thread(
+                    &question,
+                    None,
+                    thread_context.as_deref(),
+                    &history,
+                    language,
+                )
+                .await
+            {
+                Ok(answer) => {
+                    let interaction_id = self
+                        .answer_service
+                        .record_interaction(
+                            InteractionSource::Mention,
+                            &channel,
+                            &user,
+                            &question,
+                            &answer,
+                        )
+                        .await;
+                    (answer, interaction_id)
+                }
+                Err(e) => {
+                    tracing::error!("❌ Agent processing failed: {}", e);
+                    let failure = Answer {
+                        text: "❌ 回答の生成に失敗しました".to_string(),
+                        ..Default::default()
+                    };
+                    (failure, None)
+                }
+            }
+        };
+
+        self.post_answer(
+            channel,
+            Self::reply_thread_ts(ts, thread_ts),
+            reply,
+            interaction_id,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nokizaru_core::ChatRole;
+
+    #[test]
+    fn test_strip_mention() {
+        assert_eq!(
+            EventService::strip_mention("<@UBOT> 課長はだれですか？", "UBOT"),
+            "課長はだれですか？"
+        );
+        assert_eq!(
+            EventService::strip_mention("課長は <@UBOT> だれ？", "UBOT"),
+            "課長は  だれ？"
+        );
+        // 他のユーザーへのメンションは残す
+        assert_eq!(
+            EventService::strip_mention("<@UBOT> <@U1> の担当は？", "UBOT"),
+            "<@U1> の担当は？"
+        );
+        assert_eq!(EventService::strip_mention("  <@UBOT>  ", "UBOT"), "");
+    }
+
+    fn message(
+        ts: &str,
+        user: Option<&str>,
+        bot_id: Option<&str>,
+        text: &str,
+    ) -> SlackHistoryMessage {
+        SlackHistoryMessage {
+            msg_type: "message".to_string(),
+            user: user.map(str::to_string),
+            bot_id: bot_id.map(str::to_string),
+            text: text.to_string(),
+            ts: ts.to_string(),
+            reply_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_conversation_history() {
+        let bot = AuthTestResponse {
+            url: "https://example.slack.com/".to_string(),
+            team_id: "T1".to_string(),
+            user_id: "UBOT".to_string(),
+            bot_id: Some("BBOT".to_string()),
+        };
+        // API の返す順序に関わらず ts 順に並べる
+        let messages = vec![
+            message("1700000000.000300", Some("U1"), None, "<@UBOT> 部長は？"),
+            message(
+                "1700000000.000100",
+                Some("U1"),
+                None,
+                "<@UBOT> 課長はだれですか？",
+            ),
+            message(
+                "1700000000.000200",
+                None,
+                Some("BBOT"),
+                "課長は山田さんです。",
+            ),
+            message(
+                "1700000000.000400",
+                None,
+                Some("BBOT"),
+                "部長は佐藤さんです。",
+            ),
+            message("1700000000.000500", Some("U2"), None, "<@UBOT> 今の質問"),
+        ];
+
+        let history = EventService::conversation_history(&messages, &bot, "1700000000.000500");
+        let turns: Vec<(ChatRole, &str)> = history
+            .iter()
+            .map(|turn| (turn.role, turn.content.as_str()))
+            .collect();
+        assert_eq!(
+            turns,
+            vec![
+                (ChatRole::User, "<@U1>: 課長はだれですか？"),
+                (ChatRole::Assistant, "課長は山田さんです。"),
+                (ChatRole::User, "<@U1>: 部長は？"),
+                (ChatRole::Assistant, "部長は佐藤さんです。"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reply_thread_ts() {
+        // スレッド外のメンションは、その投稿を親にしたスレッドに返信する
+        assert_eq!(
+            EventService::reply_thread_ts("1700000000.000200".to_string(), None),
+            "1700000000.000200"
+        );
+        // スレッド内のメンションは、そのスレッドに返信する
+        assert_eq!(
+            EventService::reply_thread_ts(
+                "1700000000.000200".to_string(),
+                Some("1700000000.000100".to_string())
+            ),
+            "1700000000.000100"
+        );
+        assert_eq!(
+            EventService::reply_thread_ts("1700000000.000200".to_string(), Some(String::new())),
+            "1700000000.000200"
+        );
+
+        // 返信が順不同で返ってきても、thread_ts の親メッセージを選ぶ
+        let replies = vec![
+            message("1700000000.000300", Some("U2"), None, "私も知りたいです"),
+            message("1700000000.000100", Some("U1"), None, "課長はだれですか？"),
+            message("1700000000.000200", Some("U3"), None, "たしか山田さん"),
+        ];
+        let parent = EventService::thread_parent(&replies, "1700000000.000100").unwrap();
+        assert_eq!(parent.text, "課長はだれですか？");
+        // 親が見つからない場合は最も古いメッセージ
+        let parent = EventService::thread_parent(&replies, "1700000000.000050").unwrap();
+        assert_eq!(parent.ts, "1700000000.000100");
     }
 }
@@ -0,0 +1,335 @@
+//! 回答へのフィードバック
+//!
+//! ボットの回答に 👍/👎 ボタンを付け、押されたボタンと回答へのリアクションを記録します。
+//! 👎 が押された場合は、どこが良くなかったかを尋ねるモーダルを開きます。
+//! 記録は質問応答ログに紐付くため、質問・出典・プロンプトのバージョン・モデルごとに集計できます。
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use nokizaru_core::{
+    FeedbackReason, FeedbackRepository, FeedbackSettings, FeedbackSource, FeedbackSummary,
+    InteractionLogRepository, NewAnswerFeedback,
+};
+use uuid::Uuid;
+
+use crate::slack_api::{Block, BlockElement, ResponseUrlMessage, SlackApi, View};
+use crate::{SlackError, SlackInteraction};
+
+/// 「役に立った」ボタンの action_id
+pub const FEEDBACK_HELPFUL_ACTION_ID: &str = "feedback_helpful";
+/// 「役に立たなかった」ボタンの action_id
+pub const FEEDBACK_NOT_HELPFUL_ACTION_ID: &str = "feedback_not_helpful";
+/// 役に立たなかった理由を尋ねるモーダルの callback_id
+pub const FEEDBACK_REASON_CALLBACK_ID: &str = "answer_feedback";
+
+/// モーダルの入力欄の action_id
+const REASON_ACTION_ID: &str = "reason";
+const COMMENT_ACTION_ID: &str = "comment";
+/// コメントの最大文字数
+const COMMENT_MAX_CHARS: usize = 500;
+
+/// 回答へのフィードバックのドメインサービス
+pub struct FeedbackService {
+    repository: Arc<FeedbackRepository>,
+    interaction_logs: Arc<InteractionLogRepository>,
+    slack_api: Arc<SlackApi>,
+    space_id: Option<Uuid>,
+    settings: FeedbackSettings,
+}
+
+impl FeedbackService {
+    pub fn new(
+        repository: Arc<FeedbackRepository>,
+        interaction_logs: Arc<InteractionLogRepository>,
+        slack_api: Arc<SlackApi>,
+        space_id: Option<Uuid>,
+        settings: FeedbackSettings,
+    ) -> Self {
+        Self {
+            repository,
+            interaction_logs,
+            slack_api,
+            space_id,
+            settings,
+        }
+    }
+
+    /// 回答に付ける 👍/👎 ボタン（value は質問応答ログのID）
+    pub fn actions_block(interaction_id: Uuid) -> Block {
+        let value = Some(interaction_id.to_string());
+        Block::actions(vec![
+            BlockElement::button("👍 役に立った", FEEDBACK_HELPFUL_ACTION_ID, value.clone()),
+            BlockElement::button("👎 役に立たなかった", FEEDBACK_NOT_HELPFUL_ACTION_ID, value),
+        ])
+    }
+
+    /// 回答を投稿したメッセージを質問応答ログに記録する（リアクションを回答に紐付けるため）
+    ///
+    /// 記録に失敗してもボタンでの評価はできるため、エラーはログ出力のみ行います。
+    pub async fn attach_message(&self, interaction_id: Uuid, message_ts: &str) {
+        if let Err(e) = self
+            .interaction_logs
+            .attach_message(interaction_id, message_ts)
+            .await
+        {
+            tracing::warn!("Failed to attach message to interaction: {}", e);
+        }
+    }
+
+    /// 👍/👎 ボタンの評価を記録する
+    ///
+    /// 👎 の場合は理由を尋ねるモーダルを開き、それ以外は押したユーザーにだけお礼を返します。
+    pub async fn record_button(
+        &self,
+        interaction: &SlackInteraction,
+        helpful: bool,
+    ) -> Result<(), SlackError> {
+        let interaction_id = interaction
+            .value
+            .as_deref()
+            .and_then(|value| value.parse::<Uuid>().ok())
+            .ok_or(SlackError::InvalidEventPayload)?;
+
+        let feedback = NewAnswerFeedback::new(
+            self.space_id,
+            interaction_id,
+            &interaction.user_id,
+            FeedbackSource::Button,
+            helpful,
+        );
+        self.repository
+            .record(&feedback)
+            .await
+            .map_err(|e| SlackError::EventProcessingFailed(e.to_string()))?;
+        tracing::info!(
+            "Recorded feedback for {} from {} (helpful: {})",
+            interaction_id,
+            interaction.user_id,
+            helpful
+        );
+
+        if let (false, Some(trigger_id)) = (helpful, interaction.trigger_id.as_deref()) {
+            let view = View {
+                private_metadata: interaction_id.to_string(),
+                ..Self::reason_modal()
+            };
+            match self.slack_api.open_view(trigger_id, &view).await {
+                Ok(_) => return Ok(()),
+                Err(e) => tracing::warn!("Failed to open feedback modal: {}", e),
+            }
+        }
+
+        if let Some(response_url) = interaction.response_url.as_deref() {
+            self.slack_api
+                .respond(
+                    response_url,
+                    &ResponseUrlMessage::ephemeral("フィードバックありがとうございます！", None),
+                )
+                .await
+                .map_err(|e| SlackError::MessageSendFailed(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// 役に立たなかった理由を尋ねるモーダル
+    fn reason_modal() -> View {
+        let reasons = FeedbackReason::ALL
+            .into_iter()
+            .map(|reason| (reason.label().to_string(), reason.as_str().to_string()))
+            .collect();
+        View::modal(
+            FEEDBACK_REASON_CALLBACK_ID,
+            "回答へのフィードバック",
+            "送信",
+            vec![
+                Block::section("どこが良くなかったか教えてください。今後の回答の改善に使います。"),
+                Block::input(
+                    "理由",
+                    BlockElement::static_select(REASON_ACTION_ID, reasons),
+                    false,
+                ),
+                Block::input(
+                    "コメント",
+                    BlockElement::multiline_input(COMMENT_ACTION_ID, COMMENT_MAX_CHARS),
+                    true,
+                ),
+            ],
+        )
+    }
+
+    /// モーダルで送信された理由を記録する
+    pub async fn submit_reason(&self, interaction: &SlackInteraction) -> Result<(), SlackError> {
+        let view = interaction
+            .view
+            .as_ref()
+            .ok_or(SlackError::InvalidEventPayload)?;
+        let interaction_id = view
+            .private_metadata
+            .parse::<Uuid>()
+            .map_err(|_| SlackError::InvalidEventPayload)?;
+
+        let reason = view
+            .values
+            .get(REASON_ACTION_ID)
+            .and_then(|value| FeedbackReason::parse(value))
+            .unwrap_or(FeedbackReason::Other);
+        let comment = view
+            .values
+            .get(COMMENT_ACTION_ID)
+            .map(|comment| comment.trim())
+            .filter(|comment| !comment.is_empty());
+
+        let updated = self
+            .repository
+            .set_reason(interaction_id, &interaction.user_id, reason, comment)
+            .await
+            .map_err(|e| SlackError::EventProcessingFailed(e.to_string()))?;
+        if !updated {
+            tracing::warn!(
+                "No feedback to attach the reason to: {} from {}",
+                interaction_id,
+                interaction.user_id
+            );
+        }
+        Ok(())
+    }
+
+    /// ボットの回答へのリアクションを記録する（外された場合は取り消す）
+    ///
+    /// 評価に使うリアクション以外や、回答として記録していないメッセージへのリアクションは無視します。
+    pub async fn record_reaction(
+        &self,
+        user_id: &str,
+        reaction: &str,
+        channel_id: &str,
+        message_ts: &str,
+        added: bool,
+    ) -> Result<(), SlackError> {
+        let Some(helpful) = self.settings.rating_for_reaction(reaction) else {
+            return Ok(());
+        };
+        let log = self
+            .interaction_logs
+            .find_by_message(self.space_id, channel_id, message_ts)
+            .await
+            .map_err(|e| SlackError::EventProcessingFailed(e.to_string()))?;
+        let Some(log) = log else {
+            tracing::debug!("Reaction on a message without an answer log, ignoring");
+            return Ok(());
+        };
+
+        let result = if added {
+            let feedback = NewAnswerFeedback::new(
+                self.space_id,
+                log.id,
+                user_id,
+                FeedbackSource::Reaction,
+                helpful,
+            );
+            self.repository.record(&feedback).await
+        } else {
+            self.repository
+                .remove(log.id, user_id, FeedbackSource::Reaction, helpful)
+                .await
+                .map(|_| ())
+        };
+        result.map_err(|e| SlackError::EventProcessingFailed(e.to_string()))?;
+        tracing::info!(
+            "Recorded reaction :{}: on {} from {} (added: {})",
+            reaction,
+            log.id,
+            user_id,
+            added
+        );
+        Ok(())
+    }
+
+    /// /nokizaru feedback: 直近のフィードバックを集計する
+    pub async fn report(&self, days: u32) -> String {
+        let since = Utc::now() - Duration::days(days as i64);
+        match self.repository.summary(self.space_id, since).await {
+            Ok(summary) => Self::summary_report(days, &summary),
+            Err(e) => {
+                tracing::error!("❌ Failed to summarize feedback: {}", e);
+                "❌ フィードバックの集計に失敗しました".to_string()
+            }
+        }
+    }
+
+    /// フィードバックのレポート
+    fn summary_report(days: u32, summary: &FeedbackSummary) -> String {
+        let Some(helpfulness) = summary.helpfulness() else {
+            return format!("直近{}日の回答へのフィードバックはありません", days);
+        };
+
+        let mut lines = vec![
+            format!("*回答へのフィードバック（直近{}日）*", days),
+            format!(
+                "• 👍 {}件 / 👎 {}件（役に立った割合 {:.0}%）",
+                summary.helpful,
+                summary.not_helpful,
+                helpfulness * 100.0
+            ),
+        ];
+
+        if !summary.breakdown.is_empty() {
+            lines.push("*モデル・プロンプト別*".to_string());
+            for row in &summary.breakdown {
+                let total = row.helpful + row.not_helpful;
+                lines.push(format!(
+                    "• {} (prompt {}): 👍 {} / 👎 {}（{:.0}%）",
+                    row.model,
+                    row.prompt_version.as_deref().unwrap_or("-"),
+                    row.helpful,
+                    row.not_helpful,
+                    row.helpful as f64 / total.max(1) as f64 * 100.0
+                ));
+            }
+        }
+
+        if !summary.reasons.is_empty() {
+            lines.push("*役に立たなかった理由*".to_string());
+            for row in &summary.reasons {
+                let label = FeedbackReason::parse(&row.reason)
+                    .map(|reason| reason.label())
+                    .unwrap_or(row.reason.as_str());
+                lines.push(format!("• {}: {}件", label, row.count));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nokizaru_core::{FeedbackBreakdown, FeedbackReasonCount};
+
+    #[test]
+    fn test_summary_report() {
+        assert_eq!(
+            FeedbackService::summary_report(7, &FeedbackSummary::default()),
+            "直近7日の回答へのフィードバックはありません"
+        );
+
+        let summary = FeedbackSummary {
+            helpful: 3,
+            not_helpful: 1,
+            breakdown: vec![FeedbackBreakdown {
+                model: "gpt-4o-mini".to_string(),
+                prompt_version: Some("v2".to_string()),
+                helpful: 3,
+                not_helpful: 1,
+            }],
+            reasons: vec![FeedbackReasonCount {
+                reason: "outdated".to_string(),
+                count: 1,
+            }],
+        };
+        let report = FeedbackService::summary_report(7, &summary);
+        assert!(report.contains("• 👍 3件 / 👎 1件（役に立った割合 75%）"));
+        assert!(report.contains("• gpt-4o-mini (prompt v2): 👍 3 / 👎 1（75%）"));
+        assert!(report.contains("• 情報が古い: 1件"));
+    }
+}
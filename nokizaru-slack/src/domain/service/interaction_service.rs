@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use nokizaru_core::llm::UsageTags;
+
+use crate::slack_api::{Block, PostMessageRequest, ResponseUrlMessage, SlackApi};
+use crate::{
+    Audience, FeedbackService, SharedAnswer, SlackError, SlackInteraction, SummaryService,
+    FEEDBACK_HELPFUL_ACTION_ID, FEEDBACK_NOT_HELPFUL_ACTION_ID, FEEDBACK_REASON_CALLBACK_ID,
+};
+
+/// 「チャンネルに共有」ボタンの action_id
+pub const SHARE_ANSWER_ACTION_ID: &str = "share_answer";
+
+/// メッセージショートカット「スレッドを要約」の callback_id
+pub const SUMMARIZE_THREAD_CALLBACK_ID: &str = "summarize_thread";
+
+/// Slackインタラクション（ボタン操作・ショートカットなど）処理のドメインサービス
+pub struct InteractionService {
+    slack_api: Arc<SlackApi>,
+    summaries: Option<Arc<SummaryService>>,
+    feedback: Option<Arc<FeedbackService>>,
+}
+
+impl InteractionService {
+    pub fn new(slack_api: Arc<SlackApi>) -> Self {
+        Self {
+            slack_api,
+            summaries: None,
+            feedback: None,
+        }
+    }
+
+    /// 「スレッドを要約」で使う要約サービスを指定する
+    pub fn with_summaries(mut self, summaries: Arc<SummaryService>) -> Self {
+        self.summaries = Some(summaries);
+        self
+    }
+
+    /// 回答への 👍/👎 ボタンとモーダルを処理するフィードバックサービスを指定する
+    pub fn with_feedback(mut self, feedback: Arc<FeedbackService>) -> Self {
+        self.feedback = Some(feedback);
+        self
+    }
+
+    pub async fn execute(&self, interaction: SlackInteraction) -> Result<(), SlackError> {
+        match interaction.action_id.as_str() {
+            SHARE_ANSWER_ACTION_ID => self.share_answer(interaction).await,
+            SUMMARIZE_THREAD_CALLBACK_ID => self.summarize_thread(interaction).await,
+            FEEDBACK_HELPFUL_ACTION_ID | FEEDBACK_NOT_HELPFUL_ACTION_ID => {
+                let Some(feedback) = &self.feedback else {
+                    tracing::debug!("Feedback is not enabled, ignoring action");
+                    return Ok(());
+                };
+                let helpful = interaction.action_id == FEEDBACK_HELPFUL_ACTION_ID;
+                feedback.record_button(&interaction, helpful).await
+            }
+            FEEDBACK_REASON_CALLBACK_ID => match &self.feedback {
+                Some(feedback) => feedback.submit_reason(&interaction).await,
+                None => Ok(()),
+            },
+            _ => {
+                tracing::debug!("Ignoring unknown action: {}", interaction.action_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// エフェメラルな回答をチャンネルに投稿し、元のメッセージを削除する
+    async fn share_answer(&self, interaction: SlackInteraction) -> Result<(), SlackError> {
+        let value = interaction
+            .value
+            .as_deref()
+            .ok_or(SlackError::InvalidEventPayload)?;
+        let shared: SharedAnswer = serde_json::from_str(value)?;
+
+        let text = format!(
+            "<@{}> の質問: {}\n\n{}",
+            interaction.user_id, shared.question, shared.answer
+        );
+        let mut blocks = vec![
+            Block::context(vec![format!(
+                "<@{}> の質問: {}",
+                interaction.user_id, shared.question
+            )]),
+            Block::section(shared.answer),
+        ];
+        if let Some(sources) = shared.sources {
+            blocks.push(Block::context(vec![sources]));
+        }
+        let feedback = self.feedback.as_ref().zip(shared.interaction_id);
+        if let Some((_, interaction_id)) = feedback {
+            blocks.push(FeedbackService::actions_block(interaction_id));
+        }
+
+        let posted = self
+            .slack_api
+            .post_message(&PostMessageRequest {
+                channel_id: interaction.channel_id,
+                text,
+                thread_ts: None,
+                blocks: Some(blocks),
+            })
+            .await
+            .map_err(|e| SlackError::MessageSendFailed(e.to_string()))?;
+        if let Some((feedback, interaction_id)) = feedback {
+            feedback.attach_message(interaction_id, &posted.ts).await;
+        }
+
+        if let Some(response_url) = interaction.response_url.as_deref() {
+            self.slack_api
+                .respond(response_url, &ResponseUrlMessage::delete_original())
+                .await
+                .map_err(|e| SlackError::MessageSendFailed(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// ショートカットを実行したスレッドを要約し、スレッドに投稿する
+    ///
+    /// 投稿できない場合（ボットが参加していないチャンネルなど）は実行したユーザーにだけ表示します。
+    async fn summarize_thread(&self, interaction: SlackInteraction) -> Result<(), SlackError> {
+        let Some(summaries) = &self.summaries else {
+            tracing::debug!("Summaries are not enabled, ignoring shortcut");
+            return Ok(());
+        };
+        let thread_ts = interaction
+            .value
+            .clone()
+            .ok_or(SlackError::InvalidEventPayload)?;
+
+        let tags = UsageTags::new(&interaction.channel_id, Some(interaction.user_id.clone()));
+        let audience = Audience::new(&interaction.user_id, &interaction.channel_id);
+        let result = tags
+            .scope(audience.scope(summaries.summarize_thread(
+                &interaction.user_id,
+                &interaction.channel_id,
+                &thread_ts,
+            )))
+            .await;
+
+        let message = match result {
+            Ok(text) => {
+                let posted = self
+                    .slack_api
+                    .post_message(&PostMessageRequest {
+                        channel_id: interaction.channel_id.clone(),
+                        text: text.clone(),
+                        thread_ts: Some(thread_ts),
+                        blocks: None,
+                    })
+                    .await;
+                match posted {
+                    Ok(_) => return Ok(()),
+                    Err(e) => {
+                        tracing::warn!("Failed to post thread summary: {}", e);
+                        ResponseUrlMessage::ephemeral(text, None)
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!("❌ Thread summary failed: {}", e);
+                ResponseUrlMessage::ephemeral("❌ 要約の作成に失敗しました", None)
+            }
+        };
+
+        if let Some(response_url) = interaction.response_url.as_deref() {
+            self.slack_api
+                .respond(response_url, &message)
+                .await
+                .map_err(|e| SlackError::MessageSendFailed(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
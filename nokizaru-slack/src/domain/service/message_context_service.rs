@@ -1,147 +1,215 @@
 use crate::{
     slack_api::{MessageContext, SlackHistoryMessage, SlackMessage},
-    SlackError,
+    ChannelAccessService, ContextAssembler, ContextSource, MessageSearchRepository,
+    RetrievedContext, SlackError,
 };
 use anyhow::Result;
+use futures::future::join_all;
+use nokizaru_core::RetrievalSettings;
+use std::collections::HashMap;
 use std::env;
+use std::sync::Arc;
 
 pub struct MessageContextService {
-    api: crate::slack_api::SlackApi,
+    api: Arc<dyn MessageSearchRepository>,
+    retrieval: RetrievalSettings,
+    assembler: ContextAssembler,
+    access: Option<Arc<ChannelAccessService>>,
+}
+
+impl Default for MessageContextService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MessageContextService {
     pub fn new() -> Self {
         let user_token = env::var("SLACK_USER_TOKEN").unwrap();
-        let api = crate::slack_api::SlackApi::new(user_token);
-        Self { api }
+        Self::with_repository(Arc::new(crate::slack_api::SlackApi::new(user_token)))
     }
 
-    /// Format a single message for LLM consumption
-    fn format_message(msg: &SlackMessage) -> String {
-        let user = msg
-            .username
-            .as_deref()
-            .or_else(|| msg.user.as_deref())
-            .unwrap_or("unknown");
-
-        format!("[{}] {}: {}", msg.ts, user, msg.text)
+    /// 検索先を指定して作成する（評価では記録したワークスペースを使う）
+    pub fn with_repository(api: Arc<dyn MessageSearchRepository>) -> Self {
+        Self {
+            api,
+            retrieval: RetrievalSettings::default(),
+            assembler: ContextAssembler::default(),
+            access: None,
+        }
     }
 
-    /// Format a single history message for LLM consumption
-    fn format_history_message(msg: &SlackHistoryMessage) -> String {
-        let user = msg.user.as_deref().unwrap_or("unknown");
-        format!("[{}] {}: {}", msg.ts, user, msg.text)
+    /// 検索設定を指定する
+    pub fn with_retrieval(mut self, retrieval: RetrievalSettings) -> Self {
+        self.retrieval = retrieval;
+        self
     }
 
-    /// Format contexts into a clear message sequence for LLM input
-    fn format_for_llm(contexts: Vec<MessageContext>) -> String {
-        let mut output = String::new();
-        let mut seen_messages = std::collections::HashSet::new();
+    /// コンテキストの組み立て方（トークン数の見積もり・スレッドの要約）を指定する
+    pub fn with_assembler(mut self, assembler: ContextAssembler) -> Self {
+        self.assembler = assembler;
+        self
+    }
 
-        for (idx, context) in contexts.iter().enumerate() {
-            if idx > 0 {
-                output.push_str("\n---\n");
-            }
+    /// 検索結果を質問したユーザーが閲覧できるものに限定する
+    pub fn with_access(mut self, access: Arc<ChannelAccessService>) -> Self {
+        self.access = Some(access);
+        self
+    }
 
-            // Get channel info from target message
-            let channel_name = context
-                .target_message
-                .channel
-                .as_ref()
-                .and_then(|c| c.name.as_deref())
-                .unwrap_or("unknown");
+    /// 重複判定のキー（ts はチャンネルをまたぐと一意でないため channel と組にする）
+    fn message_key(msg: &SlackMessage) -> (String, String) {
+        let channel = msg
+            .channel
+            .as_ref()
+            .and_then(|c| c.id.clone())
+            .unwrap_or_default();
+        (channel, msg.ts.clone())
+    }
 
-            output.push_str(&format!("#{}:\n", channel_name));
+    /// 複数の検索結果を reciprocal rank fusion で統合する
+    ///
+    /// 各結果リストの順位 r（1始まり）に対して `weight / (k + r)` を合計し、
+    /// スコアの高い順に最大 `limit` 件を返します。
+    fn reciprocal_rank_fusion(
+        ranked_lists: Vec<(f64, Vec<SlackMessage>)>,
+        k: f64,
+        limit: usize,
+    ) -> Vec<SlackMessage> {
+        let mut fused: HashMap<(String, String), (f64, usize, SlackMessage)> = HashMap::new();
+        let mut order = 0;
+
+        for (weight, messages) in ranked_lists {
+            for (rank, msg) in messages.into_iter().enumerate() {
+                let score = weight / (k + (rank + 1) as f64);
+                fused
+                    .entry(Self::message_key(&msg))
+                    .and_modify(|entry| entry.0 += score)
+                    .or_insert_with(|| {
+                        order += 1;
+                        (score, order, msg)
+                    });
+            }
+        }
 
-            // Collect all messages in chronological order
-            let mut all_messages = Vec::new();
+        let mut fused: Vec<_> = fused.into_values().collect();
+        // 同点の場合は先に現れた結果を優先する
+        fused.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        fused.into_iter().take(limit).map(|(_, _, msg)| msg).collect()
+    }
 
-            // Before messages
-            for msg in &context.before_messages {
-                if seen_messages.insert(msg.ts.clone()) {
-                    all_messages.push((msg.ts.as_str(), msg, false));
-                }
-            }
+    /// Format a single history message for LLM consumption
+    pub(crate) fn format_history_message(msg: &SlackHistoryMessage) -> String {
+        let user = msg.user.as_deref().unwrap_or("unknown");
+        format!("[{}] {}: {}", msg.ts, user, msg.text)
+    }
 
-            // Target message - use a different formatting approach
-            let target_ts = context.target_message.ts.as_str();
-            seen_messages.insert(target_ts.to_string());
-            let target_formatted = Self::format_message(&context.target_message);
+    /// Format the messages of a single thread for LLM consumption
+    pub fn format_thread(messages: &[SlackHistoryMessage]) -> String {
+        messages
+            .iter()
+            .map(|msg| format!("{}\n", Self::format_history_message(msg)))
+            .collect()
+    }
 
-            // After messages
-            for msg in &context.after_messages {
-                if seen_messages.insert(msg.ts.clone()) {
-                    all_messages.push((msg.ts.as_str(), msg, false));
+    /// パーマリンクが未取得の出典について chat.getPermalink で取得する
+    ///
+    /// 取得に失敗した出典はリンクなしで表示されます。
+    pub async fn resolve_permalinks(&self, sources: &mut [ContextSource]) {
+        let pending = sources
+            .iter_mut()
+            .filter(|source| source.permalink.is_none() && !source.channel_id.is_empty())
+            .map(|source| async move {
+                match self.api.permalink(&source.channel_id, &source.ts).await {
+                    Ok(permalink) => source.permalink = Some(permalink),
+                    Err(e) => tracing::warn!(
+                        "Failed to get permalink for {}/{}: {}",
+                        source.channel_id,
+                        source.ts,
+                        e
+                    ),
                 }
-            }
+            });
 
-            // Sort by timestamp
-            all_messages.sort_by(|a, b| a.0.cmp(b.0));
+        join_all(pending).await;
+    }
 
-            // Format messages with target highlighted
-            for (ts, msg, _) in all_messages {
-                if ts == target_ts {
-                    output.push_str(&format!(">>> {}\n", target_formatted));
-                } else {
-                    output.push_str(&format!("{}\n", Self::format_history_message(msg)));
+    /// 全クエリを関連度順・新しい順で並列に検索し、RRF で統合する
+    async fn search_fused(&self, queries: &[String]) -> Result<Vec<SlackMessage>, SlackError> {
+        let limit = self.retrieval.per_query_limit;
+        let searches = queries.iter().flat_map(|query| {
+            [
+                ("score", self.retrieval.relevance_weight), // 関連度順
+                ("timestamp", self.retrieval.recency_weight), // 新しい順
+            ]
+            .into_iter()
+            .map(move |(sort, weight)| (query, sort, weight))
+        });
+
+        let results = join_all(searches.map(|(query, sort, weight)| async move {
+            let result = self.api.search_messages(query, limit, sort).await;
+            (query, sort, weight, result)
+        }))
+        .await;
+
+        let mut ranked_lists = Vec::new();
+        let mut last_error = None;
+        for (query, sort, weight, result) in results {
+            match result {
+                // 統合で上位に残った結果が除かれて件数が減らないよう、統合前に除く
+                Ok(messages) => ranked_lists.push((
+                    weight,
+                    match &self.access {
+                        Some(access) => access.filter_messages(messages).await,
+                        None => messages,
+                    },
+                )),
+                Err(e) => {
+                    tracing::debug!("Search failed for query {:?}", query);
+                    tracing::warn!("Search failed (sort: {}): {}", sort, e);
+                    last_error = Some(e);
                 }
             }
+        }
 
-            // Thread replies (if any)
-            if !context.threads.is_empty() {
-                output.push_str("\nThreads:\n");
-                for thread in &context.threads {
-                    for reply in &thread.replies {
-                        if seen_messages.insert(reply.ts.clone()) {
-                            output
-                                .push_str(&format!("  {}\n", Self::format_history_message(reply)));
-                        }
-                    }
-                }
+        // 一部の検索が失敗しても、残りの結果で続行する
+        if ranked_lists.is_empty() {
+            if let Some(e) = last_error {
+                return Err(SlackError::ApiError(format!(
+                    "Failed to search messages: {}",
+                    e
+                )));
             }
         }
 
-        output
+        Ok(Self::reciprocal_rank_fusion(
+            ranked_lists,
+            self.retrieval.rrf_k,
+            self.retrieval.max_results,
+        ))
     }
 
-    /// 統合検索: 関連度5件 + 新しい順5件 + 前後3件 + スレッド
+    /// 統合検索: 全クエリ × (関連度順 + 新しい順) を RRF で統合 + 前後3件 + スレッド
     pub async fn search_with_full_context(
         &self,
-        query: &str,
+        queries: &[String],
     ) -> Result<Vec<MessageContext>, SlackError> {
-        println!("🔍 Step 1: Searching messages...");
+        tracing::debug!("Searching messages for {:?}", queries);
 
-        // 並列実行: 関連度順と新しい順を同時に検索
-        let (relevance_results, recency_results) = tokio::join!(
-            self.api.search_messages(query, "5", "score"), // 関連度順
-            self.api.search_messages(query, "5", "timestamp"), // 新しい順
-        );
+        let all_messages = self.search_fused(queries).await?;
 
-        let relevance_msgs = relevance_results
-            .map_err(|e| SlackError::ApiError(format!("Failed to search by relevance: {}", e)))?;
-        let recency_msgs = recency_results
-            .map_err(|e| SlackError::ApiError(format!("Failed to search by recency: {}", e)))?;
-
-        let mut all_messages = relevance_msgs;
-        all_messages.extend(recency_msgs);
-
-        // 重複除去（ts でユニーク化）
-        let mut seen = std::collections::HashSet::new();
-        all_messages.retain(|msg| seen.insert(msg.ts.clone()));
-
-        println!("   ✓ Found {} unique messages", all_messages.len());
+        tracing::debug!("Found {} unique messages", all_messages.len());
 
         if all_messages.is_empty() {
             return Ok(vec![]);
         }
 
-        // 検索結果のメッセージ一覧を表示
-        println!("\n   📋 Search Results:");
+        // 検索結果のメッセージ一覧を出力
         for (i, msg) in all_messages.iter().enumerate() {
             let user = msg
                 .username
                 .as_deref()
-                .or_else(|| msg.user.as_deref())
+                .or(msg.user.as_deref())
                 .unwrap_or("unknown");
             let text = &msg.text;
             let ts = &msg.ts;
@@ -160,8 +228,8 @@ impl MessageContextService {
                 text.to_string()
             };
 
-            println!(
-                "      [{}/{}] #{} [{}] {}: {}",
+            tracing::debug!(
+                "[{}/{}] #{} [{}] {}: {}",
                 i + 1,
                 all_messages.len(),
                 channel,
@@ -171,8 +239,6 @@ impl MessageContextService {
             );
         }
 
-        println!("\n🔍 Step 2: Fetching context for each message...");
-
         let total_messages = all_messages.len();
 
         // タイムアウト付きで各メッセージのコンテキストを取得
@@ -186,28 +252,29 @@ impl MessageContextService {
                 .ok_or_else(|| SlackError::ApiError("Missing channel ID".to_string()))?;
             let message_ts = &msg.ts;
 
-            println!("   [{}/{}] Processing message...", idx, total_messages);
-
             // タイムアウト付きで前後のメッセージを取得
             let around_result = tokio::time::timeout(
                 std::time::Duration::from_secs(10),
-                self.api.get_messages_around(channel_id, message_ts),
+                self.api.messages_around(channel_id, message_ts),
             )
             .await;
 
             let around = match around_result {
                 Ok(Ok(data)) => data,
                 Ok(Err(e)) => {
-                    eprintln!(
-                        "   [{}/{}] Error getting messages around: {}",
-                        idx, total_messages, e
+                    tracing::warn!(
+                        "[{}/{}] Error getting messages around: {}",
+                        idx,
+                        total_messages,
+                        e
                     );
                     continue; // エラーの場合はスキップ
                 }
                 Err(_) => {
-                    eprintln!(
-                        "   [{}/{}] Timeout getting messages around",
-                        idx, total_messages
+                    tracing::warn!(
+                        "[{}/{}] Timeout getting messages around",
+                        idx,
+                        total_messages
                     );
                     continue; // タイムアウトの場合はスキップ
                 }
@@ -219,27 +286,22 @@ impl MessageContextService {
 
             let threads_result = tokio::time::timeout(
                 std::time::Duration::from_secs(10),
-                self.api.get_threads_batch(channel_id, &all_msgs),
+                self.api.threads(channel_id, &all_msgs),
             )
             .await;
 
             let threads = match threads_result {
                 Ok(Ok(data)) => data,
                 Ok(Err(e)) => {
-                    eprintln!(
-                        "   [{}/{}] Error getting threads: {}",
-                        idx, total_messages, e
-                    );
+                    tracing::warn!("[{}/{}] Error getting threads: {}", idx, total_messages, e);
                     Vec::new() // エラーの場合は空のベクター
                 }
                 Err(_) => {
-                    eprintln!("   [{}/{}] Timeout getting threads", idx, total_messages);
+                    tracing::warn!("[{}/{}] Timeout getting threads", idx, total_messages);
                     Vec::new() // タイムアウトの場合は空のベクター
                 }
             };
 
-            println!("   [{}/{}] ✓ Completed", idx, total_messages);
-
             contexts.push(MessageContext {
                 target_message: msg.clone(),
                 before_messages: around.before,
@@ -248,22 +310,66 @@ impl MessageContextService {
             });
         }
 
-        println!(
-            "   ✓ Successfully fetched context for {} messages",
-            contexts.len()
-        );
+        tracing::debug!("Fetched context for {} messages", contexts.len());
 
         Ok(contexts)
     }
 
-    pub async fn execute(&self, query: &str) -> Result<String, SlackError> {
-        let contexts = self.search_with_full_context(query).await?;
+    pub async fn execute(&self, queries: &[String]) -> Result<RetrievedContext, SlackError> {
+        let contexts = self.search_with_full_context(queries).await?;
+        Ok(self.assemble(contexts).await)
+    }
 
-        // Format contexts for LLM input
-        let formatted = Self::format_for_llm(contexts);
+    /// 検索結果をトークン数の予算内で LLM 向けに組み立てる
+    pub async fn assemble(&self, contexts: Vec<MessageContext>) -> RetrievedContext {
+        let formatted = self.assembler.assemble(contexts, &self.retrieval).await;
 
-        println!("\n📝 Formatted for LLM:\n{}", formatted);
+        tracing::debug!(
+            "Formatted for LLM: ~{} tokens, {} messages",
+            self.assembler.count_tokens(&formatted.text),
+            formatted.sources.len()
+        );
+
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slack_api::ChannelInfo;
+
+    fn message(channel: &str, ts: &str) -> SlackMessage {
+        SlackMessage {
+            msg_type: "message".to_string(),
+            user: None,
+            bot_id: None,
+            text: String::new(),
+            ts: ts.to_string(),
+            channel: Some(ChannelInfo {
+                id: Some(channel.to_string()),
+                name: None,
+            }),
+            username: None,
+            permalink: None,
+        }
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let relevance = vec![message("C1", "1"), message("C1", "2"), message("C2", "1")];
+        let recency = vec![message("C1", "3"), message("C1", "2")];
+
+        let fused = MessageContextService::reciprocal_rank_fusion(
+            vec![(1.0, relevance), (1.0, recency)],
+            60.0,
+            10,
+        );
+        let keys: Vec<_> = fused.iter().map(MessageContextService::message_key).collect();
 
-        Ok(formatted)
+        // 両方に現れた C1/2 が最上位、同じ ts でもチャンネルが違えば別メッセージ
+        assert_eq!(keys.len(), 4);
+        assert_eq!(keys[0], ("C1".to_string(), "2".to_string()));
+        assert!(keys.contains(&("C2".to_string(), "1".to_string())));
     }
 }
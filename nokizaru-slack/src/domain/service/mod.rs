@@ -1,7 +1,25 @@
 pub mod event_service;
 pub mod message_context_service;
 pub mod command_service;
+pub mod answer_service;
+pub mod interaction_service;
+pub mod slack_tools;
+pub mod context_assembler;
+pub mod channel_access_service;
+pub mod summary_service;
+pub mod digest_service;
+pub mod scheduled_message_job;
+pub mod feedback_service;
 
 pub use event_service::*;
 pub use message_context_service::*;
 pub use command_service::*;
+pub use answer_service::*;
+pub use interaction_service::*;
+pub use slack_tools::*;
+pub use context_assembler::*;
+pub use channel_access_service::*;
+pub use summary_service::*;
+pub use digest_service::*;
+pub use scheduled_message_job::*;
+pub use feedback_service::*;
@@ -0,0 +1,61 @@
+//! 定期投稿のジョブ
+//!
+//! 定期実行ジョブ `post_message` のハンドラーです。ペイロードの `channel_id` に `text` を投稿します。
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use nokizaru_core::{JobHandler, ScheduledJob};
+use serde::Deserialize;
+
+use crate::slack_api::{PostMessageRequest, SlackApi};
+
+/// 定期投稿のジョブの種類
+pub const POST_MESSAGE_JOB: &str = "post_message";
+
+/// `post_message` のペイロード
+#[derive(Debug, Clone, Deserialize)]
+struct PostMessagePayload {
+    channel_id: String,
+    text: String,
+}
+
+impl PostMessagePayload {
+    fn from_value(payload: &serde_json::Value) -> anyhow::Result<Self> {
+        let payload: Self = serde_json::from_value(payload.clone())?;
+        anyhow::ensure!(!payload.channel_id.is_empty(), "channel_id is empty");
+        anyhow::ensure!(!payload.text.trim().is_empty(), "text is empty");
+        Ok(payload)
+    }
+}
+
+/// ペイロードのメッセージをチャンネルに投稿する定期実行ジョブ
+pub struct ScheduledMessageJob {
+    slack_api: Arc<SlackApi>,
+}
+
+impl ScheduledMessageJob {
+    pub fn new(slack_api: Arc<SlackApi>) -> Self {
+        Self { slack_api }
+    }
+}
+
+#[async_trait]
+impl JobHandler for ScheduledMessageJob {
+    fn validate(&self, payload: &serde_json::Value) -> anyhow::Result<()> {
+        PostMessagePayload::from_value(payload).map(|_| ())
+    }
+
+    async fn run(&self, job: &ScheduledJob) -> anyhow::Result<()> {
+        let payload = PostMessagePayload::from_value(&job.payload)?;
+        self.slack_api
+            .post_message(&PostMessageRequest {
+                channel_id: payload.channel_id,
+                text: payload.text,
+                thread_ts: None,
+                blocks: None,
+            })
+            .await?;
+        Ok(())
+    }
+}
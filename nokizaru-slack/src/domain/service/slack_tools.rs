@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use rig::{completion::ToolDefinition, tool::Tool, tool::ToolSet};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::slack_api::{
+    error::SlackError, ChannelInfo, MessageContext, SlackApi, SlackChannelInfo,
+    SlackHistoryMessage, SlackMessage, SlackUser,
+};
+use crate::ChannelAccessService;
+
+/// エージェントモードで使う Slack のツール一式
+///
+/// 検索（search.messages）を使うため、ユーザートークンの [`SlackApi`] を渡してください。
+/// 検索結果やチャンネルの参照は `access` で質問したユーザーが閲覧できるものに限定します。
+pub fn slack_toolset(api: Arc<SlackApi>, access: Arc<ChannelAccessService>) -> ToolSet {
+    let mut tools = ToolSet::default();
+    tools.add_tool(SearchMessagesTool {
+        api: api.clone(),
+        access: access.clone(),
+    });
+    tools.add_tool(GetMessagesAroundTool {
+        api: api.clone(),
+        access: access.clone(),
+    });
+    tools.add_tool(GetThreadTool {
+        api: api.clone(),
+        access: access.clone(),
+    });
+    tools.add_tool(LookupUserTool { api: api.clone() });
+    tools.add_tool(GetChannelInfoTool { api, access });
+    tools
+}
+
+/// 回答に使えないチャンネルの参照を拒否する
+async fn ensure_allowed(access: &ChannelAccessService, channel_id: &str) -> Result<(), SlackError> {
+    if access.is_allowed(channel_id).await {
+        Ok(())
+    } else {
+        Err(SlackError::ApiError(format!(
+            "Channel {} is not available for this answer",
+            channel_id
+        )))
+    }
+}
+
+fn definition<T: JsonSchema>(name: &str, description: &str) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        description: description.to_string(),
+        parameters: serde_json::to_value(schema_for!(T)).unwrap_or_default(),
+    }
+}
+
+/// ツールが返すメッセージ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolMessage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_name: Option<String>,
+    pub ts: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+}
+
+impl From<SlackHistoryMessage> for ToolMessage {
+    fn from(msg: SlackHistoryMessage) -> Self {
+        Self {
+            channel_id: None,
+            channel_name: None,
+            ts: msg.ts,
+            user: msg.user,
+            text: msg.text,
+            permalink: None,
+        }
+    }
+}
+
+impl ToolMessage {
+    /// 検索結果と同じ形にする（出典の候補として組み立てるため）
+    ///
+    /// チャンネルがわからないメッセージは出典にできないため None を返します。
+    pub fn into_context(self) -> Option<MessageContext> {
+        let channel_id = self.channel_id?;
+        Some(MessageContext {
+            target_message: SlackMessage {
+                msg_type: "message".to_string(),
+                user: None,
+                bot_id: None,
+                text: self.text,
+                ts: self.ts,
+                channel: Some(ChannelInfo {
+                    id: Some(channel_id),
+                    name: self.channel_name,
+                }),
+                username: self.user,
+                permalink: self.permalink,
+            },
+            before_messages: Vec::new(),
+            after_messages: Vec::new(),
+            threads: Vec::new(),
+        })
+    }
+}
+
+/// メッセージ検索ツール
+pub struct SearchMessagesTool {
+    api: Arc<SlackApi>,
+    access: Arc<ChannelAccessService>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchMessagesArgs {
+    #[schemars(
+        description = "Slack search query. Supports modifiers such as in:#channel, from:@user, after:YYYY-MM-DD."
+    )]
+    pub query: String,
+    #[schemars(description = "'score' for relevance (default) or 'timestamp' for newest first.")]
+    #[serde(default)]
+    pub sort: Option<String>,
+    #[schemars(description = "Number of results, 1 to 20. Defaults to 10.")]
+    #[serde(default)]
+    pub count: Option<u32>,
+}
+
+impl Tool for SearchMessagesTool {
+    const NAME: &'static str = "search_messages";
+    type Error = SlackError;
+    type Args = SearchMessagesArgs;
+    type Output = Vec<ToolMessage>;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        definition::<Self::Args>(
+            Self::NAME,
+            "Search Slack messages. Returns channel, timestamp (ts), author, text and permalink of each hit.",
+        )
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        let sort = match args.sort.as_deref() {
+            Some("timestamp") => "timestamp",
+            _ => "score",
+        };
+        let count = args.count.unwrap_or(10).clamp(1, 20).to_string();
+
+        let messages = self.api.search_messages(&args.query, &count, sort).await?;
+        let messages = self.access.filter_messages(messages).await;
+        Ok(messages
+            .into_iter()
+            .map(|msg| ToolMessage {
+                channel_id: msg.channel.as_ref().and_then(|c| c.id.clone()),
+                channel_name: msg.channel.as_ref().and_then(|c| c.name.clone()),
+                ts: msg.ts,
+                user: msg.username.or(msg.user),
+                text: msg.text,
+                permalink: msg.permalink,
+            })
+            .collect())
+    }
+}
+
+/// 前後のメッセージ取得ツール
+pub struct GetMessagesAroundTool {
+    api: Arc<SlackApi>,
+    access: Arc<ChannelAccessService>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MessageRefArgs {
+    #[schemars(description = "Channel ID, e.g. C0123456789.")]
+    pub channel_id: String,
+    #[schemars(description = "Message timestamp (ts), e.g. 1700000000.123456.")]
+    pub ts: String,
+}
+
+impl Tool for GetMessagesAroundTool {
+    const NAME: &'static str = "get_messages_around";
+    type Error = SlackError;
+    type Args = MessageRefArgs;
+    type Output = Vec<ToolMessage>;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        definition::<Self::Args>(
+            Self::NAME,
+            "Get the messages posted just before and after a message in the same channel, in chronological order.",
+        )
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        ensure_allowed(&self.access, &args.channel_id).await?;
+        let around = self
+            .api
+            .get_messages_around(&args.channel_id, &args.ts)
+            .await?;
+        Ok(around
+            .before
+            .into_iter()
+            .chain(around.after)
+            .map(ToolMessage::from)
+            .collect())
+    }
+}
+
+/// スレッド取得ツール
+pub struct GetThreadTool {
+    api: Arc<SlackApi>,
+    access: Arc<ChannelAccessService>,
+}
+
+impl Tool for GetThreadTool {
+    const NAME: &'static str = "get_thread_messages";
+    type Error = SlackError;
+    type Args = MessageRefArgs;
+    type Output = Vec<ToolMessage>;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        definition::<Self::Args>(
+            Self::NAME,
+            "Get all replies of a thread. Pass the ts of the thread's parent message.",
+        )
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        ensure_allowed(&self.access, &args.channel_id).await?;
+        let messages = self
+            .api
+            .get_thread_messages(&args.channel_id, &args.ts)
+            .await?;
+        Ok(messages.into_iter().map(ToolMessage::from).collect())
+    }
+}
+
+/// ユーザー検索ツール
+pub struct LookupUserTool {
+    api: Arc<SlackApi>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LookupUserArgs {
+    #[schemars(description = "User ID, e.g. U0123456789. Use this when known.")]
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[schemars(description = "Part of the user's name or display name.")]
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl Tool for LookupUserTool {
+    const NAME: &'static str = "lookup_user";
+    type Error = SlackError;
+    type Args = LookupUserArgs;
+    type Output = Vec<SlackUser>;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        definition::<Self::Args>(
+            Self::NAME,
+            "Look up Slack users by ID or name. Returns their names and job titles.",
+        )
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        if let Some(user_id) = args.user_id.as_deref() {
+            return Ok(vec![self.api.get_user_info(user_id).await?]);
+        }
+
+        let name = args
+            .name
+            .ok_or_else(|| SlackError::ApiError("user_id or name is required".to_string()))?
+            .to_lowercase();
+        let users = self.api.list_users().await?;
+        Ok(users
+            .into_iter()
+            .filter(|user| {
+                let display_name = user.profile.as_ref().and_then(|p| p.display_name.as_deref());
+                [Some(user.name.as_str()), user.real_name.as_deref(), display_name]
+                    .into_iter()
+                    .flatten()
+                    .any(|n| n.to_lowercase().contains(&name))
+            })
+            .take(10)
+            .collect())
+    }
+}
+
+/// チャンネル情報取得ツール
+pub struct GetChannelInfoTool {
+    api: Arc<SlackApi>,
+    access: Arc<ChannelAccessService>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ChannelArgs {
+    #[schemars(description = "Channel ID, e.g. C0123456789.")]
+    pub channel_id: String,
+}
+
+impl Tool for GetChannelInfoTool {
+    const NAME: &'static str = "get_channel_info";
+    type Error = SlackError;
+    type Args = ChannelArgs;
+    type Output = SlackChannelInfo;
+
+    async fn definition(&self, _prompt: String) -> ToolDefinition {
+        definition::<Self::Args>(
+            Self::NAME,
+            "Get a channel's name, topic, purpose and member count.",
+        )
+    }
+
+    async fn call(&self, args: Self::Args) -> Result<Self::Output, Self::Error> {
+        ensure_allowed(&self.access, &args.channel_id).await?;
+        self.api.get_channel_info(&args.channel_id).await
+    }
+}
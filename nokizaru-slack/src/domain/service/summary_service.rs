@@ -0,0 +1,452 @@
+//! チャンネル・スレッドの要約
+//!
+//! `/nokizaru summarize [#channel] [期間]` とメッセージショートカット「スレッドを要約」から使います。
+//! 履歴をページングして取得し、[`AgentService::summarize_conversation`] で決定事項・未解決の質問・
+//! 対応事項にまとめます。要約するチャンネルは検索結果と同じく [`ChannelAccessService`] で確認し、
+//! 非公開の会話は同じ会話か、ボットとの DM にだけ投稿します。
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use nokizaru_core::{chunk_lines, llm::TokenCounter, AgentService, LanguageSettings};
+
+use crate::slack_api::{SlackApi, SlackHistoryMessage};
+use crate::{ChannelAccessService, SlackError};
+
+/// 要約するチャンネルのメッセージの上限（親メッセージ）
+const MAX_MESSAGES: usize = 1000;
+/// 返信も含めて要約するスレッドの上限（新しいものから）
+const MAX_THREADS: usize = 20;
+/// 1スレッドから取得する返信の上限
+const MAX_THREAD_MESSAGES: usize = 500;
+/// 1回の要約で LLM に渡すトークン数
+const CHUNK_TOKENS: usize = 6000;
+/// ダイジェストに載せるスレッド・質問の件数
+const DIGEST_ITEMS: usize = 5;
+/// ダイジェストに載せるメッセージの冒頭の文字数
+const EXCERPT_CHARS: usize = 60;
+/// 指定できる最長の期間
+const MAX_SINCE_DAYS: i64 = 30;
+
+/// /nokizaru summarize の引数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummarizeArgs {
+    /// 要約するチャンネル（省略時は実行したチャンネル）
+    pub channel_id: Option<String>,
+    /// この時刻以降のメッセージを要約する
+    pub oldest: DateTime<Local>,
+    /// 期間の表示
+    pub label: String,
+}
+
+impl SummarizeArgs {
+    /// `[#channel] [期間]` を解析する
+    ///
+    /// 期間は `30m` / `6h` / `3d`、`today`・`yesterday`（その日の0時以降）、`2026-10-01` を受け付け、
+    /// 省略時は直近24時間です。
+    pub fn parse(text: &str, now: DateTime<Local>) -> Result<Self, String> {
+        let mut channel_id = None;
+        let mut since = None;
+        for word in text.split_whitespace() {
+            match word.strip_prefix("<#").and_then(|w| w.strip_suffix('>')) {
+                Some(escaped) if channel_id.is_none() => {
+                    let id = escaped.split('|').next().unwrap_or_default();
+                    channel_id = Some(id.to_string());
+                }
+                _ if since.is_none() => since = Some(word),
+                _ => return Err(format!("解釈できない引数です: {}", word)),
+            }
+        }
+
+        let (oldest, label) = match since {
+            None => (now - Duration::hours(24), "直近24時間".to_string()),
+            Some(since) => Self::parse_since(since, now).ok_or_else(|| {
+                format!(
+                    "期間は 6h・3d・yesterday・2026-10-01 のように指定してください: {}",
+                    since
+                )
+            })?,
+        };
+        if now - oldest > Duration::days(MAX_SINCE_DAYS) {
+            return Err(format!("期間は{}日以内で指定してください", MAX_SINCE_DAYS));
+        }
+
+        Ok(Self {
+            channel_id,
+            oldest,
+            label,
+        })
+    }
+
+    fn parse_since(since: &str, now: DateTime<Local>) -> Option<(DateTime<Local>, String)> {
+        let start_of = |date: NaiveDate| {
+            Local
+                .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+                .earliest()
+        };
+
+        match since {
+            "today" => return Some((start_of(now.date_naive())?, "今日".to_string())),
+            "yesterday" => {
+                let yesterday = now.date_naive().pred_opt()?;
+                return Some((start_of(yesterday)?, "昨日以降".to_string()));
+            }
+            _ => {}
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+            return Some((start_of(date)?, format!("{}以降", date)));
+        }
+
+        // 「3日」のような全角の単位でも落ちないよう、文字の境界で分ける
+        let (split, _) = since.char_indices().last()?;
+        let amount: i64 = since[..split].parse().ok().filter(|n| *n > 0)?;
+        let (duration, unit) = match &since[split..] {
+            "m" => (Duration::try_minutes(amount)?, "分"),
+            "h" => (Duration::try_hours(amount)?, "時間"),
+            "d" => (Duration::try_days(amount)?, "日"),
+            _ => return None,
+        };
+        Some((now.checked_sub_signed(duration)?, format!("直近{}{}", amount, unit)))
+    }
+}
+
+/// チャンネル・スレッドを要約するドメインサービス
+pub struct SummaryService {
+    agent_service: Arc<AgentService>,
+    slack_api: Arc<SlackApi>,
+    token_counter: Arc<dyn TokenCounter>,
+    access: Option<Arc<ChannelAccessService>>,
+    language: LanguageSettings,
+}
+
+impl SummaryService {
+    pub fn new(
+        agent_service: Arc<AgentService>,
+        slack_api: Arc<SlackApi>,
+        token_counter: Arc<dyn TokenCounter>,
+    ) -> Self {
+        Self {
+            agent_service,
+            slack_api,
+            token_counter,
+            access: None,
+            language: LanguageSettings::default(),
+        }
+    }
+
+    /// 要約するチャンネルを実行したユーザーが閲覧できるか確認する
+    pub fn with_access(mut self, access: Arc<ChannelAccessService>) -> Self {
+        self.access = Some(access);
+        self
+    }
+
+    /// 要約の言語
+    pub fn with_language(mut self, language: LanguageSettings) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// チャンネルの `oldest` 以降のメッセージを要約し、投稿する mrkdwn を返す
+    ///
+    /// [`crate::Audience::scope`] の中で呼び出してください。
+    pub async fn summarize_channel(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+        oldest: DateTime<Utc>,
+        label: &str,
+    ) -> Result<String, SlackError> {
+        if let Some(message) = self.reject(channel_id).await {
+            return Ok(message);
+        }
+
+        let (messages, lines) = self.channel_transcript(channel_id, oldest).await?;
+        if messages.is_empty() {
+            return Ok(format!(
+                "<#{}> の{}のメッセージはありません",
+                channel_id, label
+            ));
+        }
+
+        let title = format!(
+            "<#{}> の要約（{}、{}件）",
+            channel_id,
+            label,
+            messages.len()
+        );
+        let mut text = self.summarize(user_id, channel_id, &title, &lines).await?;
+        if messages.len() >= MAX_MESSAGES {
+            text.push_str(&format!(
+                "\n\n_新しい{}件のメッセージだけを要約しました_",
+                MAX_MESSAGES
+            ));
+        }
+        Ok(text)
+    }
+
+    /// チャンネルの `oldest` 以降のダイジェスト（要約・盛り上がったスレッド・未回答の質問）を返す
+    ///
+    /// メッセージがない場合は None を返します。[`crate::Audience::scope`] の中で呼び出してください。
+    pub async fn digest_channel(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+        oldest: DateTime<Utc>,
+        label: &str,
+    ) -> Result<Option<String>, SlackError> {
+        if let Some(message) = self.reject(channel_id).await {
+            return Ok(Some(message));
+        }
+
+        let (messages, lines) = self.channel_transcript(channel_id, oldest).await?;
+        if messages.is_empty() {
+            return Ok(None);
+        }
+
+        let title = format!(
+            "<#{}> のダイジェスト（{}、{}件）",
+            channel_id,
+            label,
+            messages.len()
+        );
+        let mut sections = vec![self.summarize(user_id, channel_id, &title, &lines).await?];
+
+        let mut threads: Vec<&SlackHistoryMessage> =
+            messages.iter().filter(|msg| msg.reply_count > 0).collect();
+        threads.sort_by_key(|msg| std::cmp::Reverse(msg.reply_count));
+        let threads: Vec<String> = self
+            .links(channel_id, threads.into_iter().take(DIGEST_ITEMS))
+            .await
+            .into_iter()
+            .map(|(msg, link)| format!("• {}（返信 {}件）", link, msg.reply_count))
+            .collect();
+        if !threads.is_empty() {
+            sections.push(format!("*盛り上がったスレッド*\n{}", threads.join("\n")));
+        }
+
+        // 返信のない質問（bot の投稿は除く）
+        let unanswered = messages
+            .iter()
+            .rev()
+            .filter(|msg| msg.user.is_some() && msg.reply_count == 0)
+            .filter(|msg| msg.text.contains('?') || msg.text.contains('？'))
+            .take(DIGEST_ITEMS);
+        let unanswered: Vec<String> = self
+            .links(channel_id, unanswered)
+            .await
+            .into_iter()
+            .map(|(_, link)| format!("• {}", link))
+            .collect();
+        if !unanswered.is_empty() {
+            sections.push(format!("*未回答の質問*\n{}", unanswered.join("\n")));
+        }
+
+        Ok(Some(sections.join("\n\n")))
+    }
+
+    /// メッセージの冒頭をパーマリンク付きで表示する（パーマリンクを取得できない場合は冒頭のみ）
+    async fn links<'a>(
+        &self,
+        channel_id: &str,
+        messages: impl Iterator<Item = &'a SlackHistoryMessage>,
+    ) -> Vec<(&'a SlackHistoryMessage, String)> {
+        let mut links = Vec::new();
+        for msg in messages {
+            let mut excerpt: String = msg
+                .text
+                .replace('\n', " ")
+                .chars()
+                .take(EXCERPT_CHARS)
+                .collect();
+            if excerpt.chars().count() < msg.text.chars().count() {
+                excerpt.push('…');
+            }
+            let link = match self.slack_api.get_permalink(channel_id, &msg.ts).await {
+                Ok(permalink) => {
+                    format!("<{}|{}>", permalink, excerpt.replace(['<', '>', '|'], " "))
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to get permalink: {}", e);
+                    excerpt
+                }
+            };
+            links.push((msg, link));
+        }
+        links
+    }
+
+    /// チャンネルの `oldest` 以降のメッセージ（古い順）と、返信を展開した要約用の行
+    async fn channel_transcript(
+        &self,
+        channel_id: &str,
+        oldest: DateTime<Utc>,
+    ) -> Result<(Vec<SlackHistoryMessage>, Vec<String>), SlackError> {
+        let oldest_ts = format!("{}.000000", oldest.timestamp());
+        let messages = self
+            .slack_api
+            .get_channel_history_since(channel_id, &oldest_ts, MAX_MESSAGES)
+            .await
+            .map_err(|e| SlackError::ApiError(format!("Failed to fetch history: {}", e)))?;
+
+        // 返信のあるスレッドは新しいものから上限まで展開する
+        let mut expand: Vec<&str> = messages
+            .iter()
+            .rev()
+            .filter(|msg| msg.reply_count > 0)
+            .take(MAX_THREADS)
+            .map(|msg| msg.ts.as_str())
+            .collect();
+        expand.sort_unstable();
+
+        let mut lines = Vec::new();
+        for msg in &messages {
+            lines.push(Self::line(msg, ""));
+            if expand.binary_search(&msg.ts.as_str()).is_err() {
+                continue;
+            }
+            match self
+                .slack_api
+                .get_all_thread_messages(channel_id, &msg.ts, MAX_THREAD_MESSAGES)
+                .await
+            {
+                Ok(replies) => lines.extend(
+                    replies
+                        .iter()
+                        .filter(|reply| reply.ts != msg.ts)
+                        .map(|reply| Self::line(reply, "  ↳ ")),
+                ),
+                Err(e) => tracing::warn!("Failed to fetch thread {}: {}", msg.ts, e),
+            }
+        }
+
+        Ok((messages, lines))
+    }
+
+    /// スレッドを要約し、投稿する mrkdwn を返す
+    ///
+    /// [`crate::Audience::scope`] の中で呼び出してください。
+    pub async fn summarize_thread(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+        thread_ts: &str,
+    ) -> Result<String, SlackError> {
+        if let Some(message) = self.reject(channel_id).await {
+            return Ok(message);
+        }
+
+        let messages = self
+            .slack_api
+            .get_all_thread_messages(channel_id, thread_ts, MAX_THREAD_MESSAGES)
+            .await
+            .map_err(|e| SlackError::ApiError(format!("Failed to fetch thread: {}", e)))?;
+        let lines: Vec<String> = messages.iter().map(|msg| Self::line(msg, "")).collect();
+
+        let title = format!("スレッドの要約（{}件）", messages.len());
+        self.summarize(user_id, channel_id, &title, &lines).await
+    }
+
+    /// 現在の相手（[`crate::Audience`]）がチャンネルを要約できない場合は、その旨のメッセージを返す
+    pub async fn reject(&self, channel_id: &str) -> Option<String> {
+        let access = self.access.as_ref()?;
+        if access.is_allowed(channel_id).await {
+            return None;
+        }
+        tracing::info!("Refusing to summarize {} here", channel_id);
+        Some(format!(
+            "<#{}> はここでは要約できません（参加していない非公開の会話は、その会話の中かボットとの DM でのみ要約できます）",
+            channel_id
+        ))
+    }
+
+    async fn summarize(
+        &self,
+        user_id: &str,
+        channel_id: &str,
+        title: &str,
+        lines: &[String],
+    ) -> Result<String, SlackError> {
+        let chunks = chunk_lines(lines, self.token_counter.as_ref(), CHUNK_TOKENS);
+        let sample: String = lines.iter().take(20).map(String::as_str).collect();
+        let language = self.language.resolve(user_id, channel_id, &sample);
+        tracing::info!(
+            "Summarizing {} lines in {} chunks",
+            lines.len(),
+            chunks.len()
+        );
+
+        let summary = self
+            .agent_service
+            .summarize_conversation(&chunks, language)
+            .await
+            .map_err(|e| {
+                SlackError::EventProcessingFailed(format!("Failed to summarize: {}", e))
+            })?;
+        Ok(summary.to_mrkdwn(title))
+    }
+
+    /// 要約に渡す1行（`[10/18 09:30] <@U0123>: 本文`）
+    fn line(msg: &SlackHistoryMessage, prefix: &str) -> String {
+        let time = msg
+            .ts
+            .split('.')
+            .next()
+            .and_then(|secs| secs.parse::<i64>().ok())
+            .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+            .map(|time| time.format("%m/%d %H:%M").to_string())
+            .unwrap_or_default();
+        let author = match (&msg.user, &msg.bot_id) {
+            (Some(user), _) => format!("<@{}>", user),
+            (None, Some(_)) => "bot".to_string(),
+            (None, None) => "unknown".to_string(),
+        };
+        format!(
+            "{}[{}] {}: {}",
+            prefix,
+            time,
+            author,
+            msg.text.replace('\n', " ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_summarize_args() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 15, 30, 0).unwrap();
+
+        let args = SummarizeArgs::parse("", now).unwrap();
+        assert_eq!(args.channel_id, None);
+        assert_eq!(args.oldest, now - Duration::hours(24));
+
+        let args = SummarizeArgs::parse("<#C0123|incident> yesterday", now).unwrap();
+        assert_eq!(args.channel_id.as_deref(), Some("C0123"));
+        assert_eq!(
+            args.oldest,
+            Local.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap()
+        );
+        assert_eq!(args.label, "昨日以降");
+
+        let args = SummarizeArgs::parse("3d <#C0456>", now).unwrap();
+        assert_eq!(args.channel_id.as_deref(), Some("C0456"));
+        assert_eq!(args.oldest, now - Duration::days(3));
+        assert_eq!(args.label, "直近3日");
+
+        let args = SummarizeArgs::parse("2026-10-01", now).unwrap();
+        assert_eq!(
+            args.oldest,
+            Local.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()
+        );
+
+        assert!(SummarizeArgs::parse("90d", now).is_err());
+        assert!(SummarizeArgs::parse("lastweek", now).is_err());
+        assert!(SummarizeArgs::parse("6h 3d", now).is_err());
+        // 全角の単位や桁あふれする期間は使い方のエラーにする
+        assert!(SummarizeArgs::parse("昨日", now).is_err());
+        assert!(SummarizeArgs::parse("3日", now).is_err());
+        assert!(SummarizeArgs::parse("99999999999999d", now).is_err());
+    }
+}
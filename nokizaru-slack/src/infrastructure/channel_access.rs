@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+
+use crate::slack_api::SlackApi;
+use crate::{BotDirectMessageRepository, ChannelAccessRepository, ConversationKind, SlackError};
+
+/// Slack API による [`ChannelAccessRepository`] の実装
+///
+/// users.conversations はトークンの所有者も参加している非公開の会話しか返さないため、
+/// 検索と同じユーザートークンを使ってください。
+#[async_trait]
+impl ChannelAccessRepository for SlackApi {
+    async fn conversation_kind(&self, channel_id: &str) -> Result<ConversationKind, SlackError> {
+        let info = self
+            .get_channel_info(channel_id)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))?;
+
+        Ok(if info.is_im {
+            ConversationKind::DirectMessage
+        } else if info.is_mpim {
+            ConversationKind::GroupDirectMessage
+        } else if info.is_private {
+            ConversationKind::Private
+        } else {
+            ConversationKind::Public
+        })
+    }
+
+    async fn user_conversations(&self, user_id: &str) -> Result<Vec<String>, SlackError> {
+        let channels = self
+            .get_user_conversations(user_id)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))?;
+
+        Ok(channels.into_iter().map(|channel| channel.id).collect())
+    }
+}
+
+/// Slack API による [`BotDirectMessageRepository`] の実装
+///
+/// conversations.open はトークンの所有者との DM を開くため、ボットトークンを使ってください。
+#[async_trait]
+impl BotDirectMessageRepository for SlackApi {
+    async fn bot_direct_message(&self, user_id: &str) -> Result<String, SlackError> {
+        self.open_direct_message(user_id)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))
+    }
+}
@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use crate::slack_api::{MessagesAround, SlackApi, SlackHistoryMessage, SlackMessage, ThreadInfo};
+use crate::{MessageSearchRepository, SlackError};
+
+/// Slack API による [`MessageSearchRepository`] の実装（search.messages にはユーザートークンが必要）
+#[async_trait]
+impl MessageSearchRepository for SlackApi {
+    async fn search_messages(
+        &self,
+        query: &str,
+        count: u32,
+        sort: &str,
+    ) -> Result<Vec<SlackMessage>, SlackError> {
+        SlackApi::search_messages(self, query, &count.to_string(), sort)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))
+    }
+
+    async fn messages_around(
+        &self,
+        channel_id: &str,
+        ts: &str,
+    ) -> Result<MessagesAround, SlackError> {
+        self.get_messages_around(channel_id, ts)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))
+    }
+
+    async fn threads(
+        &self,
+        channel_id: &str,
+        messages: &[SlackHistoryMessage],
+    ) -> Result<Vec<ThreadInfo>, SlackError> {
+        self.get_threads_batch(channel_id, messages)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))
+    }
+
+    async fn permalink(&self, channel_id: &str, ts: &str) -> Result<String, SlackError> {
+        self.get_permalink(channel_id, ts)
+            .await
+            .map_err(|e| SlackError::ApiError(e.to_string()))
+    }
+}
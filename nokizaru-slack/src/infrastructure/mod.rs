@@ -1,3 +1,7 @@
+pub mod channel_access;
+pub mod message_search;
 pub mod signature;
+pub mod workspace_fixture;
 
 pub use signature::*;
+pub use workspace_fixture::*;
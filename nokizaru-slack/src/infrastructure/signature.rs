@@ -44,7 +44,9 @@ mod tests {
     #[test]
     fn test_verify_slack_signature() {
         let signing_secret = "test_secret";
-        let timestamp = "1234567890";
+        // 5分より古いタイムスタンプはリプレイ攻撃とみなして拒否されるため、
+        // 固定値（1234567890 など）ではなく現在時刻で署名する
+        let timestamp = &chrono::Utc::now().timestamp().to_string();
         let body = r#"{"type":"event_callback"}"#;
 
         // 正しい署名を計算
@@ -0,0 +1,361 @@
+//! 記録したワークスペース
+//!
+//! チャンネルの履歴とスレッドを JSON に保存しておき、Slack API の代わりに検索に使います。
+//! オフライン評価（`nokizaru-eval`）で、同じデータに対する検索・回答の品質を比較するためのものです。
+//!
+//! 検索は search.messages を近似し、すべての語を含むメッセージ（スレッドの返信を含む）を返します。
+//! 修飾子は `in:#channel` のみ解釈し、`from:` / `after:` などは無視します。
+
+use std::{cmp::Ordering, path::Path};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::slack_api::{
+    ChannelInfo, MessagesAround, SlackApi, SlackHistoryMessage, SlackMessage, ThreadInfo,
+};
+use crate::{MessageSearchRepository, SlackError};
+
+/// 前後に含めるメッセージ数（Slack API と同じ）
+const AROUND_LIMIT: usize = 3;
+
+/// 無視する検索修飾子
+const IGNORED_MODIFIERS: &[&str] = &[
+    "from", "to", "before", "after", "on", "during", "has", "is", "with",
+];
+
+/// 記録したワークスペース
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceFixture {
+    pub channels: Vec<FixtureChannel>,
+}
+
+/// 記録したチャンネル
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureChannel {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub messages: Vec<FixtureMessage>,
+}
+
+/// 記録したメッセージ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureMessage {
+    pub ts: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub text: String,
+    /// スレッドの返信（親メッセージは含まない）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<FixtureMessage>,
+}
+
+impl FixtureMessage {
+    fn to_history(&self) -> SlackHistoryMessage {
+        SlackHistoryMessage {
+            msg_type: "message".to_string(),
+            user: self.user.clone(),
+            bot_id: None,
+            text: self.text.clone(),
+            ts: self.ts.clone(),
+            reply_count: self.replies.len() as u32,
+        }
+    }
+}
+
+/// 検索クエリ（語と `in:` の指定）
+#[derive(Debug, Default, PartialEq)]
+struct FixtureQuery {
+    terms: Vec<String>,
+    channel: Option<String>,
+}
+
+impl FixtureQuery {
+    fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+        // `"..."` で囲んだ部分は1語として扱う
+        for (i, part) in query.split('"').enumerate() {
+            if i % 2 == 1 {
+                if !part.trim().is_empty() {
+                    parsed.terms.push(part.trim().to_lowercase());
+                }
+                continue;
+            }
+            for word in part.split_whitespace() {
+                match word.split_once(':') {
+                    Some(("in", channel)) => parsed.channel = Some(Self::channel(channel)),
+                    Some((modifier, _)) if IGNORED_MODIFIERS.contains(&modifier) => {}
+                    _ => parsed.terms.push(word.to_lowercase()),
+                }
+            }
+        }
+        parsed
+    }
+
+    /// `#general` / `<#C0123|general>` / `<#C0123>` からチャンネル名またはIDを取り出す
+    fn channel(channel: &str) -> String {
+        let channel = channel.trim_start_matches("<#").trim_end_matches('>');
+        match channel.split_once('|') {
+            Some((_, name)) if !name.is_empty() => name.to_string(),
+            Some((id, _)) => id.to_string(),
+            None => channel.trim_start_matches('#').to_string(),
+        }
+    }
+
+    /// 語の出現回数（すべての語を含まない場合は None）
+    fn score(&self, text: &str) -> Option<usize> {
+        let text = text.to_lowercase();
+        self.terms.iter().try_fold(0, |score, term| {
+            match text.matches(term.as_str()).count() {
+                0 => None,
+                n => Some(score + n),
+            }
+        })
+    }
+}
+
+/// Slack の ts を比較する（数値として比較できない場合は文字列で比較する）
+fn compare_ts(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+impl WorkspaceFixture {
+    /// JSON ファイルから読み込む
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse fixture: {}", path.display()))
+    }
+
+    /// Slack から指定したチャンネルの直近のメッセージとスレッドを記録する
+    pub async fn record(api: &SlackApi, channel_ids: &[String], limit: u32) -> Result<Self> {
+        let mut channels = Vec::new();
+        for channel_id in channel_ids {
+            let info = api
+                .get_channel_info(channel_id)
+                .await
+                .with_context(|| format!("Failed to get channel info: {}", channel_id))?;
+            let history = api
+                .get_channel_history(channel_id, Some(limit))
+                .await
+                .with_context(|| format!("Failed to get history: {}", channel_id))?;
+
+            let mut messages = Vec::new();
+            for msg in history {
+                let replies = match api.get_thread_messages(channel_id, &msg.ts).await {
+                    Ok(replies) => replies
+                        .into_iter()
+                        .filter(|reply| reply.ts != msg.ts)
+                        .map(|reply| FixtureMessage {
+                            ts: reply.ts,
+                            user: reply.user,
+                            text: reply.text,
+                            replies: Vec::new(),
+                        })
+                        .collect(),
+                    Err(e) => {
+                        tracing::warn!("Failed to get thread {}/{}: {}", channel_id, msg.ts, e);
+                        Vec::new()
+                    }
+                };
+                messages.push(FixtureMessage {
+                    ts: msg.ts,
+                    user: msg.user,
+                    text: msg.text,
+                    replies,
+                });
+            }
+            messages.sort_by(|a, b| compare_ts(&a.ts, &b.ts));
+
+            channels.push(FixtureChannel {
+                id: info.id,
+                name: info.name,
+                messages,
+            });
+        }
+
+        Ok(Self { channels })
+    }
+
+    fn channel(&self, channel_id: &str) -> Result<&FixtureChannel, SlackError> {
+        self.channels
+            .iter()
+            .find(|channel| channel.id == channel_id)
+            .ok_or_else(|| SlackError::ApiError("channel_not_found".to_string()))
+    }
+
+    fn permalink_for(channel_id: &str, ts: &str) -> String {
+        format!(
+            "https://fixture.slack.com/archives/{}/p{}",
+            channel_id,
+            ts.replace('.', "")
+        )
+    }
+
+    /// 検索（すべての語を含むメッセージを `sort` の順に最大 `count` 件）
+    pub fn search(&self, query: &str, count: usize, sort: &str) -> Vec<SlackMessage> {
+        let query = FixtureQuery::parse(query);
+        let mut hits: Vec<(usize, &FixtureChannel, &FixtureMessage)> = Vec::new();
+        for channel in &self.channels {
+            if let Some(filter) = &query.channel {
+                if filter != &channel.name && filter != &channel.id {
+                    continue;
+                }
+            }
+            let messages = channel
+                .messages
+                .iter()
+                .flat_map(|msg| std::iter::once(msg).chain(&msg.replies));
+            for msg in messages {
+                if let Some(score) = query.score(&msg.text) {
+                    hits.push((score, channel, msg));
+                }
+            }
+        }
+
+        let newest_first = |a: &FixtureMessage, b: &FixtureMessage| compare_ts(&b.ts, &a.ts);
+        if sort == "timestamp" {
+            hits.sort_by(|a, b| newest_first(a.2, b.2));
+        } else {
+            hits.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| newest_first(a.2, b.2)));
+        }
+
+        hits.into_iter()
+            .take(count)
+            .map(|(_, channel, msg)| SlackMessage {
+                msg_type: "message".to_string(),
+                user: msg.user.clone(),
+                bot_id: None,
+                text: msg.text.clone(),
+                ts: msg.ts.clone(),
+                channel: Some(ChannelInfo {
+                    id: Some(channel.id.clone()),
+                    name: Some(channel.name.clone()),
+                }),
+                username: None,
+                permalink: Some(Self::permalink_for(&channel.id, &msg.ts)),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl MessageSearchRepository for WorkspaceFixture {
+    async fn search_messages(
+        &self,
+        query: &str,
+        count: u32,
+        sort: &str,
+    ) -> Result<Vec<SlackMessage>, SlackError> {
+        Ok(self.search(query, count as usize, sort))
+    }
+
+    async fn messages_around(
+        &self,
+        channel_id: &str,
+        ts: &str,
+    ) -> Result<MessagesAround, SlackError> {
+        let messages = &self.channel(channel_id)?.messages;
+        let split = messages.partition_point(|msg| compare_ts(&msg.ts, ts) == Ordering::Less);
+        let after_start = messages.partition_point(|msg| compare_ts(&msg.ts, ts) != Ordering::Greater);
+
+        Ok(MessagesAround {
+            before: messages[split.saturating_sub(AROUND_LIMIT)..split]
+                .iter()
+                .map(FixtureMessage::to_history)
+                .collect(),
+            after: messages[after_start..]
+                .iter()
+                .take(AROUND_LIMIT)
+                .map(FixtureMessage::to_history)
+                .collect(),
+        })
+    }
+
+    async fn threads(
+        &self,
+        channel_id: &str,
+        messages: &[SlackHistoryMessage],
+    ) -> Result<Vec<ThreadInfo>, SlackError> {
+        let channel = self.channel(channel_id)?;
+        Ok(messages
+            .iter()
+            .filter_map(|msg| channel.messages.iter().find(|m| m.ts == msg.ts))
+            .filter(|parent| !parent.replies.is_empty())
+            .map(|parent| {
+                // conversations.replies と同じく親メッセージを先頭に含める
+                let replies: Vec<SlackHistoryMessage> = std::iter::once(parent)
+                    .chain(&parent.replies)
+                    .map(FixtureMessage::to_history)
+                    .collect();
+                ThreadInfo {
+                    thread_ts: parent.ts.clone(),
+                    message_ts: parent.ts.clone(),
+                    reply_count: replies.len(),
+                    replies,
+                }
+            })
+            .collect())
+    }
+
+    async fn permalink(&self, channel_id: &str, ts: &str) -> Result<String, SlackError> {
+        Ok(Self::permalink_for(channel_id, ts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_search_and_context() {
+        let fixture: WorkspaceFixture = serde_json::from_value(serde_json::json!({
+            "channels": [
+                {"id": "C1", "name": "general", "messages": [
+                    {"ts": "100.0", "user": "U1", "text": "デプロイ手順を更新しました"},
+                    {"ts": "200.0", "user": "U2", "text": "deploy は金曜に行います", "replies": [
+                        {"ts": "201.0", "user": "U1", "text": "Deploy の担当は山田さんです"}
+                    ]},
+                    {"ts": "300.0", "user": "U3", "text": "ランチどこ行く？"}
+                ]},
+                {"id": "C2", "name": "random", "messages": [
+                    {"ts": "150.0", "user": "U1", "text": "deploy deploy"}
+                ]}
+            ]
+        }))
+        .unwrap();
+
+        let ids = |hits: Vec<SlackMessage>| hits.into_iter().map(|m| m.ts).collect::<Vec<_>>();
+        assert_eq!(
+            ids(fixture.search("deploy", 10, "score")),
+            ["150.0", "201.0", "200.0"]
+        );
+        assert_eq!(
+            ids(fixture.search("deploy", 10, "timestamp")),
+            ["201.0", "200.0", "150.0"]
+        );
+        assert_eq!(
+            ids(fixture.search("deploy 担当 in:#general", 10, "score")),
+            ["201.0"]
+        );
+        assert!(fixture.search("\"deploy は月曜\"", 10, "score").is_empty());
+
+        let around = fixture.messages_around("C1", "200.0").await.unwrap();
+        assert_eq!(around.before.len(), 1);
+        assert_eq!(around.after[0].ts, "300.0");
+
+        let parents = around.before.iter().chain(&around.after).cloned();
+        let mut messages: Vec<SlackHistoryMessage> = parents.collect();
+        messages.push(FixtureMessage::to_history(&fixture.channels[0].messages[1]));
+        let threads = fixture.threads("C1", &messages).await.unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].replies.len(), 2);
+    }
+}
@@ -0,0 +1,22 @@
+use serde::Deserialize;
+
+use crate::slack_api::{client::ClientResult, SlackApi};
+
+/// auth.test レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthTestResponse {
+    /// ワークスペースURL（例: https://example.slack.com/）
+    pub url: String,
+    pub team_id: String,
+    /// トークンに紐づくユーザーID（bot token の場合はボットのユーザーID）
+    pub user_id: String,
+    #[serde(default)]
+    pub bot_id: Option<String>,
+}
+
+impl SlackApi {
+    /// トークンの認証情報を取得
+    pub async fn auth_test(&self) -> ClientResult<AuthTestResponse> {
+        self.client.http_get("auth.test", &[]).await
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+/// Block Kit ブロック
+///
+/// chat.postMessage や response_url に渡すメッセージレイアウト、およびモーダルの内容です。
+/// 使用しているブロックのみ定義しています。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Section {
+        text: TextObject,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        block_id: Option<String>,
+    },
+    Actions {
+        elements: Vec<BlockElement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        block_id: Option<String>,
+    },
+    Context {
+        elements: Vec<TextObject>,
+    },
+    Divider {},
+    /// モーダルの入力欄
+    Input {
+        block_id: String,
+        label: TextObject,
+        element: BlockElement,
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+/// Block Kit テキストオブジェクト
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TextObject {
+    #[serde(rename = "mrkdwn")]
+    Mrkdwn { text: String },
+    #[serde(rename = "plain_text")]
+    PlainText {
+        text: String,
+        #[serde(default)]
+        emoji: bool,
+    },
+}
+
+/// Block Kit インタラクティブ要素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlockElement {
+    Button {
+        text: TextObject,
+        action_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<String>,
+    },
+    StaticSelect {
+        action_id: String,
+        options: Vec<OptionObject>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        placeholder: Option<TextObject>,
+    },
+    PlainTextInput {
+        action_id: String,
+        #[serde(default)]
+        multiline: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_length: Option<usize>,
+    },
+}
+
+/// Block Kit 選択肢オブジェクト
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionObject {
+    pub text: TextObject,
+    pub value: String,
+}
+
+impl Block {
+    /// mrkdwn テキストのセクション
+    pub fn section(text: impl Into<String>) -> Self {
+        Block::Section {
+            text: TextObject::mrkdwn(text),
+            block_id: None,
+        }
+    }
+
+    /// ボタンを並べたアクションブロック
+    pub fn actions(elements: Vec<BlockElement>) -> Self {
+        Block::Actions {
+            elements,
+            block_id: None,
+        }
+    }
+
+    /// モーダルの入力欄（`block_id` と要素の `action_id` は同じにする）
+    pub fn input(label: impl Into<String>, element: BlockElement, optional: bool) -> Self {
+        Block::Input {
+            block_id: element.action_id().to_string(),
+            label: TextObject::plain(label),
+            element,
+            optional,
+        }
+    }
+
+    /// mrkdwn テキストのコンテキストブロック
+    pub fn context(texts: Vec<String>) -> Self {
+        Block::Context {
+            elements: texts.into_iter().map(TextObject::mrkdwn).collect(),
+        }
+    }
+}
+
+impl TextObject {
+    pub fn mrkdwn(text: impl Into<String>) -> Self {
+        TextObject::Mrkdwn { text: text.into() }
+    }
+
+    pub fn plain(text: impl Into<String>) -> Self {
+        TextObject::PlainText {
+            text: text.into(),
+            emoji: true,
+        }
+    }
+}
+
+impl BlockElement {
+    /// ボタン要素
+    pub fn button(
+        text: impl Into<String>,
+        action_id: impl Into<String>,
+        value: Option<String>,
+    ) -> Self {
+        BlockElement::Button {
+            text: TextObject::plain(text),
+            action_id: action_id.into(),
+            value,
+            style: None,
+        }
+    }
+
+    /// primary スタイルのボタン要素
+    pub fn primary_button(
+        text: impl Into<String>,
+        action_id: impl Into<String>,
+        value: Option<String>,
+    ) -> Self {
+        BlockElement::Button {
+            text: TextObject::plain(text),
+            action_id: action_id.into(),
+            value,
+            style: Some("primary".to_string()),
+        }
+    }
+
+    /// 選択肢（テキスト, 値）から1つ選ぶセレクトメニュー
+    pub fn static_select(action_id: impl Into<String>, options: Vec<(String, String)>) -> Self {
+        BlockElement::StaticSelect {
+            action_id: action_id.into(),
+            options: options
+                .into_iter()
+                .map(|(text, value)| OptionObject {
+                    text: TextObject::plain(text),
+                    value,
+                })
+                .collect(),
+            placeholder: None,
+        }
+    }
+
+    /// 複数行のテキスト入力欄
+    pub fn multiline_input(action_id: impl Into<String>, max_length: usize) -> Self {
+        BlockElement::PlainTextInput {
+            action_id: action_id.into(),
+            multiline: true,
+            max_length: Some(max_length),
+        }
+    }
+
+    /// 要素の action_id
+    pub fn action_id(&self) -> &str {
+        match self {
+            BlockElement::Button { action_id, .. }
+            | BlockElement::StaticSelect { action_id, .. }
+            | BlockElement::PlainTextInput { action_id, .. } => action_id,
+        }
+    }
+}
@@ -1,14 +1,17 @@
 use serde::{Deserialize, Serialize};
 
-use crate::slack_api::{SlackApi, client::ClientResult};
+use crate::slack_api::{SlackApi, client::ClientResult, Block};
 
 /// chat.postMessage リクエスト
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostMessageRequest {
+    #[serde(rename = "channel")]
     pub channel_id: String,
     pub text: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thread_ts: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<Block>>,
 }
 
 /// chat.update リクエスト
@@ -35,6 +38,13 @@ pub struct UpdateMessageResponse {
     pub text: String,
 }
 
+/// chat.getPermalink レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct GetPermalinkResponse {
+    pub channel: String,
+    pub permalink: String,
+}
+
 /// chat.delete リクエスト
 #[derive(Debug, Clone, Serialize)]
 pub struct DeleteMessageRequest {
@@ -74,6 +84,19 @@ impl SlackApi {
         self.client.http_post("chat.update", &request).await
     }
 
+    /// メッセージのパーマリンクを取得
+    pub async fn get_permalink(&self, channel: &str, message_ts: &str) -> ClientResult<String> {
+        let params = [
+            ("channel", channel.to_string()),
+            ("message_ts", message_ts.to_string()),
+        ];
+
+        let response: GetPermalinkResponse =
+            self.client.http_get("chat.getPermalink", &params).await?;
+
+        Ok(response.permalink)
+    }
+
     /// メッセージを削除
     pub async fn delete_message(
         &self,
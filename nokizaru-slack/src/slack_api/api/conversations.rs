@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::slack_api::{SlackApi, client::{ClientResult}};
+use crate::slack_api::{SlackApi, ResponseMetadata, client::{ClientResult}};
+
+/// 履歴をページングして取得する際の1ページの件数
+const HISTORY_PAGE_SIZE: u32 = 200;
 
 /// conversations.history レスポンス
 #[derive(Debug, Clone, Deserialize)]
@@ -9,7 +12,7 @@ pub struct ConversationsHistoryResponse {
     #[serde(default)]
     pub has_more: bool,
     #[serde(default)]
-    pub response_metadata: Option<serde_json::Value>,
+    pub response_metadata: Option<ResponseMetadata>,
 }
 
 /// Slack チャンネル履歴メッセージ
@@ -28,23 +31,77 @@ pub struct SlackHistoryMessage {
     pub text: String,
     /// タイムスタンプ
     pub ts: String,
+    /// スレッドの返信数（スレッドの親メッセージのみ）
+    #[serde(default)]
+    pub reply_count: u32,
 }
 
 /// Slack チャンネル情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackChannel {
     pub id: String,
+    /// チャンネル名（DM にはない）
+    #[serde(default)]
     pub name: String,
     #[serde(default)]
     pub is_private: bool,
 }
 
+/// チャンネルの説明（トピック・目的）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackChannelTopic {
+    pub value: String,
+}
+
+/// チャンネル詳細
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackChannelInfo {
+    pub id: String,
+    /// チャンネル名（DM にはない）
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub is_private: bool,
+    /// ダイレクトメッセージ
+    #[serde(default)]
+    pub is_im: bool,
+    /// グループダイレクトメッセージ
+    #[serde(default)]
+    pub is_mpim: bool,
+    #[serde(default)]
+    pub is_archived: bool,
+    #[serde(default)]
+    pub topic: Option<SlackChannelTopic>,
+    #[serde(default)]
+    pub purpose: Option<SlackChannelTopic>,
+    #[serde(default)]
+    pub num_members: Option<u32>,
+}
+
+/// conversations.info レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConversationsInfoResponse {
+    pub channel: SlackChannelInfo,
+}
+
 /// conversations.list レスポンス
 #[derive(Debug, Clone, Deserialize)]
 pub struct ConversationsListResponse {
     pub channels: Vec<SlackChannel>,
 }
 
+/// conversations.open リクエスト
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationsOpenRequest {
+    pub users: String,
+}
+
+/// conversations.open レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConversationsOpenResponse {
+    pub channel: SlackChannel,
+}
+
 /// 特定メッセージの前後のメッセージ
 #[derive(Debug, Clone)]
 pub struct MessagesAround {
@@ -99,6 +156,90 @@ impl SlackApi {
         Ok(response.messages)
     }
 
+    /// `oldest` 以降のチャンネル履歴を古い順に取得（ページングして新しいものから最大 `max` 件）
+    ///
+    /// スレッドの返信は含みません（親メッセージの `reply_count` を参照）。
+    pub async fn get_channel_history_since(
+        &self,
+        channel: &str,
+        oldest: &str,
+        max: usize,
+    ) -> ClientResult<Vec<SlackHistoryMessage>> {
+        let mut messages = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let params = [
+                ("channel", channel.to_string()),
+                ("oldest", oldest.to_string()),
+                ("limit", HISTORY_PAGE_SIZE.to_string()),
+                ("cursor", cursor.clone()),
+            ];
+
+            let response: ConversationsHistoryResponse = self
+                .client
+                .http_get("conversations.history", &params)
+                .await?;
+            messages.extend(response.messages);
+
+            cursor = response.response_metadata.unwrap_or_default().next_cursor;
+            if cursor.is_empty() || messages.len() >= max {
+                break;
+            }
+        }
+
+        // 新しい順に返るため、上限を超えた古いメッセージを除いてから古い順に並べる
+        messages.truncate(max);
+        messages.reverse();
+        Ok(messages)
+    }
+
+    /// スレッドのメッセージ（親メッセージを含む）を古い順に取得（ページングして最大 `max` 件）
+    pub async fn get_all_thread_messages(
+        &self,
+        channel: &str,
+        thread_ts: &str,
+        max: usize,
+    ) -> ClientResult<Vec<SlackHistoryMessage>> {
+        let mut messages = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let params = [
+                ("channel", channel.to_string()),
+                ("ts", thread_ts.to_string()),
+                ("limit", HISTORY_PAGE_SIZE.to_string()),
+                ("cursor", cursor.clone()),
+            ];
+
+            let response: ConversationsHistoryResponse = self
+                .client
+                .http_get("conversations.replies", &params)
+                .await?;
+            messages.extend(response.messages);
+
+            cursor = response.response_metadata.unwrap_or_default().next_cursor;
+            if cursor.is_empty() || messages.len() >= max {
+                break;
+            }
+        }
+
+        messages.truncate(max);
+        Ok(messages)
+    }
+
+    /// ユーザーとボットの DM を開き、会話IDを返す
+    pub async fn open_direct_message(&self, user_id: &str) -> ClientResult<String> {
+        let request = ConversationsOpenRequest {
+            users: user_id.to_string(),
+        };
+
+        let response: ConversationsOpenResponse = self
+            .client
+            .http_post("conversations.open", &request)
+            .await?;
+
+        Ok(response.channel.id)
+    }
+
     /// チャンネルリスト取得
     pub async fn list_channels(&self) -> ClientResult<Vec<SlackChannel>> {
         let params = [("types", "public_channel,private_channel".to_string())];
@@ -111,6 +252,18 @@ impl SlackApi {
         Ok(response.channels)
     }
 
+    /// チャンネル詳細取得
+    pub async fn get_channel_info(&self, channel: &str) -> ClientResult<SlackChannelInfo> {
+        let params = [("channel", channel.to_string())];
+
+        let response: ConversationsInfoResponse = self
+            .client
+            .http_get("conversations.info", &params)
+            .await?;
+
+        Ok(response.channel)
+    }
+
     /// 特定メッセージの前後を取得（前後3件ずつ）
     pub async fn get_messages_around(
         &self,
@@ -165,7 +318,7 @@ impl SlackApi {
         // スレッドがあるメッセージだけ抽出してタスクを作成
         let thread_tasks: Vec<_> = messages
             .iter()
-            .filter_map(|msg| {
+            .map(|msg| {
                 // thread_ts フィールドまたは reply_count をチェック
                 // SlackHistoryMessage には reply_count がないので、後で拡張が必要かもしれません
                 // 今は thread_ts がある場合のみ処理
@@ -174,7 +327,7 @@ impl SlackApi {
                 let channel = channel.to_string();
                 let api = self.clone();
 
-                Some(async move {
+                async move {
                     let replies = api.get_thread_messages(&channel, &thread_ts).await?;
 
                     Ok::<ThreadInfo, crate::slack_api::error::SlackError>(ThreadInfo {
@@ -183,7 +336,7 @@ impl SlackApi {
                         reply_count: replies.len(),
                         replies,
                     })
-                })
+                }
             })
             .collect();
 
@@ -1,14 +1,23 @@
+#[allow(clippy::module_inception)]
 pub mod api;
+pub mod auth;
+pub mod blocks;
 pub mod chat;
 pub mod conversations;
 pub mod model;
 pub mod reactions;
+pub mod response;
 pub mod search;
 pub mod users;
+pub mod views;
 
 pub use api::SlackApi;
+pub use auth::*;
+pub use blocks::*;
 pub use chat::*;
 pub use conversations::*;
 pub use model::*;
 pub use reactions::*;
+pub use response::*;
 pub use users::*;
+pub use views::*;
@@ -22,6 +22,9 @@ pub struct SlackMessage {
     /// ユーザー名（検索結果用）
     #[serde(default)]
     pub username: Option<String>,
+    /// パーマリンク（検索結果用）
+    #[serde(default)]
+    pub permalink: Option<String>,
 }
 
 /// チャンネル情報（検索結果内）
@@ -0,0 +1,62 @@
+use serde::Serialize;
+
+use crate::slack_api::{client::ClientResult, Block, SlackApi};
+
+/// response_url へ送信するメッセージ
+///
+/// スラッシュコマンドやインタラクションに対する遅延レスポンスに使用します。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResponseUrlMessage {
+    /// "in_channel" または "ephemeral"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_type: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<Vec<Block>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replace_original: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub delete_original: bool,
+}
+
+impl ResponseUrlMessage {
+    /// 実行したユーザーにだけ見えるメッセージ
+    pub fn ephemeral(text: impl Into<String>, blocks: Option<Vec<Block>>) -> Self {
+        Self {
+            response_type: Some("ephemeral".to_string()),
+            text: text.into(),
+            blocks,
+            ..Default::default()
+        }
+    }
+
+    /// チャンネルの全員に見えるメッセージ
+    pub fn in_channel(text: impl Into<String>, blocks: Option<Vec<Block>>) -> Self {
+        Self {
+            response_type: Some("in_channel".to_string()),
+            text: text.into(),
+            blocks,
+            ..Default::default()
+        }
+    }
+
+    /// 元のメッセージを削除する
+    pub fn delete_original() -> Self {
+        Self {
+            delete_original: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl SlackApi {
+    /// response_url へメッセージを送信
+    pub async fn respond(
+        &self,
+        response_url: &str,
+        message: &ResponseUrlMessage,
+    ) -> ClientResult<()> {
+        self.client.http_post_url(response_url, message).await
+    }
+}
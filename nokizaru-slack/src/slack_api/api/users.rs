@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::slack_api::{SlackApi, client::ClientResult};
+use crate::slack_api::{SlackApi, SlackChannel, client::ClientResult};
 
 /// ユーザー情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +10,39 @@ pub struct SlackUser {
     pub real_name: Option<String>,
     #[serde(default)]
     pub is_bot: bool,
+    #[serde(default)]
+    pub profile: Option<SlackUserProfile>,
+}
+
+/// ユーザーのプロフィール
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackUserProfile {
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// 役職
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// users.info レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct UsersInfoResponse {
+    pub user: SlackUser,
+}
+
+/// users.conversations レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct UsersConversationsResponse {
+    pub channels: Vec<SlackChannel>,
+    #[serde(default)]
+    pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// ページングの情報
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResponseMetadata {
+    #[serde(default)]
+    pub next_cursor: String,
 }
 
 /// users.list レスポンス
@@ -28,4 +61,44 @@ impl SlackApi {
 
         Ok(response.members)
     }
+
+    /// ユーザー情報取得
+    pub async fn get_user_info(&self, user_id: &str) -> ClientResult<SlackUser> {
+        let params = [("user", user_id.to_string())];
+
+        let response: UsersInfoResponse = self
+            .client
+            .http_get("users.info", &params)
+            .await?;
+
+        Ok(response.user)
+    }
+
+    /// ユーザーが参加している会話（チャンネル・プライベートチャンネル・DM・グループDM）
+    ///
+    /// プライベートな会話は、トークンの所有者も参加しているものだけが返ります。
+    pub async fn get_user_conversations(&self, user_id: &str) -> ClientResult<Vec<SlackChannel>> {
+        let mut channels = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let params = [
+                ("user", user_id.to_string()),
+                ("types", "public_channel,private_channel,mpim,im".to_string()),
+                ("exclude_archived", "true".to_string()),
+                ("limit", "1000".to_string()),
+                ("cursor", cursor.clone()),
+            ];
+
+            let response: UsersConversationsResponse = self
+                .client
+                .http_get("users.conversations", &params)
+                .await?;
+            channels.extend(response.channels);
+
+            cursor = response.response_metadata.unwrap_or_default().next_cursor;
+            if cursor.is_empty() {
+                return Ok(channels);
+            }
+        }
+    }
 }
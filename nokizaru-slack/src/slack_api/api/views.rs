@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::slack_api::{client::ClientResult, Block, SlackApi, TextObject};
+
+/// モーダル（views.open に渡す view）
+#[derive(Debug, Clone, Serialize)]
+pub struct View {
+    #[serde(rename = "type")]
+    pub view_type: String,
+    pub callback_id: String,
+    pub title: TextObject,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit: Option<TextObject>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close: Option<TextObject>,
+    /// view_submission でそのまま返される値
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub private_metadata: String,
+    pub blocks: Vec<Block>,
+}
+
+impl View {
+    /// 送信ボタン付きのモーダル
+    pub fn modal(
+        callback_id: impl Into<String>,
+        title: impl Into<String>,
+        submit: impl Into<String>,
+        blocks: Vec<Block>,
+    ) -> Self {
+        Self {
+            view_type: "modal".to_string(),
+            callback_id: callback_id.into(),
+            title: TextObject::plain(title),
+            submit: Some(TextObject::plain(submit)),
+            close: None,
+            private_metadata: String::new(),
+            blocks,
+        }
+    }
+}
+
+/// views.open リクエスト
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenViewRequest<'a> {
+    pub trigger_id: &'a str,
+    pub view: &'a View,
+}
+
+/// views.open レスポンス
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenViewResponse {
+    pub ok: bool,
+}
+
+impl SlackApi {
+    /// モーダルを開く
+    ///
+    /// `trigger_id` はボタン操作などから3秒以内に使う必要があります。
+    pub async fn open_view(&self, trigger_id: &str, view: &View) -> ClientResult<OpenViewResponse> {
+        let request = OpenViewRequest { trigger_id, view };
+
+        self.client.http_post("views.open", &request).await
+    }
+}
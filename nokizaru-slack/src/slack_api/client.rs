@@ -101,4 +101,24 @@ impl SlackHttpClient {
             SlackError::ParseError(e)
         })
     }
+
+    /// 任意の URL への POST（response_url 用）
+    ///
+    /// response_url はトークン不要で、レスポンスも Web API 形式ではないため
+    /// HTTP ステータスのみを確認します。
+    pub async fn http_post_url<RQ>(&self, url: &str, request: &RQ) -> ClientResult<()>
+    where
+        RQ: Serialize,
+    {
+        let response = self.http_client.post(url).json(request).send().await?;
+
+        if !response.status().is_success() {
+            return Err(SlackError::ApiError(format!(
+                "HTTP {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
 }